@@ -1,36 +1,106 @@
-use crate::config::{self, Connection};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use crate::config::{self, credentials, Connection};
+use crate::config::connection_string::{self, ConnectionStringDialect, ImportedConnection};
+use crate::db::{self, ConnectErrorKind};
 use uuid::Uuid;
 
-#[tauri::command]
-pub async fn test_connection(
-    server: String,
-    database: String,
-    auth_type: String,
-    username: Option<String>,
-    password: Option<String>,
-) -> Result<String, String> {
-    // TODO: Implement actual SQL Server connection test using tiberius
-    // For now, return success for valid-looking inputs
-    
-    if server.is_empty() {
-        return Err("Server cannot be empty".to_string());
+/// Outcome of a connection test, including what was learned from the server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionTestResult {
+    pub success: bool,
+    pub message: String,
+    pub latency_ms: u64,
+    pub server_version: Option<String>,
+    pub database_name: Option<String>,
+    pub login_name: Option<String>,
+    pub error_category: Option<ConnectErrorKind>,
+}
+
+impl ConnectionTestResult {
+    fn failure(kind: ConnectErrorKind, message: String, latency_ms: u64) -> Self {
+        Self {
+            success: false,
+            message,
+            latency_ms,
+            server_version: None,
+            database_name: None,
+            login_name: None,
+            error_category: Some(kind),
+        }
     }
-    
-    if database.is_empty() {
-        return Err("Database cannot be empty".to_string());
+}
+
+/// Open a real session against the server and run a trivial probe query.
+/// When no password is given, the one saved for the connection is used.
+/// Failures are reported in the result rather than as an `Err`, so the UI can show the category.
+#[tauri::command]
+pub async fn test_connection(connection: Connection, password: Option<String>) -> Result<ConnectionTestResult, String> {
+    if connection.provider.is_local() {
+        if connection.file_path.as_ref().map(|p| p.trim().is_empty()).unwrap_or(true) {
+            return Err("Path cannot be empty".to_string());
+        }
+    } else {
+        if connection.server.is_empty() {
+            return Err("Server cannot be empty".to_string());
+        }
+        
+        if connection.database.is_empty() {
+            return Err("Database cannot be empty".to_string());
+        }
     }
     
-    if auth_type == "sql" {
-        if username.is_none() || username.as_ref().map(|u| u.is_empty()).unwrap_or(true) {
-            return Err("Username is required for SQL authentication".to_string());
+    let mut password = password.filter(|p| !p.is_empty());
+    match connection.auth_type.as_str() {
+        "sql" => {
+            if connection.username.as_ref().map(|u| u.is_empty()).unwrap_or(true) {
+                return Err("Username is required for SQL authentication".to_string());
+            }
+            // A saved connection is tested with its saved password unless another one is typed
+            if password.is_none() && !connection.id.is_empty() {
+                password = load_saved_password(&connection.id).await
+                    .unwrap_or_else(|e| {
+                        eprintln!("Warning: Failed to read saved password: {}", e);
+                        None
+                    });
+            }
+            if password.is_none() {
+                return Err("Password is required for SQL authentication".to_string());
+            }
         }
-        if password.is_none() || password.as_ref().map(|p| p.is_empty()).unwrap_or(true) {
-            return Err("Password is required for SQL authentication".to_string());
+        "azureToken" => {
+            let is_set = |value: &Option<String>| value.as_ref().map(|v| !v.trim().is_empty()).unwrap_or(false);
+            match (is_set(&connection.access_token_file), is_set(&connection.access_token_command)) {
+                (false, false) => return Err("An access token file or token command is required for Azure AD authentication".to_string()),
+                (true, true) => return Err("Set either an access token file or a token command, not both".to_string()),
+                _ => {}
+            }
         }
+        _ => {}
     }
     
-    // Placeholder: In Phase 1, we'll implement actual connection testing
-    Ok(format!("Connection test successful: {}\\{}", server, database))
+    let started = Instant::now();
+    let elapsed_ms = || started.elapsed().as_millis() as u64;
+    
+    let probe = match db::probe(&connection, password.as_deref()).await {
+        Ok(probe) => probe,
+        Err(e) => return Ok(ConnectionTestResult::failure(e.kind, e.message, elapsed_ms())),
+    };
+    
+    Ok(ConnectionTestResult {
+        success: true,
+        message: if probe.login_name.is_empty() {
+            format!("Connected to {}", probe.database_name)
+        } else {
+            format!("Connected to {} as {}", probe.database_name, probe.login_name)
+        },
+        latency_ms: elapsed_ms(),
+        server_version: Some(probe.server_version),
+        database_name: Some(probe.database_name),
+        login_name: Some(probe.login_name),
+        error_category: None,
+    })
 }
 
 #[tauri::command]
@@ -68,6 +138,11 @@ pub async fn delete_connection(connection_id: String) -> Result<(), String> {
     let after_count = app_config.connections.len();
     println!("Connections before: {}, after: {}", before_count, after_count);
     config::save_config(&app_config)?;
+    
+    let id = connection_id.clone();
+    if let Err(e) = db::run_blocking(move |_| credentials::delete_password(&id)).await.and_then(|r| r) {
+        eprintln!("Warning: Failed to delete saved password: {}", e);
+    }
     println!("Config saved successfully");
     Ok(())
 }
@@ -88,3 +163,53 @@ pub async fn set_default_connection(connection_id: String) -> Result<(), String>
 pub fn generate_connection_id() -> String {
     Uuid::new_v4().to_string()
 }
+
+/// Read a pasted ADO.NET, JDBC or ODBC connection string into an unsaved connection
+#[tauri::command]
+pub fn parse_connection_string(connection_string: String) -> Result<ImportedConnection, String> {
+    connection_string::parse(&connection_string)
+}
+
+/// Render a saved connection as a connection string, with its saved password only when asked for
+#[tauri::command]
+pub async fn export_connection_string(connection_id: String, dialect: ConnectionStringDialect, include_password: bool) -> Result<String, String> {
+    let app_config = config::load_config()?;
+    let connection = app_config.connections.iter()
+        .find(|c| c.id == connection_id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    
+    let password = if include_password && connection.auth_type == "sql" {
+        load_saved_password(&connection_id).await?
+    } else {
+        None
+    };
+    
+    connection_string::render(connection, dialect, password.as_deref())
+}
+
+/// Save the password for a connection in the OS secret store
+#[tauri::command]
+pub async fn save_password(connection_id: String, password: String) -> Result<(), String> {
+    db::run_blocking(move |_| credentials::save_password(&connection_id, &password)).await?
+}
+
+/// Forget the saved password for a connection
+#[tauri::command]
+pub async fn delete_saved_password(connection_id: String) -> Result<(), String> {
+    db::run_blocking(move |_| credentials::delete_password(&connection_id)).await?
+}
+
+/// Check whether a password is saved for a connection, without revealing it
+#[tauri::command]
+pub async fn has_saved_password(connection_id: String) -> Result<bool, String> {
+    db::run_blocking(move |_| credentials::get_password(&connection_id))
+        .await?
+        .map(|p| p.is_some())
+}
+
+/// Load the saved password for a connection
+pub async fn load_saved_password(connection_id: &str) -> Result<Option<String>, String> {
+    let id = connection_id.to_string();
+    // Secret store access may block on D-Bus or the keychain, so keep it off the async runtime
+    db::run_blocking(move |_| credentials::get_password(&id)).await?
+}
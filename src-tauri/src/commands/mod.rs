@@ -1,3 +1,4 @@
 pub mod connection;
 pub mod schema;
 pub mod clipboard;
+pub mod settings;
@@ -1,6 +1,13 @@
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{LazyLock, Mutex};
+use tokio_util::sync::CancellationToken;
 use crate::config;
-use crate::db::{sqlserver, cache};
+use crate::commands::connection;
+use crate::db::{self, cache};
+use crate::diff::{self, SchemaDiff};
+use crate::scheduler;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -8,6 +15,51 @@ pub struct Table {
     pub schema: String,
     pub name: String,
     pub description: Option<String>,
+    #[serde(default)]
+    pub object_kind: ObjectKind,
+    /// SQL text of a view
+    pub definition: Option<String>,
+    /// Fully qualified object a synonym points to
+    pub synonym_target: Option<String>,
+    /// Extended properties other than the description
+    #[serde(default)]
+    pub extended_properties: BTreeMap<String, String>,
+}
+
+/// What kind of table-like object a `Table` is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ObjectKind {
+    #[default]
+    BaseTable,
+    View,
+    Synonym,
+    /// User-defined table type, as used for table-valued parameters
+    TableType,
+    ExternalTable,
+}
+
+impl ObjectKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ObjectKind::BaseTable => "baseTable",
+            ObjectKind::View => "view",
+            ObjectKind::Synonym => "synonym",
+            ObjectKind::TableType => "tableType",
+            ObjectKind::ExternalTable => "externalTable",
+        }
+    }
+    
+    /// Parse the value written by `as_str`, defaulting to a base table
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "view" => ObjectKind::View,
+            "synonym" => ObjectKind::Synonym,
+            "tableType" => ObjectKind::TableType,
+            "externalTable" => ObjectKind::ExternalTable,
+            _ => ObjectKind::BaseTable,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,28 +69,217 @@ pub struct Column {
     pub table_name: String,
     pub name: String,
     pub data_type: String,
+    /// Type with its declared facets, e.g. `nvarchar(50)` or `decimal(18,4)`
+    #[serde(default)]
+    pub full_type: String,
     pub is_nullable: bool,
     pub is_primary_key: bool,
     pub is_foreign_key: bool,
     pub ordinal_position: i32,
     pub description: Option<String>,
+    /// Extended properties other than the description
+    #[serde(default)]
+    pub extended_properties: BTreeMap<String, String>,
+    /// Declared length in characters (bytes for binary types); -1 means `max`
+    pub max_length: Option<i32>,
+    pub precision: Option<u8>,
+    pub scale: Option<u8>,
+    pub collation: Option<String>,
+    /// Default expression, e.g. `(getdate())`
+    pub default_value: Option<String>,
+    pub identity_seed: Option<i64>,
+    pub identity_increment: Option<i64>,
+    /// Expression of a computed column
+    pub computed_definition: Option<String>,
+    #[serde(default)]
+    pub is_sparse: bool,
+    #[serde(default)]
+    pub is_rowguid: bool,
+    #[serde(default)]
+    pub is_filestream: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ForeignKey {
     pub constraint_name: String,
+    pub parent_schema: String,
     pub parent_table: String,
-    pub parent_column: String,
+    pub referenced_schema: String,
     pub referenced_table: String,
+    /// Column pairs in key order; more than one for composite keys
+    pub columns: Vec<ForeignKeyColumn>,
+    /// `NO_ACTION`, `CASCADE`, `SET_NULL`, `SET_DEFAULT`, or `RESTRICT` on PostgreSQL
+    pub delete_referential_action: String,
+    pub update_referential_action: String,
+    pub is_disabled: bool,
+    pub is_not_trusted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForeignKeyColumn {
+    pub parent_column: String,
     pub referenced_column: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct PrimaryKey {
+    pub table_schema: String,
+    pub table_name: String,
+    pub column_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Index {
+    pub table_schema: String,
+    pub table_name: String,
+    pub name: String,
+    /// `CLUSTERED`, `NONCLUSTERED`, `CLUSTERED COLUMNSTORE`, `NONCLUSTERED COLUMNSTORE`, `XML`, `SPATIAL`, ...
+    /// or the access method on PostgreSQL (`BTREE`, `GIN`, ...) and MySQL (`BTREE`, `FULLTEXT`, ...)
+    pub index_type: String,
+    pub is_unique: bool,
+    pub is_primary_key: bool,
+    pub is_unique_constraint: bool,
+    /// Key columns in key order
+    pub key_columns: Vec<IndexColumn>,
+    /// Non-key columns from the `INCLUDE` clause
+    pub included_columns: Vec<String>,
+    /// `WHERE` predicate of a filtered index
+    pub filter_definition: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IndexColumn {
+    pub name: String,
+    pub is_descending: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UniqueConstraint {
+    pub table_schema: String,
+    pub table_name: String,
+    pub constraint_name: String,
+    pub columns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckConstraint {
+    pub table_schema: String,
+    pub table_name: String,
+    pub constraint_name: String,
+    /// Set for column-level constraints
+    pub column_name: Option<String>,
+    pub definition: String,
+    pub is_disabled: bool,
+    pub is_not_trusted: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DefaultConstraint {
+    pub table_schema: String,
     pub table_name: String,
     pub column_name: String,
+    pub constraint_name: String,
+    pub definition: String,
+}
+
+/// A stored procedure, function or trigger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Routine {
+    pub schema: String,
+    pub name: String,
+    pub routine_kind: RoutineKind,
+    pub description: Option<String>,
+    /// Extended properties other than the description
+    #[serde(default)]
+    pub extended_properties: BTreeMap<String, String>,
+    /// Table or view a trigger is defined on
+    pub parent_schema: Option<String>,
+    pub parent_table: Option<String>,
+    /// Full source text; `None` for CLR or encrypted modules
+    pub definition: Option<String>,
+    /// Parameters in declaration order; a function's return value comes first with `Return` direction
+    pub parameters: Vec<RoutineParameter>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RoutineKind {
+    StoredProcedure,
+    ScalarFunction,
+    TableValuedFunction,
+    Trigger,
+}
+
+impl RoutineKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RoutineKind::StoredProcedure => "storedProcedure",
+            RoutineKind::ScalarFunction => "scalarFunction",
+            RoutineKind::TableValuedFunction => "tableValuedFunction",
+            RoutineKind::Trigger => "trigger",
+        }
+    }
+    
+    /// Parse the value written by `as_str`, defaulting to a stored procedure
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "scalarFunction" => RoutineKind::ScalarFunction,
+            "tableValuedFunction" => RoutineKind::TableValuedFunction,
+            "trigger" => RoutineKind::Trigger,
+            _ => RoutineKind::StoredProcedure,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutineParameter {
+    /// Empty for a function's return value
+    pub name: String,
+    pub data_type: String,
+    pub direction: ParameterDirection,
+    /// Default value as written in the declaration
+    pub default_value: Option<String>,
+    pub ordinal_position: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ParameterDirection {
+    In,
+    Out,
+    InOut,
+    Return,
+}
+
+impl ParameterDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ParameterDirection::In => "in",
+            ParameterDirection::Out => "out",
+            ParameterDirection::InOut => "inOut",
+            ParameterDirection::Return => "return",
+        }
+    }
+    
+    /// Parse the value written by `as_str`, defaulting to an input parameter
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "out" => ParameterDirection::Out,
+            "inOut" => ParameterDirection::InOut,
+            "return" => ParameterDirection::Return,
+            _ => ParameterDirection::In,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -48,11 +289,61 @@ pub struct SchemaData {
     pub columns: Vec<Column>,
     pub foreign_keys: Vec<ForeignKey>,
     pub primary_keys: Vec<PrimaryKey>,
+    pub indexes: Vec<Index>,
+    pub unique_constraints: Vec<UniqueConstraint>,
+    pub check_constraints: Vec<CheckConstraint>,
+    pub default_constraints: Vec<DefaultConstraint>,
+    pub routines: Vec<Routine>,
 }
 
-/// Index schema from SQL Server and cache it locally
+/// A past state of a connection's schema kept in the cache
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaSnapshot {
+    pub id: i64,
+    pub connection_id: String,
+    /// When this schema was first read (UTC, RFC 3339)
+    pub taken_at: String,
+    /// The latest refresh that still found the schema unchanged
+    pub last_seen_at: String,
+    pub server_version: Option<String>,
+    /// SHA-256 of the schema contents, independent of the order the server listed them in
+    pub content_hash: String,
+    pub table_count: u32,
+    pub column_count: u32,
+}
+
+/// Unique constraints are enforced by an index of the same name, so they are derived from it
+pub fn unique_constraints_from_indexes(indexes: &[Index]) -> Vec<UniqueConstraint> {
+    indexes.iter()
+        .filter(|i| i.is_unique_constraint)
+        .map(|i| UniqueConstraint {
+            table_schema: i.table_schema.clone(),
+            table_name: i.table_name.clone(),
+            constraint_name: i.name.clone(),
+            columns: i.key_columns.iter().map(|c| c.name.clone()).collect(),
+        })
+        .collect()
+}
+
+/// Primary key columns in key order, for sources that only read the key's index
+pub fn primary_keys_from_indexes(indexes: &[Index]) -> Vec<PrimaryKey> {
+    indexes.iter()
+        .filter(|i| i.is_primary_key)
+        .flat_map(|i| i.key_columns.iter().map(move |c| PrimaryKey {
+            table_schema: i.table_schema.clone(),
+            table_name: i.table_name.clone(),
+            column_name: c.name.clone(),
+        }))
+        .collect()
+}
+
+/// Index schema from the connection's database and cache it locally.
+/// When no password is given, the one saved for the connection is used; a password that
+/// works is saved for next time.
+/// Set `full` to re-read every object rather than only the changed ones.
 #[tauri::command]
-pub async fn index_schema(connection_id: String, password: Option<String>) -> Result<SchemaData, String> {
+pub async fn index_schema(connection_id: String, password: Option<String>, full: Option<bool>) -> Result<SchemaData, String> {
     let app_config = config::load_config()?;
     
     // Find the connection
@@ -60,42 +351,152 @@ pub async fn index_schema(connection_id: String, password: Option<String>) -> Re
         .find(|c| c.id == connection_id)
         .ok_or_else(|| "Connection not found".to_string())?;
     
+    let supplied_password = password.is_some();
+    let password = match password {
+        Some(p) => Some(p),
+        None if connection.auth_type == "sql" => connection::load_saved_password(&connection_id).await
+            .unwrap_or_else(|e| {
+                eprintln!("Warning: Failed to read saved password: {}", e);
+                None
+            }),
+        None => None,
+    };
+    
     println!("=== INDEX SCHEMA DEBUG ===");
     println!("Connection ID: {}", connection_id);
-    println!("Server: {}", connection.server);
-    println!("Database: {}", connection.database);
+    if connection.provider.is_local() {
+        println!("Path: {}", connection.file_path.as_deref().unwrap_or(""));
+    } else {
+        println!("Server: {}", connection.server);
+        println!("Database: {}", connection.database);
+    }
+    println!("Provider: {:?}", connection.provider);
     println!("Auth Type: {}", connection.auth_type);
-    println!("Password provided: {}", password.is_some());
+    println!("Password provided: {}, available: {}", supplied_password, password.is_some());
     
-    // Try to extract schema from SQL Server
-    let pwd_ref = password.as_deref();
-    let schema = match sqlserver::extract_schema(connection, pwd_ref).await {
-        Ok(s) => {
-            println!("SUCCESS: Extracted {} tables, {} columns", s.tables.len(), s.columns.len());
-            s
-        },
+    // Try to extract schema from the database
+    let result = if connection.auth_type == "sql" && password.is_none() {
+        Err("No saved password for this connection. Please connect with password first.".to_string())
+    } else {
+        refresh_cache(connection, password.as_deref(), full.unwrap_or(false)).await
+    };
+    let schema = match result {
+        Ok(s) => s,
+        Err(e) if e == INDEXING_CANCELLED => return Err(e),
         Err(e) => {
-            println!("ERROR: SQL Server connection failed: {}", e);
+            println!("ERROR: Database connection failed: {}", e);
             
-            // If SQL Server connection fails, check if we have cached data
+            // If the connection fails, check if we have cached data
             if cache::has_cached_schema(&connection_id) {
                 println!("FALLBACK: Using cached schema");
                 return cache::load_cached_schema(&connection_id);
             }
             
             // Return the actual error so the user can see what's wrong
-            return Err(format!("SQL Server connection failed: {}", e));
+            return Err(format!("Database connection failed: {}", e));
         }
     };
     
-    // Cache the schema for offline use
-    if let Err(e) = cache::cache_schema(&schema, &connection_id) {
+    // Remember a password that just proved to work
+    if let (true, Some(pwd)) = (supplied_password, password) {
+        if let Err(e) = connection::save_password(connection_id.clone(), pwd).await {
+            eprintln!("Warning: Failed to save password: {}", e);
+        }
+    }
+    
+    Ok(schema)
+}
+
+/// Read the connection's schema from the database and cache it for offline use. Providers
+/// that report object stamps only re-read what changed since the cached copy, unless `full`
+/// is set. Unlike `index_schema`, failures are returned rather than answered from the cache.
+pub(crate) async fn refresh_cache(connection: &config::Connection, password: Option<&str>, full: bool) -> Result<SchemaData, String> {
+    // The cached copy to refresh incrementally, if its object stamps are known
+    let previous = if full || !cache::has_cached_schema(&connection.id) {
+        None
+    } else {
+        match (cache::load_object_versions(&connection.id), cache::load_cached_schema(&connection.id)) {
+            (Ok(versions), Ok(schema)) if !versions.is_empty() => Some(db::CachedSchema { schema, versions }),
+            _ => None,
+        }
+    };
+    println!("Refresh: {}", if previous.is_some() { "incremental" } else { "full" });
+    
+    let run = IndexingRun::start(&connection.id);
+    let extraction = tokio::select! {
+        result = db::extract_schema(connection, password, previous.as_ref()) => result?,
+        // Dropping the extraction closes its session; nothing has been cached yet
+        _ = run.token.cancelled() => {
+            println!("CANCELLED: Indexing stopped by the user");
+            return Err(INDEXING_CANCELLED.to_string());
+        }
+    };
+    println!("SUCCESS: Extracted {} tables, {} columns", extraction.schema.tables.len(), extraction.schema.columns.len());
+    
+    let db::Extraction { schema, server_version, versions } = extraction;
+    if let Err(e) = cache::cache_schema(&schema, &connection.id, &server_version, &versions) {
         eprintln!("Warning: Failed to cache schema: {}", e);
     }
     
     Ok(schema)
 }
 
+/// Whether an `index_schema` for the connection is in progress
+pub(crate) fn is_indexing(connection_id: &str) -> bool {
+    INDEXING_RUNS.lock().unwrap().contains_key(connection_id)
+}
+
+/// Error returned by an indexing run that `cancel_indexing` stopped
+const INDEXING_CANCELLED: &str = "Indexing cancelled";
+
+/// Cancellation tokens of the indexing runs in progress, by connection id
+static INDEXING_RUNS: LazyLock<Mutex<HashMap<String, (u64, CancellationToken)>>> = LazyLock::new(Default::default);
+
+static NEXT_RUN_ID: AtomicU64 = AtomicU64::new(0);
+
+/// An indexing run that can be cancelled; it is unregistered again when dropped
+struct IndexingRun {
+    connection_id: String,
+    id: u64,
+    token: CancellationToken,
+}
+
+impl IndexingRun {
+    fn start(connection_id: &str) -> Self {
+        let run = Self {
+            connection_id: connection_id.to_string(),
+            id: NEXT_RUN_ID.fetch_add(1, Ordering::Relaxed),
+            token: CancellationToken::new(),
+        };
+        // A newer run for the same connection takes over its slot
+        INDEXING_RUNS.lock().unwrap().insert(run.connection_id.clone(), (run.id, run.token.clone()));
+        run
+    }
+}
+
+impl Drop for IndexingRun {
+    fn drop(&mut self) {
+        let mut runs = INDEXING_RUNS.lock().unwrap();
+        if runs.get(&self.connection_id).is_some_and(|(id, _)| *id == self.id) {
+            runs.remove(&self.connection_id);
+        }
+    }
+}
+
+/// Stop an in-flight `index_schema` for the connection. Returns false if none was running.
+/// The cached schema is left as it was.
+#[tauri::command]
+pub async fn cancel_indexing(connection_id: String) -> Result<bool, String> {
+    let run = INDEXING_RUNS.lock().unwrap().remove(&connection_id);
+    Ok(match run {
+        Some((_, token)) => {
+            token.cancel();
+            true
+        }
+        None => false,
+    })
+}
+
 /// Get schema from cache (fast, offline)
 #[tauri::command]
 pub async fn get_schema(connection_id: Option<String>) -> Result<SchemaData, String> {
@@ -116,12 +517,72 @@ pub async fn get_schema(connection_id: Option<String>) -> Result<SchemaData, Str
         return cache::load_cached_schema(&conn_id);
     }
     
-    // If no cache, return error - we can't connect without password
-    Err("No cached schema found. Please connect with password first.".to_string())
+    // No cache yet - index from the server using the saved credentials
+    index_schema(conn_id, None, None).await
+}
+
+/// Refresh schema - always fetches from the database, using the saved password if none is given.
+/// Set `full` to re-read every object instead of only the changed ones.
+#[tauri::command]
+pub async fn refresh_schema(connection_id: String, password: Option<String>, full: Option<bool>) -> Result<SchemaData, String> {
+    index_schema(connection_id, password, full).await
+}
+
+/// Background refresh status of a connection, and when its schema was last cached
+#[tauri::command]
+pub async fn get_refresh_status(connection_id: String) -> Result<scheduler::RefreshStatus, String> {
+    Ok(scheduler::status(&connection_id))
+}
+
+/// List the schema snapshots kept for a connection, newest first
+#[tauri::command]
+pub async fn list_schema_snapshots(connection_id: String) -> Result<Vec<SchemaSnapshot>, String> {
+    cache::list_snapshots(&connection_id)
+}
+
+/// Get the schema as it was when a snapshot was taken
+#[tauri::command]
+pub async fn get_schema_snapshot(snapshot_id: i64) -> Result<SchemaData, String> {
+    cache::load_snapshot(snapshot_id)
+}
+
+/// Delete old snapshots of a connection by age and/or count, keeping at least the newest.
+/// Returns how many were deleted.
+#[tauri::command]
+pub async fn prune_schema_snapshots(connection_id: String, max_age_days: Option<u32>, max_count: Option<u32>) -> Result<usize, String> {
+    cache::prune_snapshots(&connection_id, max_age_days, max_count)
+}
+
+/// A schema held in the cache, as one side of a comparison
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum SchemaRef {
+    /// The latest schema cached for a connection
+    Connection { connection_id: String },
+    Snapshot { snapshot_id: i64 },
+}
+
+fn load_schema_ref(schema: &SchemaRef) -> Result<SchemaData, String> {
+    match schema {
+        SchemaRef::Connection { connection_id } if cache::has_cached_schema(connection_id) => {
+            cache::load_cached_schema(connection_id)
+        }
+        SchemaRef::Connection { .. } => Err("This connection has not been indexed yet; refresh it first".to_string()),
+        SchemaRef::Snapshot { snapshot_id } => cache::load_snapshot(*snapshot_id),
+    }
+}
+
+/// Compare two cached schemas (snapshots, or connections such as dev and prod), reporting what
+/// changed going from `before` to `after`
+#[tauri::command]
+pub async fn diff_schemas(before: SchemaRef, after: SchemaRef) -> Result<SchemaDiff, String> {
+    let before = load_schema_ref(&before)?;
+    let after = load_schema_ref(&after)?;
+    Ok(diff::diff_schemas(&before, &after))
 }
 
-/// Refresh schema - always fetches from SQL Server
+/// Get stored procedures, functions and triggers from cache
 #[tauri::command]
-pub async fn refresh_schema(connection_id: String, password: Option<String>) -> Result<SchemaData, String> {
-    index_schema(connection_id, password).await
+pub async fn get_routines(connection_id: Option<String>) -> Result<Vec<Routine>, String> {
+    get_schema(connection_id).await.map(|schema| schema.routines)
 }
@@ -0,0 +1,18 @@
+use crate::config::{self, Settings};
+use crate::scheduler;
+
+#[tauri::command]
+pub fn get_settings() -> Result<Settings, String> {
+    Ok(config::load_config()?.settings)
+}
+
+/// Save the settings; the background refresh picks up changes straight away
+#[tauri::command]
+pub fn save_settings(settings: Settings) -> Result<(), String> {
+    let mut app_config = config::load_config()?;
+    app_config.settings = settings;
+    config::save_config(&app_config)?;
+    
+    scheduler::wake(false);
+    Ok(())
+}
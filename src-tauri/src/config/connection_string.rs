@@ -0,0 +1,567 @@
+// SQL Server connection strings in the ADO.NET, JDBC and ODBC dialects.
+// Tiberius' `Config::from_ado_string` and `Config::from_jdbc_string` keep what they parse
+// private and the `connection-string` parser behind them mangles escaped quotes, so the
+// keywords are read here and mapped onto a `Connection` directly.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use super::{Connection, EncryptionMode, Provider};
+use crate::db::sqlserver::parse_server_address;
+
+/// Connection string syntax
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConnectionStringDialect {
+    /// `Server=tcp:host,1433;Database=...` as used by SqlClient
+    AdoNet,
+    /// `jdbc:sqlserver://host:1433;databaseName=...`
+    Jdbc,
+    /// `Driver={ODBC Driver 18 for SQL Server};Server=...`
+    Odbc,
+}
+
+/// A connection read from a connection string
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedConnection {
+    pub connection: Connection,
+    /// Password from the string; kept out of `connection` so it goes to the secret store
+    pub password: Option<String>,
+    pub dialect: ConnectionStringDialect,
+    /// Keywords Glance has no use for, as written (keys lowercased)
+    pub options: BTreeMap<String, String>,
+}
+
+/// Command suggested for Azure AD connections, whose tokens can't be part of the string
+const AZURE_TOKEN_COMMAND: &str = "az account get-access-token --resource https://database.windows.net/";
+
+/// Remove the first of `keys` present, ignoring case and spaces in the keyword
+fn take(pairs: &mut HashMap<String, String>, keys: &[&str]) -> Option<String> {
+    let normalized = |k: &str| k.replace(' ', "").to_lowercase();
+    let key = pairs.keys().find(|k| keys.iter().any(|want| normalized(k) == *want))?.clone();
+    pairs.remove(&key).map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+/// Split `key=value` pairs separated by `;`. Values may be quoted (`'...'` or `"..."`, ADO.NET)
+/// or braced (`{...}`, ODBC and JDBC); the closing character is doubled to escape it.
+/// Keys are lowercased and a repeated key keeps its last value.
+fn parse_pairs(input: &str) -> Result<HashMap<String, String>, String> {
+    let mut pairs = HashMap::new();
+    let mut chars = input.chars().peekable();
+    
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == ';').is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+        
+        let mut key = String::new();
+        loop {
+            match chars.next() {
+                Some('=') => break,
+                Some(';') | None => return Err(format!("Expected '=' after '{}'", key.trim())),
+                Some(c) => key.push(c),
+            }
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        
+        let mut value = String::new();
+        let closer = match chars.peek() {
+            Some('"') => Some('"'),
+            Some('\'') => Some('\''),
+            Some('{') => Some('}'),
+            _ => None,
+        };
+        match closer {
+            Some(closer) => {
+                chars.next();
+                loop {
+                    match chars.next() {
+                        Some(c) if c == closer => {
+                            if chars.next_if_eq(&closer).is_none() {
+                                break;
+                            }
+                            value.push(c);
+                        }
+                        Some(c) => value.push(c),
+                        None => return Err(format!("Unclosed value for '{}'", key.trim())),
+                    }
+                }
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+                if !matches!(chars.next(), Some(';') | None) {
+                    return Err(format!("Expected ';' after the value of '{}'", key.trim()));
+                }
+            }
+            None => {
+                while let Some(c) = chars.next_if(|c| *c != ';') {
+                    value.push(c);
+                }
+                value = value.trim_end().to_string();
+            }
+        }
+        
+        pairs.insert(key.trim().to_lowercase(), value);
+    }
+    
+    Ok(pairs)
+}
+
+/// Split the `host[\instance][:port]` part of a JDBC URL; IPv6 hosts are bracketed
+fn parse_jdbc_address(address: &str) -> Result<(String, Option<String>, Option<u16>), String> {
+    let (host, rest) = match address.strip_prefix('[') {
+        Some(rest) => rest.split_once(']')
+            .ok_or_else(|| format!("Invalid server '{}'", address))?,
+        None => address.split_at(address.find(['\\', ':']).unwrap_or(address.len())),
+    };
+    let (instance, port) = match rest.rsplit_once(':') {
+        Some((instance, port)) => (instance, Some(port)),
+        None => (rest, None),
+    };
+    let port = port
+        .map(|p| p.trim().parse::<u16>().map_err(|_| format!("Invalid port '{}'", p.trim())))
+        .transpose()?;
+    let instance = match instance.strip_prefix('\\') {
+        Some(instance) => Some(instance.trim().to_string()).filter(|i| !i.is_empty()),
+        None if instance.trim().is_empty() => None,
+        None => return Err(format!("Invalid server '{}'", address)),
+    };
+    Ok((host.trim().to_string(), instance, port))
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value.trim().parse().map_err(|_| format!("'{}' is not a valid number", value))
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value.to_lowercase().as_str() {
+        "true" | "yes" | "sspi" => Ok(true),
+        "false" | "no" => Ok(false),
+        _ => Err(format!("'{}' is not a valid boolean", value)),
+    }
+}
+
+/// tiberius only speaks TDS 7.x and validates certificates itself, so neither TDS 8.0 nor an
+/// exact certificate match can be offered for SQL Server
+const STRICT_UNSUPPORTED: &str = "Strict encryption (TDS 8.0) is not supported for SQL Server connections; use Encrypt=Mandatory";
+const PINNED_UNSUPPORTED: &str = "Pinned server certificates are not supported for SQL Server connections; use a CA certificate instead";
+
+/// Map an `Encrypt` value; SqlClient and ODBC 18 spell their modes differently
+fn parse_encryption(value: &str) -> Result<EncryptionMode, String> {
+    match value.to_lowercase().as_str() {
+        "strict" => Err(STRICT_UNSUPPORTED.to_string()),
+        "true" | "yes" | "mandatory" => Ok(EncryptionMode::Required),
+        "false" | "no" | "optional" => Ok(EncryptionMode::Off),
+        _ => Err(format!("Unsupported Encrypt value '{}'", value)),
+    }
+}
+
+/// Parse a connection string, detecting its dialect
+pub fn parse(input: &str) -> Result<ImportedConnection, String> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("Connection string is empty".to_string());
+    }
+    
+    let mut connection = Connection {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: String::new(),
+        provider: Provider::SqlServer,
+        server: String::new(),
+        database: String::new(),
+        file_path: None,
+        port: None,
+        auth_type: "sql".to_string(),
+        username: None,
+        access_token_file: None,
+        access_token_command: None,
+        is_default: false,
+        encryption: EncryptionMode::default(),
+        trust_server_certificate: false,
+        ca_certificate_path: None,
+        pinned_certificate_path: None,
+        read_only_intent: false,
+        ssh_tunnel: None,
+        connect_timeout_secs: None,
+        query_timeout_secs: None,
+        connect_retries: None,
+    };
+    
+    let (dialect, mut pairs) = if input.to_lowercase().starts_with("jdbc:") {
+        let rest = input.get(..17)
+            .filter(|prefix| prefix.eq_ignore_ascii_case("jdbc:sqlserver://"))
+            .map(|_| &input[17..])
+            .ok_or_else(|| "Only SQL Server JDBC URLs (jdbc:sqlserver://) are supported".to_string())?;
+        let (address, properties) = rest.split_once(';').unwrap_or((rest, ""));
+        let mut pairs = parse_pairs(properties)?;
+        
+        let (mut host, mut instance, mut port) = parse_jdbc_address(address)?;
+        if host.is_empty() {
+            host = take(&mut pairs, &["servername", "server"]).unwrap_or_default();
+        }
+        if instance.is_none() {
+            instance = take(&mut pairs, &["instancename"]);
+        }
+        if port.is_none() {
+            port = take(&mut pairs, &["portnumber", "port"])
+                .map(|p| p.parse().map_err(|_| format!("Invalid port '{}'", p)))
+                .transpose()?;
+        }
+        connection.server = match instance {
+            Some(instance) => format!("{}\\{}", host, instance),
+            None => host,
+        };
+        connection.port = port;
+        (ConnectionStringDialect::Jdbc, pairs)
+    } else {
+        let mut pairs = parse_pairs(input)?;
+        let dialect = match take(&mut pairs, &["driver"]) {
+            Some(_) => ConnectionStringDialect::Odbc,
+            None => ConnectionStringDialect::AdoNet,
+        };
+        connection.server = take(&mut pairs, &["server", "datasource", "address", "addr", "networkaddress"])
+            .unwrap_or_default();
+        (dialect, pairs)
+    };
+    
+    if connection.server.is_empty() {
+        return Err("Connection string has no server".to_string());
+    }
+    // Check the host, instance and port the same way connecting will
+    parse_server_address(&connection.server)?;
+    
+    connection.database = take(&mut pairs, &["database", "initialcatalog", "databasename"]).unwrap_or_default();
+    connection.username = take(&mut pairs, &["userid", "uid", "user", "username"]);
+    let password = take(&mut pairs, &["password", "pwd"]);
+    
+    let integrated = take(&mut pairs, &["integratedsecurity", "trusted_connection"])
+        .map(|v| parse_bool(&v))
+        .transpose()?
+        .unwrap_or(false);
+    let kerberos = take(&mut pairs, &["authenticationscheme"])
+        .map(|v| v.eq_ignore_ascii_case("JavaKerberos"))
+        .unwrap_or(false);
+    match take(&mut pairs, &["authentication"]) {
+        // Every Azure AD flavour (password, interactive, default, ...) becomes token auth
+        Some(method) if method.replace(' ', "").to_lowercase().starts_with("activedirectory") => {
+            connection.auth_type = "azureToken".to_string();
+            connection.access_token_command = Some(AZURE_TOKEN_COMMAND.to_string());
+            connection.username = None;
+        }
+        Some(method) if method.replace(' ', "").eq_ignore_ascii_case("SqlPassword") => {}
+        Some(method) => return Err(format!("Unsupported authentication method '{}'", method)),
+        None if integrated => {
+            connection.auth_type = if kerberos || !cfg!(windows) { "kerberos" } else { "windows" }.to_string();
+            connection.username = None;
+        }
+        None => {}
+    }
+    
+    if let Some(encrypt) = take(&mut pairs, &["encrypt"]) {
+        connection.encryption = parse_encryption(&encrypt)?;
+    }
+    connection.trust_server_certificate = take(&mut pairs, &["trustservercertificate"])
+        .map(|v| parse_bool(&v))
+        .transpose()?
+        .unwrap_or(false);
+    connection.ca_certificate_path = take(&mut pairs, &["trustservercertificateca"]);
+    if take(&mut pairs, &["servercertificate"]).is_some() {
+        return Err(PINNED_UNSUPPORTED.to_string());
+    }
+    connection.read_only_intent = take(&mut pairs, &["applicationintent"])
+        .map(|v| v.eq_ignore_ascii_case("ReadOnly"))
+        .unwrap_or(false);
+    connection.connect_timeout_secs = take(&mut pairs, &["connecttimeout", "connectiontimeout", "timeout", "logintimeout"])
+        .map(|v| parse_number(&v))
+        .transpose()?;
+    connection.query_timeout_secs = take(&mut pairs, &["commandtimeout", "querytimeout"])
+        .map(|v| parse_number(&v))
+        .transpose()?;
+    connection.connect_retries = take(&mut pairs, &["connectretrycount"])
+        .map(|v| parse_number(&v))
+        .transpose()?;
+    
+    connection.name = if connection.database.is_empty() {
+        connection.server.clone()
+    } else {
+        format!("{}/{}", connection.server, connection.database)
+    };
+    
+    let password = if connection.auth_type == "sql" { password } else { None };
+    Ok(ImportedConnection {
+        connection,
+        password,
+        dialect,
+        options: pairs.into_iter().collect(),
+    })
+}
+
+/// Whether a value has to be quoted or braced: the parser would split it, trim it or take its
+/// first character for an opening quote or brace
+fn needs_quoting(value: &str, special: [char; 4]) -> bool {
+    value.contains(special) || value.trim() != value || value.starts_with(['{', '"', '\''])
+}
+
+/// Quote an ADO.NET value if it would otherwise be misread
+fn ado_value(value: &str) -> String {
+    if needs_quoting(value, [';', '"', '\'', '=']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Brace an ODBC or JDBC value if it would otherwise be misread
+fn braced_value(value: &str) -> String {
+    if needs_quoting(value, [';', '{', '}', '=']) {
+        format!("{{{}}}", value.replace('}', "}}"))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Render a saved connection in the given dialect. The password is only written when passed in.
+pub fn render(connection: &Connection, dialect: ConnectionStringDialect, password: Option<&str>) -> Result<String, String> {
+    if connection.provider != Provider::SqlServer {
+        return Err("Connection strings can only be exported for SQL Server connections".to_string());
+    }
+    
+    let address = parse_server_address(&connection.server)?;
+    let port = connection.port.or(address.port);
+    let mut pairs: Vec<(&str, String)> = Vec::new();
+    
+    let (yes, no) = match dialect {
+        ConnectionStringDialect::AdoNet => ("True", "False"),
+        ConnectionStringDialect::Jdbc => ("true", "false"),
+        ConnectionStringDialect::Odbc => ("yes", "no"),
+    };
+    let is_jdbc = dialect == ConnectionStringDialect::Jdbc;
+    
+    let mut prefix = String::new();
+    match dialect {
+        ConnectionStringDialect::Jdbc => {
+            prefix.push_str("jdbc:sqlserver://");
+            if address.host.contains(':') {
+                prefix.push_str(&format!("[{}]", address.host));
+            } else {
+                prefix.push_str(&address.host);
+            }
+            if let Some(instance) = &address.instance {
+                prefix.push('\\');
+                prefix.push_str(instance);
+            }
+            if let Some(port) = port {
+                prefix.push_str(&format!(":{}", port));
+            }
+        }
+        _ => {
+            if dialect == ConnectionStringDialect::Odbc {
+                pairs.push(("Driver", "ODBC Driver 18 for SQL Server".to_string()));
+            }
+            let mut server = format!("tcp:{}", address.host);
+            if let Some(instance) = &address.instance {
+                server.push('\\');
+                server.push_str(instance);
+            }
+            if let Some(port) = port {
+                server.push_str(&format!(",{}", port));
+            }
+            pairs.push(("Server", server));
+        }
+    }
+    
+    if !connection.database.is_empty() {
+        pairs.push((if is_jdbc { "databaseName" } else { "Database" }, connection.database.clone()));
+    }
+    
+    match connection.auth_type.as_str() {
+        "sql" => {
+            let (user_key, password_key) = match dialect {
+                ConnectionStringDialect::AdoNet => ("User Id", "Password"),
+                ConnectionStringDialect::Jdbc => ("user", "password"),
+                ConnectionStringDialect::Odbc => ("UID", "PWD"),
+            };
+            if let Some(username) = &connection.username {
+                pairs.push((user_key, username.clone()));
+            }
+            if let Some(password) = password {
+                pairs.push((password_key, password.to_string()));
+            }
+        }
+        "windows" | "kerberos" => match dialect {
+            ConnectionStringDialect::AdoNet => pairs.push(("Integrated Security", yes.to_string())),
+            ConnectionStringDialect::Jdbc => {
+                pairs.push(("integratedSecurity", yes.to_string()));
+                if connection.auth_type == "kerberos" {
+                    pairs.push(("authenticationScheme", "JavaKerberos".to_string()));
+                }
+            }
+            ConnectionStringDialect::Odbc => pairs.push(("Trusted_Connection", yes.to_string())),
+        },
+        "azureToken" => pairs.push(match dialect {
+            ConnectionStringDialect::AdoNet => ("Authentication", "Active Directory Default".to_string()),
+            ConnectionStringDialect::Jdbc => ("authentication", "ActiveDirectoryDefault".to_string()),
+            // ODBC has no default credential chain
+            ConnectionStringDialect::Odbc => ("Authentication", "ActiveDirectoryInteractive".to_string()),
+        }),
+        _ => {}
+    }
+    
+    let encrypt = match connection.encryption {
+        EncryptionMode::Off => no,
+        EncryptionMode::On | EncryptionMode::Required => yes,
+        EncryptionMode::Strict => return Err(STRICT_UNSUPPORTED.to_string()),
+    };
+    if connection.pinned_certificate_path.is_some() {
+        return Err(PINNED_UNSUPPORTED.to_string());
+    }
+    pairs.push((if is_jdbc { "encrypt" } else { "Encrypt" }, encrypt.to_string()));
+    if connection.trust_server_certificate {
+        pairs.push((if is_jdbc { "trustServerCertificate" } else { "TrustServerCertificate" }, yes.to_string()));
+    }
+    if connection.read_only_intent {
+        pairs.push((if is_jdbc { "applicationIntent" } else { "ApplicationIntent" }, "ReadOnly".to_string()));
+    }
+    // ODBC sets its timeouts through connection attributes rather than keywords
+    if let Some(secs) = connection.connect_timeout_secs.filter(|_| dialect != ConnectionStringDialect::Odbc) {
+        pairs.push((if is_jdbc { "loginTimeout" } else { "Connect Timeout" }, secs.to_string()));
+    }
+    if let Some(secs) = connection.query_timeout_secs.filter(|_| dialect != ConnectionStringDialect::Odbc) {
+        pairs.push((if is_jdbc { "queryTimeout" } else { "Command Timeout" }, secs.to_string()));
+    }
+    if let Some(retries) = connection.connect_retries {
+        pairs.push((if is_jdbc { "connectRetryCount" } else { "ConnectRetryCount" }, retries.to_string()));
+    }
+    
+    let rendered: Vec<String> = pairs.into_iter()
+        .map(|(key, value)| match dialect {
+            ConnectionStringDialect::AdoNet => format!("{}={}", key, ado_value(&value)),
+            // Driver names are braced by convention even without special characters
+            ConnectionStringDialect::Odbc if key == "Driver" => format!("{}={{{}}}", key, value),
+            _ => format!("{}={}", key, braced_value(&value)),
+        })
+        .collect();
+    
+    Ok(if is_jdbc {
+        format!("{};{}", prefix, rendered.join(";"))
+    } else {
+        rendered.join(";")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const DIALECTS: [ConnectionStringDialect; 3] = [
+        ConnectionStringDialect::AdoNet,
+        ConnectionStringDialect::Jdbc,
+        ConnectionStringDialect::Odbc,
+    ];
+    
+    /// Host, instance and port, however the server string spells them
+    fn endpoint(connection: &Connection) -> (String, Option<String>, Option<u16>) {
+        let address = parse_server_address(&connection.server).unwrap();
+        (address.host, address.instance, connection.port.or(address.port))
+    }
+    
+    #[test]
+    fn parses_each_dialect() {
+        let ado = parse(r#"Data Source=tcp:db.example.com,1433;Initial Catalog=Sales;User ID=app;Password="p;a""ss";Encrypt=True;Foo=bar"#).unwrap();
+        assert_eq!(ado.dialect, ConnectionStringDialect::AdoNet);
+        assert_eq!(endpoint(&ado.connection), ("db.example.com".to_string(), None, Some(1433)));
+        assert_eq!(ado.password.as_deref(), Some(r#"p;a"ss"#));
+        assert_eq!(ado.options.get("foo").map(String::as_str), Some("bar"));
+        
+        let odbc = parse("Driver={ODBC Driver 18 for SQL Server};Server=db01\\SQLEXPRESS;Database=Sales;UID=app;PWD={pa}}ss}").unwrap();
+        assert_eq!(odbc.dialect, ConnectionStringDialect::Odbc);
+        assert_eq!(endpoint(&odbc.connection), ("db01".to_string(), Some("SQLEXPRESS".to_string()), None));
+        assert_eq!(odbc.password.as_deref(), Some("pa}ss"));
+        
+        let jdbc = parse("jdbc:sqlserver://[2001:db8::1]\\SQLEXPRESS:1500;databaseName=Sales;integratedSecurity=true").unwrap();
+        assert_eq!(jdbc.dialect, ConnectionStringDialect::Jdbc);
+        assert_eq!(endpoint(&jdbc.connection), ("2001:db8::1".to_string(), Some("SQLEXPRESS".to_string()), Some(1500)));
+        assert_ne!(jdbc.connection.auth_type, "sql");
+        
+        let bare = parse("jdbc:sqlserver://[::1];databaseName=Sales").unwrap();
+        assert_eq!(endpoint(&bare.connection), ("::1".to_string(), None, None));
+        
+        assert!(parse("jdbc:sqlserver://[::1]junk;databaseName=Sales").is_err());
+        assert!(parse("Server=db;Password='unclosed").is_err());
+    }
+    
+    #[test]
+    fn values_starting_with_a_quote_or_brace_are_escaped() {
+        for value in ["{braced}", "\"double\"", "'single'", "{", "'", " padded "] {
+            assert_ne!(ado_value(value), value);
+            assert_ne!(braced_value(value), value);
+        }
+        assert_eq!(ado_value("plain"), "plain");
+        assert_eq!(ado_value("{x}"), "\"{x}\"");
+        assert_eq!(braced_value("'x'"), "{'x'}");
+        assert_eq!(braced_value("{x}"), "{{x}}}");
+    }
+    
+    #[test]
+    fn round_trips_through_every_dialect() {
+        let inputs = [
+            r#"Server=tcp:db.example.com,1433;Initial Catalog=Sales;User ID=app;Password="p;a""ss";Encrypt=True;TrustServerCertificate=True;Connect Timeout=30;Command Timeout=60"#,
+            r#"Data Source=db01\SQLEXPRESS;Database="{braced}";User Id='"quoted"';Password="'single'";ApplicationIntent=ReadOnly"#,
+            "Server=tcp:db01\\SQLEXPRESS,1500;Database=Sales;Integrated Security=SSPI;ConnectRetryCount=3",
+            "Driver={ODBC Driver 18 for SQL Server};Server=tcp:2001:db8::1,1433;Database=Sales;UID=app;PWD={it's = {fine}}};Encrypt=no",
+            "jdbc:sqlserver://[2001:db8::1]:1433;databaseName=Sales;user=app;password={pa;ss}}word};encrypt=true;applicationIntent=ReadOnly",
+            "jdbc:sqlserver://[fe80::1]\\SQLEXPRESS;databaseName=Sales;user= app ;password=x",
+            "jdbc:sqlserver://db01\\SQLEXPRESS:1500;databaseName=Sales;integratedSecurity=true;loginTimeout=15",
+            "jdbc:sqlserver://;serverName=db01;portNumber=1433;databaseName=Sales;authentication=ActiveDirectoryDefault",
+        ];
+        
+        for input in inputs {
+            let original = parse(input).unwrap_or_else(|e| panic!("{}: {}", input, e));
+            
+            for dialect in DIALECTS {
+                let rendered = render(&original.connection, dialect, original.password.as_deref()).unwrap();
+                let parsed = parse(&rendered).unwrap_or_else(|e| panic!("{} -> {}: {}", input, rendered, e));
+                let (before, after) = (&original.connection, &parsed.connection);
+                let context = format!("{} -> {}", input, rendered);
+                
+                assert_eq!(parsed.dialect, dialect, "{}", context);
+                assert_eq!(endpoint(after), endpoint(before), "{}", context);
+                assert_eq!(after.database, before.database, "{}", context);
+                assert_eq!(after.username, before.username, "{}", context);
+                assert_eq!(parsed.password, original.password, "{}", context);
+                assert_eq!(after.auth_type, before.auth_type, "{}", context);
+                assert_eq!(after.encryption, before.encryption, "{}", context);
+                assert_eq!(after.trust_server_certificate, before.trust_server_certificate, "{}", context);
+                assert_eq!(after.read_only_intent, before.read_only_intent, "{}", context);
+                assert_eq!(after.connect_retries, before.connect_retries, "{}", context);
+                if dialect != ConnectionStringDialect::Odbc {
+                    assert_eq!(after.connect_timeout_secs, before.connect_timeout_secs, "{}", context);
+                    assert_eq!(after.query_timeout_secs, before.query_timeout_secs, "{}", context);
+                }
+                
+                // Rendering what was read back gives the same string
+                assert_eq!(render(after, dialect, parsed.password.as_deref()).unwrap(), rendered, "{}", context);
+            }
+        }
+    }
+    
+    #[test]
+    fn sql_server_encryption_options() {
+        assert_eq!(parse("Server=db;Encrypt=Strict").unwrap_err(), STRICT_UNSUPPORTED);
+        assert_eq!(parse("Server=db;Encrypt=True;ServerCertificate=C:\\certs\\db.cer").unwrap_err(), PINNED_UNSUPPORTED);
+        
+        let mut connection = parse("Server=db;Database=Sales").unwrap().connection;
+        for (mode, encrypt) in [(EncryptionMode::On, "Encrypt=True"), (EncryptionMode::Off, "Encrypt=False")] {
+            connection.encryption = mode;
+            let rendered = render(&connection, ConnectionStringDialect::AdoNet, None).unwrap();
+            assert!(rendered.contains(encrypt), "{}", rendered);
+        }
+        connection.encryption = EncryptionMode::On;
+        assert!(render(&connection, ConnectionStringDialect::Odbc, None).unwrap().contains("Encrypt=yes"));
+        
+        connection.encryption = EncryptionMode::Strict;
+        assert!(render(&connection, ConnectionStringDialect::AdoNet, None).is_err());
+        connection.encryption = EncryptionMode::Required;
+        connection.pinned_certificate_path = Some("db.cer".to_string());
+        assert!(render(&connection, ConnectionStringDialect::AdoNet, None).is_err());
+    }
+}
@@ -0,0 +1,221 @@
+// Password storage for SQL-auth connections, keyed by `Connection::id`.
+// Passwords live in the OS secret store (Keychain, Credential Manager, Secret Service).
+// When no secret store is reachable (e.g. headless Linux without libsecret), they fall back
+// to an AES-GCM encrypted file whose key is kept next to it with owner-only permissions.
+// That is obfuscation, not protection: anyone who can read the config directory can read both
+// files and decrypt the passwords. Only the file permissions keep other users out.
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use std::collections::HashMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard};
+use super::get_config_dir;
+
+const KEYRING_SERVICE: &str = "glance";
+const NONCE_LEN: usize = 12;
+
+/// Held while the fallback file is read, changed and written back, so that two saves or
+/// deletes at once cannot drop each other's entries
+static FALLBACK_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_fallback() -> MutexGuard<'static, ()> {
+    // The lock guards no data of its own, so a panic while it was held leaves nothing broken
+    FALLBACK_LOCK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Get the path to the encrypted fallback credential file
+fn get_fallback_path() -> Result<PathBuf, String> {
+    get_config_dir().map(|p| p.join("credentials.enc.json"))
+}
+
+/// Get the path to the key protecting the fallback credential file
+fn get_fallback_key_path() -> Result<PathBuf, String> {
+    get_config_dir().map(|p| p.join("credentials.key"))
+}
+
+fn keyring_entry(connection_id: &str) -> Result<keyring::Entry, keyring::Error> {
+    keyring::Entry::new(KEYRING_SERVICE, connection_id)
+}
+
+/// Whether a keyring error means the secret store itself is unavailable
+fn is_store_unavailable(error: &keyring::Error) -> bool {
+    matches!(error, keyring::Error::PlatformFailure(_) | keyring::Error::NoStorageAccess(_))
+}
+
+/// Save the password for a connection
+pub fn save_password(connection_id: &str, password: &str) -> Result<(), String> {
+    match keyring_entry(connection_id).and_then(|entry| entry.set_password(password)) {
+        Ok(()) => {
+            // Don't leave a stale copy behind if the keyring became available later
+            remove_fallback_password(connection_id)?;
+            Ok(())
+        }
+        Err(e) if is_store_unavailable(&e) => {
+            eprintln!("OS secret store unavailable ({}), using encrypted file", e);
+            save_fallback_password(connection_id, password)
+        }
+        Err(e) => Err(format!("Failed to save password: {}", e)),
+    }
+}
+
+/// Load the saved password for a connection, if any
+pub fn get_password(connection_id: &str) -> Result<Option<String>, String> {
+    match keyring_entry(connection_id).and_then(|entry| entry.get_password()) {
+        Ok(password) => Ok(Some(password)),
+        Err(keyring::Error::NoEntry) => load_fallback_password(connection_id),
+        Err(e) if is_store_unavailable(&e) => load_fallback_password(connection_id),
+        Err(e) => Err(format!("Failed to read saved password: {}", e)),
+    }
+}
+
+/// Remove the saved password for a connection from every store
+pub fn delete_password(connection_id: &str) -> Result<(), String> {
+    match keyring_entry(connection_id).and_then(|entry| entry.delete_credential()) {
+        Ok(()) | Err(keyring::Error::NoEntry) => {}
+        Err(e) if is_store_unavailable(&e) => {}
+        Err(e) => return Err(format!("Failed to delete saved password: {}", e)),
+    }
+    
+    remove_fallback_password(connection_id)
+}
+
+/// Create a file that must not exist yet, readable by the owner only from the start
+fn create_private(path: &Path) -> io::Result<File> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    
+    options.open(path)
+}
+
+fn read_key(key_path: &Path) -> Result<Key<Aes256Gcm>, String> {
+    let bytes = fs::read(key_path)
+        .map_err(|e| format!("Failed to read credential key: {}", e))?;
+    if bytes.len() != 32 {
+        return Err("Credential key file is corrupt".to_string());
+    }
+    Ok(*Key::<Aes256Gcm>::from_slice(&bytes))
+}
+
+/// Load the fallback key, creating it on first use
+fn load_or_create_key() -> Result<Key<Aes256Gcm>, String> {
+    let key_path = get_fallback_key_path()?;
+    
+    if key_path.exists() {
+        return read_key(&key_path);
+    }
+    
+    fs::create_dir_all(get_config_dir()?)
+        .map_err(|e| format!("Failed to create config directory: {}", e))?;
+    
+    let key = Aes256Gcm::generate_key(OsRng);
+    let mut file = match create_private(&key_path) {
+        Ok(file) => file,
+        // Another save created it in the meantime; use theirs so both can decrypt
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => return read_key(&key_path),
+        Err(e) => return Err(format!("Failed to create credential key: {}", e)),
+    };
+    if let Err(e) = file.write_all(key.as_slice()).and_then(|_| file.sync_all()) {
+        drop(file);
+        let _ = fs::remove_file(&key_path);
+        return Err(format!("Failed to write credential key: {}", e));
+    }
+    
+    Ok(key)
+}
+
+/// Read the fallback file as connection id -> base64(nonce || ciphertext)
+fn read_fallback_file() -> Result<HashMap<String, String>, String> {
+    let path = get_fallback_path()?;
+    
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read credential file: {}", e))?;
+    
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse credential file: {}", e))
+}
+
+/// Replace the fallback file atomically, so a crash never leaves it truncated
+fn write_fallback_file(entries: &HashMap<String, String>) -> Result<(), String> {
+    let path = get_fallback_path()?;
+    let temp_path = path.with_extension("json.tmp");
+    
+    let content = serde_json::to_string_pretty(entries)
+        .map_err(|e| format!("Failed to serialize credential file: {}", e))?;
+    
+    // Left over from an interrupted write
+    let _ = fs::remove_file(&temp_path);
+    let written = create_private(&temp_path)
+        .and_then(|mut file| file.write_all(content.as_bytes()).and_then(|_| file.sync_all()))
+        .and_then(|_| fs::rename(&temp_path, &path));
+    
+    if let Err(e) = written {
+        let _ = fs::remove_file(&temp_path);
+        return Err(format!("Failed to write credential file: {}", e));
+    }
+    
+    Ok(())
+}
+
+fn save_fallback_password(connection_id: &str, password: &str) -> Result<(), String> {
+    let cipher = Aes256Gcm::new(&load_or_create_key()?);
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    
+    let ciphertext = cipher.encrypt(&nonce, password.as_bytes())
+        .map_err(|_| "Failed to encrypt password".to_string())?;
+    
+    let mut payload = nonce.to_vec();
+    payload.extend_from_slice(&ciphertext);
+    
+    let _lock = lock_fallback();
+    let mut entries = read_fallback_file()?;
+    entries.insert(connection_id.to_string(), BASE64.encode(payload));
+    write_fallback_file(&entries)
+}
+
+fn load_fallback_password(connection_id: &str) -> Result<Option<String>, String> {
+    let entries = read_fallback_file()?;
+    
+    let Some(encoded) = entries.get(connection_id) else {
+        return Ok(None);
+    };
+    
+    let payload = BASE64.decode(encoded)
+        .map_err(|e| format!("Failed to decode saved password: {}", e))?;
+    if payload.len() <= NONCE_LEN {
+        return Err("Saved password is corrupt".to_string());
+    }
+    
+    let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+    let cipher = Aes256Gcm::new(&load_or_create_key()?);
+    let plaintext = cipher.decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Failed to decrypt saved password".to_string())?;
+    
+    String::from_utf8(plaintext)
+        .map(Some)
+        .map_err(|_| "Saved password is not valid UTF-8".to_string())
+}
+
+fn remove_fallback_password(connection_id: &str) -> Result<(), String> {
+    let _lock = lock_fallback();
+    let mut entries = read_fallback_file()?;
+    
+    if entries.remove(connection_id).is_some() {
+        write_fallback_file(&entries)?;
+    }
+    
+    Ok(())
+}
@@ -1,19 +1,145 @@
 pub mod settings;
+pub mod credentials;
+pub mod connection_string;
 
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Connection {
     pub id: String,
     pub name: String,
+    /// Database engine the connection points at
+    #[serde(default)]
+    pub provider: Provider,
     pub server: String,
     pub database: String,
+    /// Database file (SQLite) or script folder (DDL scripts) of a connection that has no
+    /// server or database name
+    #[serde(default)]
+    pub file_path: Option<String>,
+    /// Explicit TCP port; overrides any port or instance name in `server` (SQL Server)
+    /// or the provider's default port
+    pub port: Option<u16>,
+    /// `sql`, `windows`, `kerberos`, `azureToken`, or `none` for local providers
     pub auth_type: String,
     pub username: Option<String>,
+    /// File holding an Azure AD access token (`azureToken` auth)
+    pub access_token_file: Option<String>,
+    /// Command that prints an Azure AD access token (`azureToken` auth), either bare or as
+    /// the JSON written by `az account get-access-token`
+    pub access_token_command: Option<String>,
     pub is_default: bool,
+    #[serde(default)]
+    pub encryption: EncryptionMode,
+    /// Accept the server certificate without validation
+    #[serde(default)]
+    pub trust_server_certificate: bool,
+    /// CA certificate (pem, crt or der) used instead of the OS trust store
+    pub ca_certificate_path: Option<String>,
+    /// The server's own certificate; only this exact certificate is accepted. MySQL can only
+    /// trust it as its sole CA, and SQL Server connections reject it.
+    pub pinned_certificate_path: Option<String>,
+    /// Declare a read-only workload (`ApplicationIntent=ReadOnly`), so an availability group
+    /// listener routes the session to a readable secondary
+    #[serde(default)]
+    pub read_only_intent: bool,
+    /// Reach the server through an SSH bastion
+    #[serde(default)]
+    pub ssh_tunnel: Option<SshTunnel>,
+    /// Seconds to wait for the server to accept the connection and login; defaults to 15
+    #[serde(default)]
+    pub connect_timeout_secs: Option<u64>,
+    /// Seconds each catalog query may run while indexing; defaults to 120
+    #[serde(default)]
+    pub query_timeout_secs: Option<u64>,
+    /// Further attempts after a connection fails for a transient reason; defaults to 2
+    #[serde(default)]
+    pub connect_retries: Option<u32>,
+}
+
+impl Connection {
+    pub fn connect_timeout(&self) -> Duration {
+        Duration::from_secs(self.connect_timeout_secs.filter(|s| *s > 0).unwrap_or(15))
+    }
+    
+    pub fn query_timeout(&self) -> Duration {
+        Duration::from_secs(self.query_timeout_secs.filter(|s| *s > 0).unwrap_or(120))
+    }
+    
+    pub fn connect_retries(&self) -> u32 {
+        self.connect_retries.unwrap_or(2)
+    }
+}
+
+/// SSH bastion a connection is forwarded through
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SshTunnel {
+    pub host: String,
+    /// Defaults to 22
+    pub port: Option<u16>,
+    pub user: String,
+    /// Private key (OpenSSH or PEM); without one, keys from the running SSH agent are tried
+    pub key_path: Option<String>,
+    #[serde(default)]
+    pub known_hosts: KnownHostsPolicy,
+}
+
+/// How the bastion's host key is checked against `~/.ssh/known_hosts`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KnownHostsPolicy {
+    /// The host must already be listed with a matching key
+    #[default]
+    Strict,
+    /// Unknown hosts are added on first use; a changed key is still rejected
+    AcceptNew,
+    /// Skip the check entirely
+    AcceptAny,
+}
+
+/// Database engine a connection reads its schema from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Provider {
+    #[default]
+    SqlServer,
+    Postgres,
+    /// MySQL or MariaDB
+    #[serde(rename = "mysql")]
+    MySql,
+    /// A local database file
+    Sqlite,
+    /// A folder of T-SQL `.sql` scripts, read without a server
+    DdlScripts,
+    /// An SSDT `.dacpac` build artifact or `.bacpac` export
+    Dacpac,
+}
+
+impl Provider {
+    /// Whether the schema is read from a local file or folder rather than a server
+    pub fn is_local(&self) -> bool {
+        matches!(self, Provider::Sqlite | Provider::DdlScripts | Provider::Dacpac)
+    }
+}
+
+/// How the connection to the server is encrypted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EncryptionMode {
+    /// Only the login packet is encrypted
+    Off,
+    /// Encrypt everything if the server supports it
+    On,
+    /// Encrypt everything and fail if the server does not support it
+    #[default]
+    Required,
+    /// Like `Required`, but the server certificate must always be validated; not available for SQL Server
+    Strict,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,9 +148,19 @@ pub struct Settings {
     pub hotkey: String,
     pub row_limit: u32,
     pub theme: String,
+    /// Refresh every connection's schema in the background
     pub auto_refresh: bool,
     pub copy_behavior: String,
     pub groups_file_path: Option<String>,
+    /// Minutes between background refreshes; defaults to 60
+    #[serde(default)]
+    pub refresh_interval_minutes: Option<u32>,
+    /// Also refresh when the app starts, however recent the cache; defaults to false
+    #[serde(default)]
+    pub refresh_on_start: Option<bool>,
+    /// Refresh when the window is shown and the cache is at least this many hours old
+    #[serde(default)]
+    pub refresh_when_shown_after_hours: Option<u32>,
 }
 
 impl Default for Settings {
@@ -36,10 +172,29 @@ impl Default for Settings {
             auto_refresh: false,
             copy_behavior: "copyOnly".to_string(),
             groups_file_path: None,
+            refresh_interval_minutes: None,
+            refresh_on_start: None,
+            refresh_when_shown_after_hours: None,
         }
     }
 }
 
+impl Settings {
+    pub fn refresh_interval(&self) -> Duration {
+        Duration::from_secs(60 * u64::from(self.refresh_interval_minutes.filter(|m| *m > 0).unwrap_or(60)))
+    }
+    
+    pub fn refresh_on_start(&self) -> bool {
+        self.refresh_on_start.unwrap_or(false)
+    }
+    
+    pub fn refresh_when_shown_after(&self) -> Option<Duration> {
+        self.refresh_when_shown_after_hours
+            .filter(|h| *h > 0)
+            .map(|h| Duration::from_secs(3600 * u64::from(h)))
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RecentItem {
@@ -1,14 +1,313 @@
-use rusqlite::{Connection, params};
+use rusqlite::{Connection, OptionalExtension, TransactionBehavior, params};
+use sha2::{Digest, Sha256};
 use crate::config;
-use crate::commands::schema::{Table, Column, ForeignKey, PrimaryKey, SchemaData};
+use super::ObjectVersion;
+use crate::commands::schema::{
+    Table, ObjectKind, Column, ForeignKey, ForeignKeyColumn, PrimaryKey, Index, IndexColumn,
+    CheckConstraint, DefaultConstraint, Routine, RoutineKind, RoutineParameter, ParameterDirection,
+    SchemaData, SchemaSnapshot, unique_constraints_from_indexes,
+};
+use std::collections::{BTreeMap, HashMap};
 use std::path::PathBuf;
+use std::time::Duration;
 
 /// Get the path to the SQLite cache database
 fn get_cache_path() -> Result<PathBuf, String> {
     config::get_cache_path()
 }
 
-/// Initialize the cache database with required tables
+/// Cache schema changes in order; `PRAGMA user_version` records how many have been applied.
+/// Never edit a released migration: append a new one, e.g. `ALTER TABLE columns ADD COLUMN ...`
+/// for a new `Column` field. The cache only holds copies of server metadata, so a migration
+/// may also drop tables outright and let the next refresh fill them again.
+const MIGRATIONS: &[&str] = &[
+    // 1: the tables as first versioned. Unversioned caches from older builds may lack
+    // columns, so they are replaced rather than kept
+    "
+    DROP TABLE IF EXISTS tables;
+    DROP TABLE IF EXISTS columns;
+    DROP TABLE IF EXISTS primary_keys;
+    DROP TABLE IF EXISTS foreign_keys;
+    DROP TABLE IF EXISTS foreign_key_columns;
+    DROP TABLE IF EXISTS indexes;
+    DROP TABLE IF EXISTS index_columns;
+    DROP TABLE IF EXISTS check_constraints;
+    DROP TABLE IF EXISTS default_constraints;
+    DROP TABLE IF EXISTS routines;
+    DROP TABLE IF EXISTS routine_parameters;
+    
+    CREATE TABLE tables (
+        id INTEGER PRIMARY KEY,
+        schema TEXT NOT NULL,
+        name TEXT NOT NULL,
+        description TEXT,
+        object_kind TEXT NOT NULL,
+        definition TEXT,
+        synonym_target TEXT,
+        extended_properties TEXT NOT NULL,
+        connection_id TEXT NOT NULL,
+        UNIQUE(schema, name, connection_id)
+    );
+    
+    CREATE TABLE columns (
+        id INTEGER PRIMARY KEY,
+        table_schema TEXT NOT NULL,
+        table_name TEXT NOT NULL,
+        name TEXT NOT NULL,
+        data_type TEXT NOT NULL,
+        full_type TEXT NOT NULL,
+        is_nullable INTEGER NOT NULL,
+        is_primary_key INTEGER NOT NULL,
+        is_foreign_key INTEGER NOT NULL,
+        ordinal_position INTEGER NOT NULL,
+        description TEXT,
+        extended_properties TEXT NOT NULL,
+        max_length INTEGER,
+        precision INTEGER,
+        scale INTEGER,
+        collation TEXT,
+        default_value TEXT,
+        identity_seed INTEGER,
+        identity_increment INTEGER,
+        computed_definition TEXT,
+        is_sparse INTEGER NOT NULL,
+        is_rowguid INTEGER NOT NULL,
+        is_filestream INTEGER NOT NULL,
+        connection_id TEXT NOT NULL,
+        UNIQUE(table_schema, table_name, name, connection_id)
+    );
+    
+    CREATE TABLE primary_keys (
+        id INTEGER PRIMARY KEY,
+        table_schema TEXT NOT NULL,
+        table_name TEXT NOT NULL,
+        column_name TEXT NOT NULL,
+        connection_id TEXT NOT NULL,
+        UNIQUE(table_schema, table_name, column_name, connection_id)
+    );
+    
+    CREATE TABLE foreign_keys (
+        id INTEGER PRIMARY KEY,
+        constraint_name TEXT NOT NULL,
+        parent_schema TEXT NOT NULL,
+        parent_table TEXT NOT NULL,
+        referenced_schema TEXT NOT NULL,
+        referenced_table TEXT NOT NULL,
+        delete_referential_action TEXT NOT NULL,
+        update_referential_action TEXT NOT NULL,
+        is_disabled INTEGER NOT NULL,
+        is_not_trusted INTEGER NOT NULL,
+        connection_id TEXT NOT NULL,
+        UNIQUE(parent_schema, constraint_name, connection_id)
+    );
+    
+    CREATE TABLE foreign_key_columns (
+        id INTEGER PRIMARY KEY,
+        parent_schema TEXT NOT NULL,
+        constraint_name TEXT NOT NULL,
+        ordinal INTEGER NOT NULL,
+        parent_column TEXT NOT NULL,
+        referenced_column TEXT NOT NULL,
+        connection_id TEXT NOT NULL,
+        UNIQUE(parent_schema, constraint_name, ordinal, connection_id)
+    );
+    
+    CREATE TABLE indexes (
+        id INTEGER PRIMARY KEY,
+        table_schema TEXT NOT NULL,
+        table_name TEXT NOT NULL,
+        name TEXT NOT NULL,
+        index_type TEXT NOT NULL,
+        is_unique INTEGER NOT NULL,
+        is_primary_key INTEGER NOT NULL,
+        is_unique_constraint INTEGER NOT NULL,
+        filter_definition TEXT,
+        connection_id TEXT NOT NULL,
+        UNIQUE(table_schema, table_name, name, connection_id)
+    );
+    
+    CREATE TABLE index_columns (
+        id INTEGER PRIMARY KEY,
+        table_schema TEXT NOT NULL,
+        table_name TEXT NOT NULL,
+        index_name TEXT NOT NULL,
+        ordinal INTEGER NOT NULL,
+        column_name TEXT NOT NULL,
+        is_descending INTEGER NOT NULL,
+        is_included INTEGER NOT NULL,
+        connection_id TEXT NOT NULL,
+        UNIQUE(table_schema, table_name, index_name, ordinal, connection_id)
+    );
+    
+    CREATE TABLE check_constraints (
+        id INTEGER PRIMARY KEY,
+        table_schema TEXT NOT NULL,
+        table_name TEXT NOT NULL,
+        constraint_name TEXT NOT NULL,
+        column_name TEXT,
+        definition TEXT NOT NULL,
+        is_disabled INTEGER NOT NULL,
+        is_not_trusted INTEGER NOT NULL,
+        connection_id TEXT NOT NULL,
+        UNIQUE(table_schema, constraint_name, connection_id)
+    );
+    
+    CREATE TABLE default_constraints (
+        id INTEGER PRIMARY KEY,
+        table_schema TEXT NOT NULL,
+        table_name TEXT NOT NULL,
+        column_name TEXT NOT NULL,
+        constraint_name TEXT NOT NULL,
+        definition TEXT NOT NULL,
+        connection_id TEXT NOT NULL,
+        UNIQUE(table_schema, table_name, column_name, connection_id)
+    );
+    
+    CREATE TABLE routines (
+        id INTEGER PRIMARY KEY,
+        schema TEXT NOT NULL,
+        name TEXT NOT NULL,
+        routine_kind TEXT NOT NULL,
+        description TEXT,
+        extended_properties TEXT NOT NULL,
+        parent_schema TEXT,
+        parent_table TEXT,
+        definition TEXT,
+        connection_id TEXT NOT NULL,
+        UNIQUE(schema, name, connection_id)
+    );
+    
+    CREATE TABLE routine_parameters (
+        id INTEGER PRIMARY KEY,
+        routine_schema TEXT NOT NULL,
+        routine_name TEXT NOT NULL,
+        name TEXT NOT NULL,
+        data_type TEXT NOT NULL,
+        direction TEXT NOT NULL,
+        default_value TEXT,
+        ordinal_position INTEGER NOT NULL,
+        connection_id TEXT NOT NULL,
+        UNIQUE(routine_schema, routine_name, ordinal_position, connection_id)
+    );
+    
+    -- One row per connection whose schema was fully written; partial refreshes never commit one
+    CREATE TABLE cache_state (
+        connection_id TEXT PRIMARY KEY,
+        refreshed_at TEXT NOT NULL
+    );
+    ",
+    // 2: schema history. A snapshot is only added when a refresh finds something changed
+    "
+    CREATE TABLE snapshots (
+        id INTEGER PRIMARY KEY,
+        connection_id TEXT NOT NULL,
+        taken_at TEXT NOT NULL,
+        last_seen_at TEXT NOT NULL,
+        server_version TEXT,
+        content_hash TEXT NOT NULL,
+        table_count INTEGER NOT NULL,
+        column_count INTEGER NOT NULL,
+        schema_json TEXT NOT NULL
+    );
+    
+    CREATE INDEX snapshots_by_connection ON snapshots (connection_id, id);
+    ",
+    // 3: object stamps of the cached schema, for incremental refresh
+    "
+    CREATE TABLE object_versions (
+        connection_id TEXT NOT NULL,
+        object_id INTEGER NOT NULL,
+        schema TEXT NOT NULL,
+        name TEXT NOT NULL,
+        stamp TEXT NOT NULL,
+        PRIMARY KEY (connection_id, object_id)
+    );
+    ",
+    // 4: key constraints by their table, since PostgreSQL names them per table, and routines
+    // by signature, since PostgreSQL overloads functions and lets triggers share their names
+    "
+    DROP TABLE foreign_keys;
+    DROP TABLE foreign_key_columns;
+    DROP TABLE check_constraints;
+    DROP TABLE routines;
+    DROP TABLE routine_parameters;
+    
+    CREATE TABLE foreign_keys (
+        id INTEGER PRIMARY KEY,
+        constraint_name TEXT NOT NULL,
+        parent_schema TEXT NOT NULL,
+        parent_table TEXT NOT NULL,
+        referenced_schema TEXT NOT NULL,
+        referenced_table TEXT NOT NULL,
+        delete_referential_action TEXT NOT NULL,
+        update_referential_action TEXT NOT NULL,
+        is_disabled INTEGER NOT NULL,
+        is_not_trusted INTEGER NOT NULL,
+        connection_id TEXT NOT NULL,
+        UNIQUE(parent_schema, parent_table, constraint_name, connection_id)
+    );
+    
+    CREATE TABLE foreign_key_columns (
+        id INTEGER PRIMARY KEY,
+        parent_schema TEXT NOT NULL,
+        parent_table TEXT NOT NULL,
+        constraint_name TEXT NOT NULL,
+        ordinal INTEGER NOT NULL,
+        parent_column TEXT NOT NULL,
+        referenced_column TEXT NOT NULL,
+        connection_id TEXT NOT NULL,
+        UNIQUE(parent_schema, parent_table, constraint_name, ordinal, connection_id)
+    );
+    
+    CREATE TABLE check_constraints (
+        id INTEGER PRIMARY KEY,
+        table_schema TEXT NOT NULL,
+        table_name TEXT NOT NULL,
+        constraint_name TEXT NOT NULL,
+        column_name TEXT,
+        definition TEXT NOT NULL,
+        is_disabled INTEGER NOT NULL,
+        is_not_trusted INTEGER NOT NULL,
+        connection_id TEXT NOT NULL,
+        UNIQUE(table_schema, table_name, constraint_name, connection_id)
+    );
+    
+    CREATE TABLE routines (
+        id INTEGER PRIMARY KEY,
+        schema TEXT NOT NULL,
+        name TEXT NOT NULL,
+        signature TEXT NOT NULL,
+        routine_kind TEXT NOT NULL,
+        description TEXT,
+        extended_properties TEXT NOT NULL,
+        parent_schema TEXT,
+        parent_table TEXT,
+        definition TEXT,
+        connection_id TEXT NOT NULL,
+        UNIQUE(schema, name, signature, connection_id)
+    );
+    
+    CREATE TABLE routine_parameters (
+        id INTEGER PRIMARY KEY,
+        routine_schema TEXT NOT NULL,
+        routine_name TEXT NOT NULL,
+        routine_signature TEXT NOT NULL,
+        name TEXT NOT NULL,
+        data_type TEXT NOT NULL,
+        direction TEXT NOT NULL,
+        default_value TEXT,
+        ordinal_position INTEGER NOT NULL,
+        connection_id TEXT NOT NULL,
+        UNIQUE(routine_schema, routine_name, routine_signature, ordinal_position, connection_id)
+    );
+    
+    -- Rows lost to the old keys are gone; every connection is indexed afresh
+    DELETE FROM cache_state;
+    DELETE FROM object_versions;
+    ",
+];
+
+/// Open the cache database, migrating it to the current version
 pub fn init_cache() -> Result<Connection, String> {
     let cache_path = get_cache_path()?;
     
@@ -18,66 +317,68 @@ pub fn init_cache() -> Result<Connection, String> {
             .map_err(|e| format!("Failed to create cache directory: {}", e))?;
     }
     
-    let conn = Connection::open(&cache_path)
+    let mut conn = Connection::open(&cache_path)
         .map_err(|e| format!("Failed to open cache database: {}", e))?;
+    // A background refresh may be writing while the UI reads
+    conn.busy_timeout(Duration::from_secs(5))
+        .map_err(|e| format!("Failed to configure cache database: {}", e))?;
     
-    // Create tables
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS tables (
-            id INTEGER PRIMARY KEY,
-            schema TEXT NOT NULL,
-            name TEXT NOT NULL,
-            description TEXT,
-            connection_id TEXT NOT NULL,
-            UNIQUE(schema, name, connection_id)
-        )",
-        [],
-    ).map_err(|e| format!("Failed to create tables table: {}", e))?;
+    migrate(&mut conn)?;
     
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS columns (
-            id INTEGER PRIMARY KEY,
-            table_schema TEXT NOT NULL,
-            table_name TEXT NOT NULL,
-            name TEXT NOT NULL,
-            data_type TEXT NOT NULL,
-            is_nullable INTEGER NOT NULL,
-            is_primary_key INTEGER NOT NULL,
-            is_foreign_key INTEGER NOT NULL,
-            ordinal_position INTEGER NOT NULL,
-            description TEXT,
-            connection_id TEXT NOT NULL,
-            UNIQUE(table_schema, table_name, name, connection_id)
-        )",
-        [],
-    ).map_err(|e| format!("Failed to create columns table: {}", e))?;
-    
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS primary_keys (
-            id INTEGER PRIMARY KEY,
-            table_name TEXT NOT NULL,
-            column_name TEXT NOT NULL,
-            connection_id TEXT NOT NULL,
-            UNIQUE(table_name, column_name, connection_id)
-        )",
-        [],
-    ).map_err(|e| format!("Failed to create primary_keys table: {}", e))?;
+    Ok(conn)
+}
+
+/// Bring the cache database up to `MIGRATIONS.len()`, one committed step per migration
+pub(crate) fn migrate(conn: &mut Connection) -> Result<(), String> {
+    loop {
+        // Immediate, so two windows opening the cache at once cannot apply the same step twice
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)
+            .map_err(|e| format!("Failed to start cache migration: {}", e))?;
+        let version: usize = tx.query_row("PRAGMA user_version", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to read cache version: {}", e))?;
+        
+        let target = if version == MIGRATIONS.len() {
+            return Ok(());
+        } else if version > MIGRATIONS.len() {
+            // Written by a newer build; the cache only holds copies, so start over
+            println!("Cache version {} is newer than this build, rebuilding it", version);
+            drop_all_tables(&tx)?;
+            0
+        } else {
+            tx.execute_batch(MIGRATIONS[version])
+                .map_err(|e| format!("Failed to migrate cache to version {}: {}", version + 1, e))?;
+            version + 1
+        };
+        
+        tx.pragma_update(None, "user_version", target)
+            .and_then(|_| tx.commit())
+            .map_err(|e| format!("Failed to migrate cache to version {}: {}", target, e))?;
+    }
+}
+
+/// Drop every table in the cache database
+fn drop_all_tables(conn: &Connection) -> Result<(), String> {
+    let tables: Vec<String> = conn.prepare("SELECT name FROM sqlite_master WHERE type = 'table'")
+        .and_then(|mut stmt| stmt.query_map([], |row| row.get(0))?.collect())
+        .map_err(|e| format!("Failed to list cache tables: {}", e))?;
     
-    conn.execute(
-        "CREATE TABLE IF NOT EXISTS foreign_keys (
-            id INTEGER PRIMARY KEY,
-            constraint_name TEXT NOT NULL,
-            parent_table TEXT NOT NULL,
-            parent_column TEXT NOT NULL,
-            referenced_table TEXT NOT NULL,
-            referenced_column TEXT NOT NULL,
-            connection_id TEXT NOT NULL,
-            UNIQUE(constraint_name, connection_id)
-        )",
-        [],
-    ).map_err(|e| format!("Failed to create foreign_keys table: {}", e))?;
+    for table in tables {
+        conn.execute(&format!("DROP TABLE \"{}\"", table), [])
+            .map_err(|e| format!("Failed to drop cache table {}: {}", table, e))?;
+    }
     
-    Ok(conn)
+    Ok(())
+}
+
+/// Serialize extended properties for a TEXT column
+fn properties_to_json(properties: &BTreeMap<String, String>) -> Result<String, String> {
+    serde_json::to_string(properties)
+        .map_err(|e| format!("Failed to serialize extended properties: {}", e))
+}
+
+/// Parse extended properties stored by `properties_to_json`
+fn properties_from_json(json: &str) -> BTreeMap<String, String> {
+    serde_json::from_str(json).unwrap_or_default()
 }
 
 /// Cache tables in SQLite
@@ -90,7 +391,8 @@ pub fn cache_tables(conn: &Connection, tables: &[Table], connection_id: &str) ->
     
     // Insert new tables
     let mut stmt = conn.prepare(
-        "INSERT OR REPLACE INTO tables (schema, name, description, connection_id) VALUES (?1, ?2, ?3, ?4)"
+        "INSERT OR REPLACE INTO tables (schema, name, description, object_kind, definition, synonym_target, extended_properties, connection_id) 
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
     ).map_err(|e| format!("Failed to prepare insert: {}", e))?;
     
     for table in tables {
@@ -98,6 +400,10 @@ pub fn cache_tables(conn: &Connection, tables: &[Table], connection_id: &str) ->
             &table.schema,
             &table.name,
             &table.description,
+            table.object_kind.as_str(),
+            &table.definition,
+            &table.synonym_target,
+            properties_to_json(&table.extended_properties)?,
             connection_id
         ]).map_err(|e| format!("Failed to insert table: {}", e))?;
     }
@@ -114,8 +420,10 @@ pub fn cache_columns(conn: &Connection, columns: &[Column], connection_id: &str)
     
     let mut stmt = conn.prepare(
         "INSERT OR REPLACE INTO columns 
-         (table_schema, table_name, name, data_type, is_nullable, is_primary_key, is_foreign_key, ordinal_position, description, connection_id) 
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"
+         (table_schema, table_name, name, data_type, full_type, is_nullable, is_primary_key, is_foreign_key, ordinal_position, description, 
+          extended_properties, max_length, precision, scale, collation, default_value, identity_seed, identity_increment, 
+          computed_definition, is_sparse, is_rowguid, is_filestream, connection_id) 
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19, ?20, ?21, ?22, ?23)"
     ).map_err(|e| format!("Failed to prepare insert: {}", e))?;
     
     for col in columns {
@@ -124,11 +432,24 @@ pub fn cache_columns(conn: &Connection, columns: &[Column], connection_id: &str)
             &col.table_name,
             &col.name,
             &col.data_type,
+            &col.full_type,
             col.is_nullable as i32,
             col.is_primary_key as i32,
             col.is_foreign_key as i32,
             col.ordinal_position,
             &col.description,
+            properties_to_json(&col.extended_properties)?,
+            col.max_length,
+            col.precision,
+            col.scale,
+            &col.collation,
+            &col.default_value,
+            col.identity_seed,
+            col.identity_increment,
+            &col.computed_definition,
+            col.is_sparse as i32,
+            col.is_rowguid as i32,
+            col.is_filestream as i32,
             connection_id
         ]).map_err(|e| format!("Failed to insert column: {}", e))?;
     }
@@ -144,11 +465,12 @@ pub fn cache_primary_keys(conn: &Connection, pks: &[PrimaryKey], connection_id:
     ).map_err(|e| format!("Failed to clear primary_keys cache: {}", e))?;
     
     let mut stmt = conn.prepare(
-        "INSERT OR REPLACE INTO primary_keys (table_name, column_name, connection_id) VALUES (?1, ?2, ?3)"
+        "INSERT OR REPLACE INTO primary_keys (table_schema, table_name, column_name, connection_id) VALUES (?1, ?2, ?3, ?4)"
     ).map_err(|e| format!("Failed to prepare insert: {}", e))?;
     
     for pk in pks {
         stmt.execute(params![
+            &pk.table_schema,
             &pk.table_name,
             &pk.column_name,
             connection_id
@@ -158,52 +480,473 @@ pub fn cache_primary_keys(conn: &Connection, pks: &[PrimaryKey], connection_id:
     Ok(())
 }
 
-/// Cache foreign keys in SQLite
+/// Cache foreign keys in SQLite, one row per constraint plus one per column pair
 pub fn cache_foreign_keys(conn: &Connection, fks: &[ForeignKey], connection_id: &str) -> Result<(), String> {
     conn.execute(
         "DELETE FROM foreign_keys WHERE connection_id = ?1",
         params![connection_id],
     ).map_err(|e| format!("Failed to clear foreign_keys cache: {}", e))?;
     
+    conn.execute(
+        "DELETE FROM foreign_key_columns WHERE connection_id = ?1",
+        params![connection_id],
+    ).map_err(|e| format!("Failed to clear foreign_key_columns cache: {}", e))?;
+    
     let mut stmt = conn.prepare(
         "INSERT OR REPLACE INTO foreign_keys 
-         (constraint_name, parent_table, parent_column, referenced_table, referenced_column, connection_id) 
-         VALUES (?1, ?2, ?3, ?4, ?5, ?6)"
+         (constraint_name, parent_schema, parent_table, referenced_schema, referenced_table, 
+          delete_referential_action, update_referential_action, is_disabled, is_not_trusted, connection_id) 
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"
+    ).map_err(|e| format!("Failed to prepare insert: {}", e))?;
+    
+    let mut column_stmt = conn.prepare(
+        "INSERT OR REPLACE INTO foreign_key_columns 
+         (parent_schema, parent_table, constraint_name, ordinal, parent_column, referenced_column, connection_id) 
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"
     ).map_err(|e| format!("Failed to prepare insert: {}", e))?;
     
     for fk in fks {
         stmt.execute(params![
             &fk.constraint_name,
+            &fk.parent_schema,
             &fk.parent_table,
-            &fk.parent_column,
+            &fk.referenced_schema,
             &fk.referenced_table,
-            &fk.referenced_column,
+            &fk.delete_referential_action,
+            &fk.update_referential_action,
+            fk.is_disabled as i32,
+            fk.is_not_trusted as i32,
             connection_id
         ]).map_err(|e| format!("Failed to insert foreign key: {}", e))?;
+        
+        for (ordinal, col) in fk.columns.iter().enumerate() {
+            column_stmt.execute(params![
+                &fk.parent_schema,
+                &fk.parent_table,
+                &fk.constraint_name,
+                ordinal as i32,
+                &col.parent_column,
+                &col.referenced_column,
+                connection_id
+            ]).map_err(|e| format!("Failed to insert foreign key column: {}", e))?;
+        }
     }
     
     Ok(())
 }
 
-/// Cache complete schema data
-pub fn cache_schema(schema: &SchemaData, connection_id: &str) -> Result<(), String> {
+/// Cache indexes in SQLite, one row per index plus one per key or included column
+pub fn cache_indexes(conn: &Connection, indexes: &[Index], connection_id: &str) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM indexes WHERE connection_id = ?1",
+        params![connection_id],
+    ).map_err(|e| format!("Failed to clear indexes cache: {}", e))?;
+    
+    conn.execute(
+        "DELETE FROM index_columns WHERE connection_id = ?1",
+        params![connection_id],
+    ).map_err(|e| format!("Failed to clear index_columns cache: {}", e))?;
+    
+    let mut stmt = conn.prepare(
+        "INSERT OR REPLACE INTO indexes 
+         (table_schema, table_name, name, index_type, is_unique, is_primary_key, is_unique_constraint, filter_definition, connection_id) 
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"
+    ).map_err(|e| format!("Failed to prepare insert: {}", e))?;
+    
+    let mut column_stmt = conn.prepare(
+        "INSERT OR REPLACE INTO index_columns 
+         (table_schema, table_name, index_name, ordinal, column_name, is_descending, is_included, connection_id) 
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
+    ).map_err(|e| format!("Failed to prepare insert: {}", e))?;
+    
+    for index in indexes {
+        stmt.execute(params![
+            &index.table_schema,
+            &index.table_name,
+            &index.name,
+            &index.index_type,
+            index.is_unique as i32,
+            index.is_primary_key as i32,
+            index.is_unique_constraint as i32,
+            &index.filter_definition,
+            connection_id
+        ]).map_err(|e| format!("Failed to insert index: {}", e))?;
+        
+        // Key columns first, then included columns, so ordinal preserves both orders
+        let key_columns = index.key_columns.iter().map(|c| (c.name.as_str(), c.is_descending, false));
+        let included_columns = index.included_columns.iter().map(|c| (c.as_str(), false, true));
+        
+        for (ordinal, (name, is_descending, is_included)) in key_columns.chain(included_columns).enumerate() {
+            column_stmt.execute(params![
+                &index.table_schema,
+                &index.table_name,
+                &index.name,
+                ordinal as i32,
+                name,
+                is_descending as i32,
+                is_included as i32,
+                connection_id
+            ]).map_err(|e| format!("Failed to insert index column: {}", e))?;
+        }
+    }
+    
+    Ok(())
+}
+
+/// Cache check constraints in SQLite
+pub fn cache_check_constraints(conn: &Connection, constraints: &[CheckConstraint], connection_id: &str) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM check_constraints WHERE connection_id = ?1",
+        params![connection_id],
+    ).map_err(|e| format!("Failed to clear check_constraints cache: {}", e))?;
+    
+    let mut stmt = conn.prepare(
+        "INSERT OR REPLACE INTO check_constraints 
+         (table_schema, table_name, constraint_name, column_name, definition, is_disabled, is_not_trusted, connection_id) 
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"
+    ).map_err(|e| format!("Failed to prepare insert: {}", e))?;
+    
+    for cc in constraints {
+        stmt.execute(params![
+            &cc.table_schema,
+            &cc.table_name,
+            &cc.constraint_name,
+            &cc.column_name,
+            &cc.definition,
+            cc.is_disabled as i32,
+            cc.is_not_trusted as i32,
+            connection_id
+        ]).map_err(|e| format!("Failed to insert check constraint: {}", e))?;
+    }
+    
+    Ok(())
+}
+
+/// Cache default constraints in SQLite
+pub fn cache_default_constraints(conn: &Connection, constraints: &[DefaultConstraint], connection_id: &str) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM default_constraints WHERE connection_id = ?1",
+        params![connection_id],
+    ).map_err(|e| format!("Failed to clear default_constraints cache: {}", e))?;
+    
+    let mut stmt = conn.prepare(
+        "INSERT OR REPLACE INTO default_constraints 
+         (table_schema, table_name, column_name, constraint_name, definition, connection_id) 
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)"
+    ).map_err(|e| format!("Failed to prepare insert: {}", e))?;
+    
+    for dc in constraints {
+        stmt.execute(params![
+            &dc.table_schema,
+            &dc.table_name,
+            &dc.column_name,
+            &dc.constraint_name,
+            &dc.definition,
+            connection_id
+        ]).map_err(|e| format!("Failed to insert default constraint: {}", e))?;
+    }
+    
+    Ok(())
+}
+
+/// Cache stored procedures, functions and triggers with their parameters
+pub fn cache_routines(conn: &Connection, routines: &[Routine], connection_id: &str) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM routines WHERE connection_id = ?1",
+        params![connection_id],
+    ).map_err(|e| format!("Failed to clear routines cache: {}", e))?;
+    
+    conn.execute(
+        "DELETE FROM routine_parameters WHERE connection_id = ?1",
+        params![connection_id],
+    ).map_err(|e| format!("Failed to clear routine_parameters cache: {}", e))?;
+    
+    let mut stmt = conn.prepare(
+        "INSERT OR REPLACE INTO routines 
+         (schema, name, signature, routine_kind, description, extended_properties, parent_schema, parent_table, definition, connection_id) 
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)"
+    ).map_err(|e| format!("Failed to prepare insert: {}", e))?;
+    
+    let mut param_stmt = conn.prepare(
+        "INSERT OR REPLACE INTO routine_parameters 
+         (routine_schema, routine_name, routine_signature, name, data_type, direction, default_value, ordinal_position, connection_id) 
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"
+    ).map_err(|e| format!("Failed to prepare insert: {}", e))?;
+    
+    for routine in routines {
+        let signature = routine_signature(routine);
+        stmt.execute(params![
+            &routine.schema,
+            &routine.name,
+            &signature,
+            routine.routine_kind.as_str(),
+            &routine.description,
+            properties_to_json(&routine.extended_properties)?,
+            &routine.parent_schema,
+            &routine.parent_table,
+            &routine.definition,
+            connection_id
+        ]).map_err(|e| format!("Failed to insert routine: {}", e))?;
+        
+        for param in &routine.parameters {
+            param_stmt.execute(params![
+                &routine.schema,
+                &routine.name,
+                &signature,
+                &param.name,
+                &param.data_type,
+                param.direction.as_str(),
+                &param.default_value,
+                param.ordinal_position,
+                connection_id
+            ]).map_err(|e| format!("Failed to insert routine parameter: {}", e))?;
+        }
+    }
+    
+    Ok(())
+}
+
+/// Tells apart routines that share a schema and name: overloaded functions by their input
+/// types, triggers by the table they are on
+pub(crate) fn routine_signature(routine: &Routine) -> String {
+    match routine.routine_kind {
+        RoutineKind::Trigger => format!(
+            "trigger on {}.{}",
+            routine.parent_schema.as_deref().unwrap_or(""),
+            routine.parent_table.as_deref().unwrap_or(""),
+        ),
+        kind => {
+            let inputs: Vec<&str> = routine.parameters.iter()
+                .filter(|p| matches!(p.direction, ParameterDirection::In | ParameterDirection::InOut))
+                .map(|p| p.data_type.as_str())
+                .collect();
+            format!("{}({})", kind.as_str(), inputs.join(","))
+        }
+    }
+}
+
+/// Cache the object stamps the schema was read at
+fn cache_object_versions(conn: &Connection, versions: &[ObjectVersion], connection_id: &str) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM object_versions WHERE connection_id = ?1",
+        params![connection_id],
+    ).map_err(|e| format!("Failed to clear object versions cache: {}", e))?;
+    
+    let mut stmt = conn.prepare(
+        "INSERT OR REPLACE INTO object_versions (connection_id, object_id, schema, name, stamp) VALUES (?1, ?2, ?3, ?4, ?5)"
+    ).map_err(|e| format!("Failed to prepare insert: {}", e))?;
+    
+    for version in versions {
+        stmt.execute(params![connection_id, version.object_id, &version.schema, &version.name, &version.stamp])
+            .map_err(|e| format!("Failed to insert object version: {}", e))?;
+    }
+    
+    Ok(())
+}
+
+/// Load the object stamps of the cached schema; empty if the provider reports none
+pub fn load_object_versions(connection_id: &str) -> Result<Vec<ObjectVersion>, String> {
     let conn = init_cache()?;
+    let mut stmt = conn.prepare(
+        "SELECT object_id, schema, name, stamp FROM object_versions WHERE connection_id = ?1"
+    ).map_err(|e| format!("Failed to prepare query: {}", e))?;
+    
+    let versions = stmt.query_map(params![connection_id], |row| {
+        Ok(ObjectVersion {
+            object_id: row.get(0)?,
+            schema: row.get(1)?,
+            name: row.get(2)?,
+            stamp: row.get(3)?,
+        })
+    }).map_err(|e| format!("Failed to query object versions: {}", e))?
+    .filter_map(|r| r.ok())
+    .collect();
+    
+    Ok(versions)
+}
+
+/// Cache complete schema data
+pub fn cache_schema(schema: &SchemaData, connection_id: &str, server_version: &str, versions: &[ObjectVersion]) -> Result<(), String> {
+    store_schema(&mut init_cache()?, schema, connection_id, server_version, versions)
+}
+
+pub(crate) fn store_schema(db: &mut Connection, schema: &SchemaData, connection_id: &str, server_version: &str, versions: &[ObjectVersion]) -> Result<(), String> {
+    // The old schema stays visible until the new one is complete; a failure or crash midway
+    // rolls back to it
+    let conn = db.transaction_with_behavior(TransactionBehavior::Immediate)
+        .map_err(|e| format!("Failed to start cache update: {}", e))?;
     
     cache_tables(&conn, &schema.tables, connection_id)?;
     cache_columns(&conn, &schema.columns, connection_id)?;
     cache_primary_keys(&conn, &schema.primary_keys, connection_id)?;
     cache_foreign_keys(&conn, &schema.foreign_keys, connection_id)?;
+    cache_indexes(&conn, &schema.indexes, connection_id)?;
+    cache_check_constraints(&conn, &schema.check_constraints, connection_id)?;
+    cache_default_constraints(&conn, &schema.default_constraints, connection_id)?;
+    cache_routines(&conn, &schema.routines, connection_id)?;
+    cache_object_versions(&conn, versions, connection_id)?;
+    
+    conn.execute(
+        "INSERT OR REPLACE INTO cache_state (connection_id, refreshed_at) VALUES (?1, datetime('now'))",
+        params![connection_id],
+    ).map_err(|e| format!("Failed to record cache update: {}", e))?;
+    record_snapshot(&conn, schema, connection_id, server_version)?;
+    
+    conn.commit()
+        .map_err(|e| format!("Failed to commit cache update: {}", e))
+}
+
+/// Current UTC time as stored in snapshot timestamps
+const NOW: &str = "strftime('%Y-%m-%dT%H:%M:%SZ', 'now')";
+
+/// Hash the schema so that the same objects listed in a different order hash the same
+fn content_hash(schema: &SchemaData) -> Result<String, String> {
+    let value = serde_json::to_value(schema)
+        .map_err(|e| format!("Failed to serialize schema: {}", e))?;
+    
+    let mut hasher = Sha256::new();
+    if let serde_json::Value::Object(lists) = value {
+        for (name, list) in lists {
+            let mut items: Vec<String> = match list {
+                serde_json::Value::Array(items) => items.iter().map(|item| item.to_string()).collect(),
+                other => vec![other.to_string()],
+            };
+            items.sort();
+            
+            hasher.update(name.as_bytes());
+            for item in items {
+                hasher.update(b"\n");
+                hasher.update(item.as_bytes());
+            }
+            hasher.update(b"\n\n");
+        }
+    }
+    
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Add a snapshot for a freshly read schema, or mark the latest one as still current
+fn record_snapshot(conn: &Connection, schema: &SchemaData, connection_id: &str, server_version: &str) -> Result<(), String> {
+    let hash = content_hash(schema)?;
+    
+    let latest: Option<(i64, String, Option<String>)> = conn.query_row(
+        "SELECT id, content_hash, server_version FROM snapshots WHERE connection_id = ?1 ORDER BY id DESC LIMIT 1",
+        params![connection_id],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+    ).optional().map_err(|e| format!("Failed to read snapshots: {}", e))?;
+    
+    match latest {
+        Some((id, latest_hash, latest_version)) if latest_hash == hash && latest_version.as_deref() == Some(server_version) => {
+            conn.execute(
+                &format!("UPDATE snapshots SET last_seen_at = {} WHERE id = ?1", NOW),
+                params![id],
+            ).map_err(|e| format!("Failed to update snapshot: {}", e))?;
+        }
+        _ => {
+            let json = serde_json::to_string(schema)
+                .map_err(|e| format!("Failed to serialize schema: {}", e))?;
+            conn.execute(
+                &format!(
+                    "INSERT INTO snapshots (connection_id, taken_at, last_seen_at, server_version, content_hash, table_count, column_count, schema_json)
+                     VALUES (?1, {now}, {now}, ?2, ?3, ?4, ?5, ?6)",
+                    now = NOW,
+                ),
+                params![connection_id, server_version, hash, schema.tables.len(), schema.columns.len(), json],
+            ).map_err(|e| format!("Failed to save snapshot: {}", e))?;
+        }
+    }
     
     Ok(())
 }
 
+/// List the snapshots kept for a connection, newest first
+pub fn list_snapshots(connection_id: &str) -> Result<Vec<SchemaSnapshot>, String> {
+    let conn = init_cache()?;
+    let mut stmt = conn.prepare(
+        "SELECT id, connection_id, taken_at, last_seen_at, server_version, content_hash, table_count, column_count
+         FROM snapshots WHERE connection_id = ?1 ORDER BY id DESC"
+    ).map_err(|e| format!("Failed to prepare query: {}", e))?;
+    
+    let snapshots = stmt.query_map(params![connection_id], |row| {
+        Ok(SchemaSnapshot {
+            id: row.get(0)?,
+            connection_id: row.get(1)?,
+            taken_at: row.get(2)?,
+            last_seen_at: row.get(3)?,
+            server_version: row.get(4)?,
+            content_hash: row.get(5)?,
+            table_count: row.get(6)?,
+            column_count: row.get(7)?,
+        })
+    }).map_err(|e| format!("Failed to query snapshots: {}", e))?
+    .filter_map(|r| r.ok())
+    .collect();
+    
+    Ok(snapshots)
+}
+
+/// Load the schema stored in a snapshot
+pub fn load_snapshot(snapshot_id: i64) -> Result<SchemaData, String> {
+    let conn = init_cache()?;
+    let json: String = conn.query_row(
+        "SELECT schema_json FROM snapshots WHERE id = ?1",
+        params![snapshot_id],
+        |row| row.get(0),
+    ).optional()
+    .map_err(|e| format!("Failed to read snapshot: {}", e))?
+    .ok_or_else(|| format!("Snapshot {} not found", snapshot_id))?;
+    
+    serde_json::from_str(&json)
+        .map_err(|e| format!("Failed to parse snapshot {}: {}", snapshot_id, e))
+}
+
+/// Delete a connection's snapshots that were last current more than `max_age_days` ago and
+/// any beyond the newest `max_count`. The newest snapshot is always kept.
+/// Returns how many were deleted.
+pub fn prune_snapshots(connection_id: &str, max_age_days: Option<u32>, max_count: Option<u32>) -> Result<usize, String> {
+    trim_snapshots(&mut init_cache()?, connection_id, max_age_days, max_count)
+}
+
+fn trim_snapshots(db: &mut Connection, connection_id: &str, max_age_days: Option<u32>, max_count: Option<u32>) -> Result<usize, String> {
+    let conn = db.transaction()
+        .map_err(|e| format!("Failed to start pruning snapshots: {}", e))?;
+    let mut deleted = 0;
+    
+    if let Some(days) = max_age_days {
+        deleted += conn.execute(
+            "DELETE FROM snapshots WHERE connection_id = ?1
+             AND last_seen_at < strftime('%Y-%m-%dT%H:%M:%SZ', 'now', ?2)
+             AND id <> (SELECT MAX(id) FROM snapshots WHERE connection_id = ?1)",
+            params![connection_id, format!("-{} days", days)],
+        ).map_err(|e| format!("Failed to prune snapshots: {}", e))?;
+    }
+    
+    if let Some(count) = max_count {
+        deleted += conn.execute(
+            "DELETE FROM snapshots WHERE connection_id = ?1
+             AND id NOT IN (SELECT id FROM snapshots WHERE connection_id = ?1 ORDER BY id DESC LIMIT ?2)",
+            params![connection_id, count.max(1)],
+        ).map_err(|e| format!("Failed to prune snapshots: {}", e))?;
+    }
+    
+    conn.commit()
+        .map_err(|e| format!("Failed to prune snapshots: {}", e))?;
+    Ok(deleted)
+}
+
 /// Load cached schema from SQLite
 pub fn load_cached_schema(connection_id: &str) -> Result<SchemaData, String> {
-    let conn = init_cache()?;
+    read_schema(&mut init_cache()?, connection_id)
+}
+
+pub(crate) fn read_schema(db: &mut Connection, connection_id: &str) -> Result<SchemaData, String> {
+    // Read every table from the same snapshot, even if a refresh commits meanwhile
+    let conn = db.transaction()
+        .map_err(|e| format!("Failed to read cache: {}", e))?;
     
     // Load tables
     let mut stmt = conn.prepare(
-        "SELECT schema, name, description FROM tables WHERE connection_id = ?1"
+        "SELECT schema, name, description, object_kind, definition, synonym_target, extended_properties FROM tables WHERE connection_id = ?1"
     ).map_err(|e| format!("Failed to prepare query: {}", e))?;
     
     let tables: Vec<Table> = stmt.query_map(params![connection_id], |row| {
@@ -211,6 +954,10 @@ pub fn load_cached_schema(connection_id: &str) -> Result<SchemaData, String> {
             schema: row.get(0)?,
             name: row.get(1)?,
             description: row.get(2)?,
+            object_kind: ObjectKind::parse(&row.get::<_, String>(3)?),
+            definition: row.get(4)?,
+            synonym_target: row.get(5)?,
+            extended_properties: properties_from_json(&row.get::<_, String>(6)?),
         })
     }).map_err(|e| format!("Failed to query tables: {}", e))?
     .filter_map(|r| r.ok())
@@ -218,7 +965,9 @@ pub fn load_cached_schema(connection_id: &str) -> Result<SchemaData, String> {
     
     // Load columns
     let mut stmt = conn.prepare(
-        "SELECT table_schema, table_name, name, data_type, is_nullable, is_primary_key, is_foreign_key, ordinal_position, description 
+        "SELECT table_schema, table_name, name, data_type, full_type, is_nullable, is_primary_key, is_foreign_key, ordinal_position, description, 
+                extended_properties, max_length, precision, scale, collation, default_value, identity_seed, identity_increment, 
+                computed_definition, is_sparse, is_rowguid, is_filestream 
          FROM columns WHERE connection_id = ?1"
     ).map_err(|e| format!("Failed to prepare query: {}", e))?;
     
@@ -228,11 +977,24 @@ pub fn load_cached_schema(connection_id: &str) -> Result<SchemaData, String> {
             table_name: row.get(1)?,
             name: row.get(2)?,
             data_type: row.get(3)?,
-            is_nullable: row.get::<_, i32>(4)? != 0,
-            is_primary_key: row.get::<_, i32>(5)? != 0,
-            is_foreign_key: row.get::<_, i32>(6)? != 0,
-            ordinal_position: row.get(7)?,
-            description: row.get(8)?,
+            full_type: row.get(4)?,
+            is_nullable: row.get::<_, i32>(5)? != 0,
+            is_primary_key: row.get::<_, i32>(6)? != 0,
+            is_foreign_key: row.get::<_, i32>(7)? != 0,
+            ordinal_position: row.get(8)?,
+            description: row.get(9)?,
+            extended_properties: properties_from_json(&row.get::<_, String>(10)?),
+            max_length: row.get(11)?,
+            precision: row.get(12)?,
+            scale: row.get(13)?,
+            collation: row.get(14)?,
+            default_value: row.get(15)?,
+            identity_seed: row.get(16)?,
+            identity_increment: row.get(17)?,
+            computed_definition: row.get(18)?,
+            is_sparse: row.get::<_, i32>(19)? != 0,
+            is_rowguid: row.get::<_, i32>(20)? != 0,
+            is_filestream: row.get::<_, i32>(21)? != 0,
         })
     }).map_err(|e| format!("Failed to query columns: {}", e))?
     .filter_map(|r| r.ok())
@@ -240,49 +1002,261 @@ pub fn load_cached_schema(connection_id: &str) -> Result<SchemaData, String> {
     
     // Load primary keys
     let mut stmt = conn.prepare(
-        "SELECT table_name, column_name FROM primary_keys WHERE connection_id = ?1"
+        "SELECT table_schema, table_name, column_name FROM primary_keys WHERE connection_id = ?1"
     ).map_err(|e| format!("Failed to prepare query: {}", e))?;
     
     let primary_keys: Vec<PrimaryKey> = stmt.query_map(params![connection_id], |row| {
         Ok(PrimaryKey {
-            table_name: row.get(0)?,
-            column_name: row.get(1)?,
+            table_schema: row.get(0)?,
+            table_name: row.get(1)?,
+            column_name: row.get(2)?,
         })
     }).map_err(|e| format!("Failed to query primary_keys: {}", e))?
     .filter_map(|r| r.ok())
     .collect();
     
-    // Load foreign keys
+    let indexes = load_indexes(&conn, connection_id)?;
+    
+    Ok(SchemaData {
+        tables,
+        columns,
+        primary_keys,
+        foreign_keys: load_foreign_keys(&conn, connection_id)?,
+        unique_constraints: unique_constraints_from_indexes(&indexes),
+        indexes,
+        check_constraints: load_check_constraints(&conn, connection_id)?,
+        default_constraints: load_default_constraints(&conn, connection_id)?,
+        routines: load_routines(&conn, connection_id)?,
+    })
+}
+
+/// Load cached foreign keys with their column pairs
+fn load_foreign_keys(conn: &Connection, connection_id: &str) -> Result<Vec<ForeignKey>, String> {
     let mut stmt = conn.prepare(
-        "SELECT constraint_name, parent_table, parent_column, referenced_table, referenced_column 
+        "SELECT constraint_name, parent_schema, parent_table, referenced_schema, referenced_table, 
+                delete_referential_action, update_referential_action, is_disabled, is_not_trusted 
          FROM foreign_keys WHERE connection_id = ?1"
     ).map_err(|e| format!("Failed to prepare query: {}", e))?;
     
-    let foreign_keys: Vec<ForeignKey> = stmt.query_map(params![connection_id], |row| {
+    let mut foreign_keys: Vec<ForeignKey> = stmt.query_map(params![connection_id], |row| {
         Ok(ForeignKey {
             constraint_name: row.get(0)?,
-            parent_table: row.get(1)?,
-            parent_column: row.get(2)?,
-            referenced_table: row.get(3)?,
-            referenced_column: row.get(4)?,
+            parent_schema: row.get(1)?,
+            parent_table: row.get(2)?,
+            referenced_schema: row.get(3)?,
+            referenced_table: row.get(4)?,
+            columns: Vec::new(),
+            delete_referential_action: row.get(5)?,
+            update_referential_action: row.get(6)?,
+            is_disabled: row.get::<_, i32>(7)? != 0,
+            is_not_trusted: row.get::<_, i32>(8)? != 0,
         })
     }).map_err(|e| format!("Failed to query foreign_keys: {}", e))?
     .filter_map(|r| r.ok())
     .collect();
     
-    Ok(SchemaData {
-        tables,
-        columns,
-        primary_keys,
-        foreign_keys,
-    })
+    // Attach column pairs to their constraints
+    let mut stmt = conn.prepare(
+        "SELECT parent_schema, parent_table, constraint_name, parent_column, referenced_column 
+         FROM foreign_key_columns WHERE connection_id = ?1 
+         ORDER BY parent_schema, parent_table, constraint_name, ordinal"
+    ).map_err(|e| format!("Failed to prepare query: {}", e))?;
+    
+    let fk_columns: Vec<((String, String, String), ForeignKeyColumn)> = stmt.query_map(params![connection_id], |row| {
+        Ok(((row.get(0)?, row.get(1)?, row.get(2)?), ForeignKeyColumn {
+            parent_column: row.get(3)?,
+            referenced_column: row.get(4)?,
+        }))
+    }).map_err(|e| format!("Failed to query foreign_key_columns: {}", e))?
+    .filter_map(|r| r.ok())
+    .collect();
+    
+    let fk_positions: HashMap<(String, String, String), usize> = foreign_keys.iter()
+        .enumerate()
+        .map(|(i, fk)| ((fk.parent_schema.clone(), fk.parent_table.clone(), fk.constraint_name.clone()), i))
+        .collect();
+    
+    for (key, column) in fk_columns {
+        if let Some(&i) = fk_positions.get(&key) {
+            foreign_keys[i].columns.push(column);
+        }
+    }
+    
+    Ok(foreign_keys)
 }
 
-/// Check if cache exists for a connection
+/// Load cached routines with their parameters
+fn load_routines(conn: &Connection, connection_id: &str) -> Result<Vec<Routine>, String> {
+    let mut stmt = conn.prepare(
+        "SELECT schema, name, signature, routine_kind, description, extended_properties, parent_schema, parent_table, definition 
+         FROM routines WHERE connection_id = ?1 
+         ORDER BY schema, name, signature"
+    ).map_err(|e| format!("Failed to prepare query: {}", e))?;
+    
+    let rows: Vec<(String, Routine)> = stmt.query_map(params![connection_id], |row| {
+        Ok((row.get(2)?, Routine {
+            schema: row.get(0)?,
+            name: row.get(1)?,
+            routine_kind: RoutineKind::parse(&row.get::<_, String>(3)?),
+            description: row.get(4)?,
+            extended_properties: properties_from_json(&row.get::<_, String>(5)?),
+            parent_schema: row.get(6)?,
+            parent_table: row.get(7)?,
+            definition: row.get(8)?,
+            parameters: Vec::new(),
+        }))
+    }).map_err(|e| format!("Failed to query routines: {}", e))?
+    .filter_map(|r| r.ok())
+    .collect();
+    
+    let positions: HashMap<(String, String, String), usize> = rows.iter()
+        .enumerate()
+        .map(|(i, (signature, r))| ((r.schema.clone(), r.name.clone(), signature.clone()), i))
+        .collect();
+    let mut routines: Vec<Routine> = rows.into_iter().map(|(_, r)| r).collect();
+    
+    let mut stmt = conn.prepare(
+        "SELECT routine_schema, routine_name, routine_signature, name, data_type, direction, default_value, ordinal_position 
+         FROM routine_parameters WHERE connection_id = ?1 
+         ORDER BY routine_schema, routine_name, routine_signature, ordinal_position"
+    ).map_err(|e| format!("Failed to prepare query: {}", e))?;
+    
+    let params = stmt.query_map(params![connection_id], |row| {
+        Ok((
+            (row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?),
+            RoutineParameter {
+                name: row.get(3)?,
+                data_type: row.get(4)?,
+                direction: ParameterDirection::parse(&row.get::<_, String>(5)?),
+                default_value: row.get(6)?,
+                ordinal_position: row.get(7)?,
+            },
+        ))
+    }).map_err(|e| format!("Failed to query routine_parameters: {}", e))?
+    .filter_map(|r| r.ok());
+    
+    for (key, param) in params {
+        if let Some(&i) = positions.get(&key) {
+            routines[i].parameters.push(param);
+        }
+    }
+    
+    Ok(routines)
+}
+
+/// Load cached indexes with their columns
+fn load_indexes(conn: &Connection, connection_id: &str) -> Result<Vec<Index>, String> {
+    let mut stmt = conn.prepare(
+        "SELECT table_schema, table_name, name, index_type, is_unique, is_primary_key, is_unique_constraint, filter_definition 
+         FROM indexes WHERE connection_id = ?1 
+         ORDER BY table_schema, table_name, name"
+    ).map_err(|e| format!("Failed to prepare query: {}", e))?;
+    
+    let mut indexes: Vec<Index> = stmt.query_map(params![connection_id], |row| {
+        Ok(Index {
+            table_schema: row.get(0)?,
+            table_name: row.get(1)?,
+            name: row.get(2)?,
+            index_type: row.get(3)?,
+            is_unique: row.get::<_, i32>(4)? != 0,
+            is_primary_key: row.get::<_, i32>(5)? != 0,
+            is_unique_constraint: row.get::<_, i32>(6)? != 0,
+            key_columns: Vec::new(),
+            included_columns: Vec::new(),
+            filter_definition: row.get(7)?,
+        })
+    }).map_err(|e| format!("Failed to query indexes: {}", e))?
+    .filter_map(|r| r.ok())
+    .collect();
+    
+    let positions: HashMap<(String, String, String), usize> = indexes.iter()
+        .enumerate()
+        .map(|(i, idx)| ((idx.table_schema.clone(), idx.table_name.clone(), idx.name.clone()), i))
+        .collect();
+    
+    let mut stmt = conn.prepare(
+        "SELECT table_schema, table_name, index_name, column_name, is_descending, is_included 
+         FROM index_columns WHERE connection_id = ?1 
+         ORDER BY table_schema, table_name, index_name, ordinal"
+    ).map_err(|e| format!("Failed to prepare query: {}", e))?;
+    
+    let rows = stmt.query_map(params![connection_id], |row| {
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            row.get::<_, String>(2)?,
+            row.get::<_, String>(3)?,
+            row.get::<_, i32>(4)? != 0,
+            row.get::<_, i32>(5)? != 0,
+        ))
+    }).map_err(|e| format!("Failed to query index_columns: {}", e))?
+    .filter_map(|r| r.ok());
+    
+    for (schema, table, index_name, column_name, is_descending, is_included) in rows {
+        let Some(&i) = positions.get(&(schema, table, index_name)) else {
+            continue;
+        };
+        if is_included {
+            indexes[i].included_columns.push(column_name);
+        } else {
+            indexes[i].key_columns.push(IndexColumn { name: column_name, is_descending });
+        }
+    }
+    
+    Ok(indexes)
+}
+
+/// Load cached check constraints
+fn load_check_constraints(conn: &Connection, connection_id: &str) -> Result<Vec<CheckConstraint>, String> {
+    let mut stmt = conn.prepare(
+        "SELECT table_schema, table_name, constraint_name, column_name, definition, is_disabled, is_not_trusted 
+         FROM check_constraints WHERE connection_id = ?1"
+    ).map_err(|e| format!("Failed to prepare query: {}", e))?;
+    
+    let constraints = stmt.query_map(params![connection_id], |row| {
+        Ok(CheckConstraint {
+            table_schema: row.get(0)?,
+            table_name: row.get(1)?,
+            constraint_name: row.get(2)?,
+            column_name: row.get(3)?,
+            definition: row.get(4)?,
+            is_disabled: row.get::<_, i32>(5)? != 0,
+            is_not_trusted: row.get::<_, i32>(6)? != 0,
+        })
+    }).map_err(|e| format!("Failed to query check_constraints: {}", e))?
+    .filter_map(|r| r.ok())
+    .collect();
+    
+    Ok(constraints)
+}
+
+/// Load cached default constraints
+fn load_default_constraints(conn: &Connection, connection_id: &str) -> Result<Vec<DefaultConstraint>, String> {
+    let mut stmt = conn.prepare(
+        "SELECT table_schema, table_name, column_name, constraint_name, definition 
+         FROM default_constraints WHERE connection_id = ?1"
+    ).map_err(|e| format!("Failed to prepare query: {}", e))?;
+    
+    let constraints = stmt.query_map(params![connection_id], |row| {
+        Ok(DefaultConstraint {
+            table_schema: row.get(0)?,
+            table_name: row.get(1)?,
+            column_name: row.get(2)?,
+            constraint_name: row.get(3)?,
+            definition: row.get(4)?,
+        })
+    }).map_err(|e| format!("Failed to query default_constraints: {}", e))?
+    .filter_map(|r| r.ok())
+    .collect();
+    
+    Ok(constraints)
+}
+
+/// Check if a complete schema has been cached for a connection
 pub fn has_cached_schema(connection_id: &str) -> bool {
     if let Ok(conn) = init_cache() {
         let count: i32 = conn.query_row(
-            "SELECT COUNT(*) FROM tables WHERE connection_id = ?1",
+            "SELECT COUNT(*) FROM cache_state WHERE connection_id = ?1",
             params![connection_id],
             |row| row.get(0)
         ).unwrap_or(0);
@@ -291,3 +1265,300 @@ pub fn has_cached_schema(connection_id: &str) -> bool {
         false
     }
 }
+
+/// When the connection's schema was last cached, as an ISO 8601 UTC timestamp, and how long ago
+pub fn last_refresh(connection_id: &str) -> Result<Option<(String, Duration)>, String> {
+    let conn = init_cache()?;
+    let refresh = conn.query_row(
+        "SELECT strftime('%Y-%m-%dT%H:%M:%SZ', refreshed_at), MAX(0, CAST(strftime('%s', 'now') AS INTEGER) - CAST(strftime('%s', refreshed_at) AS INTEGER)) FROM cache_state WHERE connection_id = ?1",
+        params![connection_id],
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)),
+    ).optional().map_err(|e| format!("Failed to read cache state: {}", e))?;
+    
+    Ok(refresh.map(|(at, age)| (at, Duration::from_secs(age as u64))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::schema::ForeignKeyColumn;
+    
+    fn open() -> Connection {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+        conn
+    }
+    
+    fn foreign_key(table: &str, column: &str) -> ForeignKey {
+        ForeignKey {
+            constraint_name: "fk_customer".to_string(),
+            parent_schema: "public".to_string(),
+            parent_table: table.to_string(),
+            referenced_schema: "public".to_string(),
+            referenced_table: "customer".to_string(),
+            columns: vec![ForeignKeyColumn { parent_column: column.to_string(), referenced_column: "id".to_string() }],
+            delete_referential_action: "NO_ACTION".to_string(),
+            update_referential_action: "NO_ACTION".to_string(),
+            is_disabled: false,
+            is_not_trusted: false,
+        }
+    }
+    
+    fn routine(name: &str, kind: RoutineKind, inputs: &[&str], parent_table: Option<&str>) -> Routine {
+        Routine {
+            schema: "public".to_string(),
+            name: name.to_string(),
+            routine_kind: kind,
+            description: None,
+            extended_properties: BTreeMap::new(),
+            parent_schema: parent_table.map(|_| "public".to_string()),
+            parent_table: parent_table.map(|t| t.to_string()),
+            definition: None,
+            parameters: inputs.iter().enumerate().map(|(i, data_type)| RoutineParameter {
+                name: format!("p{}", i + 1),
+                data_type: data_type.to_string(),
+                direction: ParameterDirection::In,
+                default_value: None,
+                ordinal_position: i as i32 + 1,
+            }).collect(),
+        }
+    }
+    
+    fn table(name: &str) -> Table {
+        Table {
+            schema: "public".to_string(),
+            name: name.to_string(),
+            description: None,
+            object_kind: ObjectKind::BaseTable,
+            definition: None,
+            synonym_target: None,
+            extended_properties: BTreeMap::new(),
+        }
+    }
+    
+    fn count(conn: &Connection, sql: &str) -> i64 {
+        conn.query_row(sql, [], |row| row.get(0)).unwrap()
+    }
+    
+    #[test]
+    fn unversioned_cache_is_rebuilt_at_the_current_version() {
+        // The tables as created before the cache was versioned
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch("
+            CREATE TABLE tables (
+                id INTEGER PRIMARY KEY,
+                schema TEXT NOT NULL,
+                name TEXT NOT NULL,
+                description TEXT,
+                connection_id TEXT NOT NULL,
+                UNIQUE(schema, name, connection_id)
+            );
+            CREATE TABLE foreign_keys (
+                id INTEGER PRIMARY KEY,
+                constraint_name TEXT NOT NULL,
+                parent_table TEXT NOT NULL,
+                parent_column TEXT NOT NULL,
+                referenced_table TEXT NOT NULL,
+                referenced_column TEXT NOT NULL,
+                connection_id TEXT NOT NULL,
+                UNIQUE(constraint_name, connection_id)
+            );
+            INSERT INTO tables (schema, name, connection_id) VALUES ('public', 'orders', 'c');
+            INSERT INTO foreign_keys VALUES (1, 'fk_customer', 'orders', 'customer_id', 'customer', 'id', 'c');
+        ").unwrap();
+        
+        migrate(&mut conn).unwrap();
+        assert_eq!(count(&conn, "PRAGMA user_version"), MIGRATIONS.len() as i64);
+        // Never recorded as complete, so the connection is indexed again rather than read half-filled
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM cache_state"), 0);
+        
+        let schema = SchemaData { tables: vec![table("orders")], ..Default::default() };
+        store_schema(&mut conn, &schema, "c", "16.0", &[]).unwrap();
+        assert_eq!(read_schema(&mut conn, "c").unwrap().tables.len(), 1);
+        
+        // Already current: nothing to do
+        migrate(&mut conn).unwrap();
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM tables"), 1);
+    }
+    
+    #[test]
+    fn later_migrations_keep_cached_rows() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MIGRATIONS[0]).unwrap();
+        conn.pragma_update(None, "user_version", 1).unwrap();
+        cache_tables(&conn, &[table("orders"), table("customer")], "c").unwrap();
+        cache_primary_keys(&conn, &[PrimaryKey {
+            table_schema: "public".to_string(),
+            table_name: "orders".to_string(),
+            column_name: "id".to_string(),
+        }], "c").unwrap();
+        
+        migrate(&mut conn).unwrap();
+        assert_eq!(count(&conn, "PRAGMA user_version"), MIGRATIONS.len() as i64);
+        let schema = read_schema(&mut conn, "c").unwrap();
+        let mut names: Vec<&str> = schema.tables.iter().map(|t| t.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["customer", "orders"]);
+        assert_eq!(schema.primary_keys.len(), 1);
+    }
+    
+    #[test]
+    fn interrupted_store_leaves_the_previous_schema() {
+        let mut conn = open();
+        let before = SchemaData { tables: vec![table("orders")], ..Default::default() };
+        store_schema(&mut conn, &before, "c", "16.0", &[]).unwrap();
+        let refreshed_at: String = conn.query_row("SELECT refreshed_at FROM cache_state", [], |row| row.get(0)).unwrap();
+        
+        // Fail partway through, after the tables were already rewritten
+        conn.execute_batch("
+            CREATE TRIGGER interrupt BEFORE INSERT ON routines BEGIN SELECT RAISE(ABORT, 'interrupted'); END;
+        ").unwrap();
+        let after = SchemaData {
+            tables: vec![table("orders"), table("invoices")],
+            routines: vec![routine("audit", RoutineKind::ScalarFunction, &[], None)],
+            ..Default::default()
+        };
+        assert!(store_schema(&mut conn, &after, "c", "16.0", &[]).is_err());
+        assert!(store_schema(&mut conn, &after, "d", "16.0", &[]).is_err());
+        
+        // The connection cached before keeps its old state; the new one is not marked complete
+        assert_eq!(read_schema(&mut conn, "c").unwrap().tables.len(), 1);
+        let state: Vec<(String, String)> = conn.prepare("SELECT connection_id, refreshed_at FROM cache_state")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(state, vec![("c".to_string(), refreshed_at)]);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM tables WHERE connection_id = 'd'"), 0);
+        assert_eq!(count(&conn, "SELECT COUNT(*) FROM snapshots"), 1);
+    }
+    
+    fn snapshot_ids(conn: &Connection) -> Vec<i64> {
+        conn.prepare("SELECT id FROM snapshots ORDER BY id")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+    
+    #[test]
+    fn content_hash_ignores_order() {
+        let schema = SchemaData {
+            tables: vec![table("orders"), table("customer")],
+            foreign_keys: vec![foreign_key("orders", "customer_id"), foreign_key("invoices", "billed_to")],
+            ..Default::default()
+        };
+        let mut reordered = schema.clone();
+        reordered.tables.reverse();
+        reordered.foreign_keys.reverse();
+        assert_eq!(content_hash(&schema).unwrap(), content_hash(&reordered).unwrap());
+        
+        let mut changed = schema.clone();
+        changed.tables[0].description = Some("Customer orders".to_string());
+        assert_ne!(content_hash(&schema).unwrap(), content_hash(&changed).unwrap());
+        
+        // The same item listed under another kind is a different schema
+        let moved = SchemaData { tables: Vec::new(), ..schema.clone() };
+        assert_ne!(content_hash(&schema).unwrap(), content_hash(&moved).unwrap());
+    }
+    
+    #[test]
+    fn unchanged_schema_keeps_the_latest_snapshot() {
+        let conn = open();
+        let schema = SchemaData { tables: vec![table("orders"), table("customer")], ..Default::default() };
+        record_snapshot(&conn, &schema, "c", "16.0").unwrap();
+        conn.execute("UPDATE snapshots SET last_seen_at = '2020-01-01T00:00:00Z'", []).unwrap();
+        
+        let mut reordered = schema.clone();
+        reordered.tables.reverse();
+        record_snapshot(&conn, &reordered, "c", "16.0").unwrap();
+        assert_eq!(snapshot_ids(&conn).len(), 1);
+        let last_seen: String = conn.query_row("SELECT last_seen_at FROM snapshots", [], |row| row.get(0)).unwrap();
+        assert!(last_seen.as_str() > "2020-01-01T00:00:00Z");
+        
+        // Other connections and server upgrades are recorded apart
+        record_snapshot(&conn, &schema, "d", "16.0").unwrap();
+        record_snapshot(&conn, &schema, "c", "17.0").unwrap();
+        assert_eq!(snapshot_ids(&conn).len(), 3);
+        
+        let changed = SchemaData { tables: vec![table("orders")], ..Default::default() };
+        record_snapshot(&conn, &changed, "c", "17.0").unwrap();
+        assert_eq!(snapshot_ids(&conn).len(), 4);
+    }
+    
+    #[test]
+    fn pruning_keeps_the_newest_snapshot() {
+        let mut conn = open();
+        for name in ["a", "b", "c", "d"] {
+            record_snapshot(&conn, &SchemaData { tables: vec![table(name)], ..Default::default() }, "c", "16.0").unwrap();
+        }
+        record_snapshot(&conn, &SchemaData::default(), "other", "16.0").unwrap();
+        let ids = snapshot_ids(&conn);
+        
+        // Only the second is recent; by age alone the newest survives as well
+        conn.execute("UPDATE snapshots SET last_seen_at = '2020-01-01T00:00:00Z' WHERE id <> ?1", params![ids[1]]).unwrap();
+        assert_eq!(trim_snapshots(&mut conn, "c", Some(30), None).unwrap(), 2);
+        assert_eq!(snapshot_ids(&conn), vec![ids[1], ids[3], ids[4]]);
+        
+        // A count of zero still keeps one
+        assert_eq!(trim_snapshots(&mut conn, "c", None, Some(0)).unwrap(), 1);
+        assert_eq!(snapshot_ids(&conn), vec![ids[3], ids[4]]);
+        
+        // Even when the newest is old too
+        assert_eq!(trim_snapshots(&mut conn, "c", Some(30), Some(5)).unwrap(), 0);
+        assert_eq!(snapshot_ids(&conn), vec![ids[3], ids[4]]);
+    }
+    
+    #[test]
+    fn same_constraint_name_on_two_tables() {
+        let conn = open();
+        cache_foreign_keys(&conn, &[foreign_key("orders", "customer_id"), foreign_key("invoices", "billed_to")], "c").unwrap();
+        
+        let mut fks = load_foreign_keys(&conn, "c").unwrap();
+        fks.sort_by(|a, b| a.parent_table.cmp(&b.parent_table));
+        assert_eq!(fks.len(), 2);
+        assert_eq!(fks[0].parent_table, "invoices");
+        assert_eq!(fks[0].columns.len(), 1);
+        assert_eq!(fks[0].columns[0].parent_column, "billed_to");
+        assert_eq!(fks[1].columns.len(), 1);
+        assert_eq!(fks[1].columns[0].parent_column, "customer_id");
+        
+        let check = |table: &str| CheckConstraint {
+            table_schema: "public".to_string(),
+            table_name: table.to_string(),
+            constraint_name: "age_check".to_string(),
+            column_name: Some("age".to_string()),
+            definition: format!("CHECK (age > 0) -- {}", table),
+            is_disabled: false,
+            is_not_trusted: false,
+        };
+        cache_check_constraints(&conn, &[check("person"), check("pet")], "c").unwrap();
+        let checks = load_check_constraints(&conn, "c").unwrap();
+        assert_eq!(checks.len(), 2);
+        assert!(checks.iter().all(|c| c.definition.ends_with(&c.table_name)));
+    }
+    
+    #[test]
+    fn overloads_and_triggers_sharing_a_name() {
+        let conn = open();
+        let routines = [
+            routine("area", RoutineKind::ScalarFunction, &["integer"], None),
+            routine("area", RoutineKind::ScalarFunction, &["numeric", "numeric"], None),
+            routine("audit", RoutineKind::ScalarFunction, &[], None),
+            routine("audit", RoutineKind::Trigger, &[], Some("orders")),
+            routine("audit", RoutineKind::Trigger, &[], Some("invoices")),
+        ];
+        cache_routines(&conn, &routines, "c").unwrap();
+        
+        let loaded = load_routines(&conn, "c").unwrap();
+        assert_eq!(loaded.len(), 5);
+        let mut arities: Vec<usize> = loaded.iter().filter(|r| r.name == "area").map(|r| r.parameters.len()).collect();
+        arities.sort();
+        assert_eq!(arities, vec![1, 2]);
+        let mut tables: Vec<&str> = loaded.iter().filter_map(|r| r.parent_table.as_deref()).collect();
+        tables.sort();
+        assert_eq!(tables, vec!["invoices", "orders"]);
+    }
+}
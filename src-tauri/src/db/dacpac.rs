@@ -0,0 +1,662 @@
+use std::collections::HashMap;
+use std::io::Read;
+use std::path::Path;
+use roxmltree::{Document, Node};
+use crate::config::Connection;
+use crate::commands::schema::{
+    Table, ObjectKind, Column, ForeignKey, ForeignKeyColumn, PrimaryKey, Index, IndexColumn,
+    CheckConstraint, DefaultConstraint, primary_keys_from_indexes,
+};
+use super::{ConnectError, ConnectErrorKind, ObjectProperties, PropertyOwner, ServerProbe, SchemaSource, run_blocking};
+use super::sqlserver::format_type;
+
+/// Name of the schema model inside a `.dacpac` or `.bacpac` package
+const MODEL_ENTRY: &str = "model.xml";
+
+/// Split a model name such as `[dbo].[Customer].[Id]` into its parts
+fn name_parts(name: &str) -> Vec<String> {
+    let mut parts = Vec::new();
+    let mut chars = name.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '[' {
+            continue;
+        }
+        let mut part = String::new();
+        while let Some(c) = chars.next() {
+            if c == ']' {
+                // `]]` stands for a literal bracket
+                if chars.peek() == Some(&']') {
+                    chars.next();
+                } else {
+                    break;
+                }
+            }
+            part.push(c);
+        }
+        parts.push(part);
+    }
+    parts
+}
+
+/// Bracket a name part, doubling any `]` in it; the reverse of `name_parts`
+fn quote_name(part: &str) -> String {
+    format!("[{}]", part.replace(']', "]]"))
+}
+
+/// Read a single-quoted script literal (`N'text'`) as plain text
+fn unquote_literal(script: &str) -> String {
+    let script = script.trim();
+    let script = script.strip_prefix(['N', 'n']).unwrap_or(script);
+    match script.strip_prefix('\'').and_then(|s| s.strip_suffix('\'')) {
+        Some(inner) => inner.replace("''", "'"),
+        None => script.to_string(),
+    }
+}
+
+fn children<'a, 'input>(node: Node<'a, 'input>, tag: &'static str) -> impl Iterator<Item = Node<'a, 'input>> {
+    node.children().filter(move |c| c.has_tag_name(tag))
+}
+
+/// Value of a `<Property>`, given either as an attribute or as a `<Value>` script
+fn property(node: Node, name: &str) -> Option<String> {
+    let property = children(node, "Property").find(|p| p.attribute("Name") == Some(name))?;
+    match property.attribute("Value") {
+        Some(value) => Some(value.to_string()),
+        None => children(property, "Value").next().and_then(|v| v.text()).map(|t| t.to_string()),
+    }
+}
+
+fn flag(node: Node, name: &str, default: bool) -> bool {
+    match property(node, name) {
+        Some(value) => value.eq_ignore_ascii_case("True"),
+        None => default,
+    }
+}
+
+/// Entries of a `<Relationship>`
+fn relationship<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Vec<Node<'a, 'input>> {
+    children(node, "Relationship")
+        .find(|r| r.attribute("Name") == Some(name))
+        .map(|r| children(r, "Entry").collect())
+        .unwrap_or_default()
+}
+
+/// Names an element refers to through a relationship
+fn references(node: Node, name: &str) -> Vec<String> {
+    relationship(node, name)
+        .into_iter()
+        .filter_map(|entry| children(entry, "References").next())
+        .filter_map(|r| r.attribute("Name").map(|n| n.to_string()))
+        .collect()
+}
+
+/// Elements nested in the entries of a relationship
+fn nested<'a, 'input>(node: Node<'a, 'input>, name: &str) -> Vec<Node<'a, 'input>> {
+    relationship(node, name)
+        .into_iter()
+        .filter_map(|entry| children(entry, "Element").next())
+        .collect()
+}
+
+/// Schema and object of the table an element is defined on, from a relationship naming it
+fn defining_table(node: Node, name: &str) -> Option<(String, String)> {
+    let parts = name_parts(references(node, name).first()?);
+    match parts.as_slice() {
+        [schema, table, ..] => Some((schema.clone(), table.clone())),
+        _ => None,
+    }
+}
+
+/// Last part of each referenced name, i.e. the column names
+fn column_names(node: Node, name: &str) -> Vec<String> {
+    references(node, name)
+        .iter()
+        .filter_map(|n| name_parts(n).pop())
+        .collect()
+}
+
+/// Key columns of a constraint or index with their sort direction
+fn key_columns(node: Node) -> Vec<IndexColumn> {
+    nested(node, "ColumnSpecifications")
+        .into_iter()
+        .filter_map(|spec| {
+            let name = column_names(spec, "Column").pop()?;
+            Some(IndexColumn { name, is_descending: !flag(spec, "IsAscending", true) })
+        })
+        .collect()
+}
+
+/// Last part of the element's own name, or `fallback` for unnamed constraints
+fn element_name(node: Node, fallback: impl FnOnce() -> String) -> String {
+    node.attribute("Name")
+        .and_then(|n| name_parts(n).pop())
+        .unwrap_or_else(fallback)
+}
+
+/// Map a `DeleteAction`/`UpdateAction` value to the names SQL Server reports
+fn referential_action(value: Option<String>) -> String {
+    match value.as_deref() {
+        Some("1") | Some("Cascade") => "CASCADE",
+        Some("2") | Some("SetNull") => "SET_NULL",
+        Some("3") | Some("SetDefault") => "SET_DEFAULT",
+        _ => "NO_ACTION",
+    }.to_string()
+}
+
+/// Everything read from the package's model
+#[derive(Debug, Default)]
+struct Model {
+    /// Schema provider named by the package, e.g. `Sql160`
+    platform: String,
+    tables: Vec<Table>,
+    columns: Vec<Column>,
+    foreign_keys: Vec<ForeignKey>,
+    indexes: Vec<Index>,
+    check_constraints: Vec<CheckConstraint>,
+    default_constraints: Vec<DefaultConstraint>,
+    properties: HashMap<PropertyOwner, ObjectProperties>,
+}
+
+impl Model {
+    /// Read a table's or view's columns in declaration order
+    fn add_columns(&mut self, element: Node, schema: &str, table: &str) {
+        for (i, column) in nested(element, "Columns").into_iter().enumerate() {
+            let name = element_name(column, String::new);
+            let mut data_type = String::new();
+            let mut full_type = String::new();
+            let (mut max_length, mut precision, mut scale) = (None, None, None);
+            
+            if let Some(specifier) = nested(column, "TypeSpecifier").first() {
+                let type_parts = references(*specifier, "Type")
+                    .first()
+                    .map(|n| name_parts(n))
+                    .unwrap_or_default();
+                // Built-in types are named alone (`[int]`); user-defined types carry their schema
+                data_type = match type_parts.as_slice() {
+                    [name] => name.to_lowercase(),
+                    parts => parts.last().cloned().unwrap_or_default(),
+                };
+                max_length = if flag(*specifier, "IsMax", false) {
+                    Some(-1)
+                } else {
+                    property(*specifier, "Length").and_then(|l| l.parse().ok())
+                };
+                precision = property(*specifier, "Precision").and_then(|p| p.parse().ok());
+                scale = property(*specifier, "Scale").and_then(|s| s.parse().ok());
+                full_type = format_type(&data_type, max_length, precision, scale);
+            }
+            
+            let is_identity = flag(column, "IsIdentity", false);
+            // Seed and increment live on the nested identity options; SQL Server defaults both to 1
+            let identity = nested(column, "IdentitySpecifier").first().copied();
+            let identity_value = |name: &str| {
+                identity
+                    .and_then(|options| property(options, name))
+                    .and_then(|value| value.trim().parse().ok())
+                    .unwrap_or(1)
+            };
+            self.columns.push(Column {
+                table_schema: schema.to_string(),
+                table_name: table.to_string(),
+                name,
+                data_type,
+                full_type,
+                is_nullable: flag(column, "IsNullable", true),
+                is_primary_key: false, // Will be updated later
+                is_foreign_key: false, // Will be updated later
+                ordinal_position: i as i32 + 1,
+                description: None,
+                extended_properties: Default::default(),
+                max_length,
+                precision,
+                scale,
+                collation: property(column, "Collation"),
+                default_value: None,
+                identity_seed: is_identity.then(|| identity_value("IdentitySeed")),
+                identity_increment: is_identity.then(|| identity_value("IdentityIncrement")),
+                computed_definition: property(column, "ExpressionScript"),
+                is_sparse: flag(column, "IsSparse", false),
+                is_rowguid: flag(column, "IsRowGuidCol", false),
+                is_filestream: flag(column, "IsFileStream", false),
+            });
+        }
+    }
+    
+    fn add_element(&mut self, element: Node) {
+        let element_type = element.attribute("Type").unwrap_or("");
+        let parts = element.attribute("Name").map(name_parts).unwrap_or_default();
+        
+        match element_type {
+            "SqlTable" | "SqlView" => {
+                let [schema, name] = parts.as_slice() else { return };
+                let is_view = element_type == "SqlView";
+                let definition = property(element, "QueryScript")
+                    .filter(|_| is_view)
+                    .map(|query| format!("CREATE VIEW {}.{} AS\n{}", quote_name(schema), quote_name(name), query));
+                self.tables.push(Table {
+                    schema: schema.clone(),
+                    name: name.clone(),
+                    description: None,
+                    object_kind: if is_view { ObjectKind::View } else { ObjectKind::BaseTable },
+                    definition,
+                    synonym_target: None,
+                    extended_properties: Default::default(),
+                });
+                self.add_columns(element, schema, name);
+            }
+            "SqlPrimaryKeyConstraint" | "SqlUniqueConstraint" => {
+                let Some((schema, table)) = defining_table(element, "DefiningTable") else { return };
+                let is_primary_key = element_type == "SqlPrimaryKeyConstraint";
+                let key_columns = key_columns(element);
+                let name = element_name(element, || {
+                    if is_primary_key {
+                        format!("PK_{}", table)
+                    } else {
+                        let columns: Vec<&str> = key_columns.iter().map(|c| c.name.as_str()).collect();
+                        format!("UQ_{}_{}", table, columns.join("_"))
+                    }
+                });
+                self.indexes.push(Index {
+                    table_schema: schema,
+                    table_name: table,
+                    name,
+                    // Primary keys are clustered unless stated otherwise, unique constraints the reverse
+                    index_type: if flag(element, "IsClustered", is_primary_key) { "CLUSTERED" } else { "NONCLUSTERED" }.to_string(),
+                    is_unique: true,
+                    is_primary_key,
+                    is_unique_constraint: !is_primary_key,
+                    key_columns,
+                    included_columns: Vec::new(),
+                    filter_definition: None,
+                });
+            }
+            "SqlIndex" => {
+                let Some((schema, table)) = defining_table(element, "IndexedObject") else { return };
+                self.indexes.push(Index {
+                    table_schema: schema,
+                    table_name: table,
+                    name: element_name(element, String::new),
+                    index_type: if flag(element, "IsClustered", false) { "CLUSTERED" } else { "NONCLUSTERED" }.to_string(),
+                    is_unique: flag(element, "IsUnique", false),
+                    is_primary_key: false,
+                    is_unique_constraint: false,
+                    key_columns: key_columns(element),
+                    included_columns: column_names(element, "IncludedColumns"),
+                    filter_definition: property(element, "FilterPredicate"),
+                });
+            }
+            "SqlForeignKeyConstraint" => {
+                let Some((schema, table)) = defining_table(element, "DefiningTable") else { return };
+                let Some((referenced_schema, referenced_table)) = defining_table(element, "ForeignTable") else { return };
+                let columns = column_names(element, "Columns")
+                    .into_iter()
+                    .zip(column_names(element, "ForeignColumns"))
+                    .map(|(parent_column, referenced_column)| ForeignKeyColumn { parent_column, referenced_column })
+                    .collect();
+                self.foreign_keys.push(ForeignKey {
+                    constraint_name: element_name(element, || format!("FK_{}_{}", table, referenced_table)),
+                    parent_schema: schema,
+                    parent_table: table,
+                    referenced_schema,
+                    referenced_table,
+                    columns,
+                    delete_referential_action: referential_action(property(element, "DeleteAction")),
+                    update_referential_action: referential_action(property(element, "UpdateAction")),
+                    is_disabled: false,
+                    is_not_trusted: flag(element, "WithNoCheck", false),
+                });
+            }
+            "SqlCheckConstraint" => {
+                let Some((schema, table)) = defining_table(element, "DefiningTable") else { return };
+                self.check_constraints.push(CheckConstraint {
+                    constraint_name: element_name(element, || format!("CK_{}", table)),
+                    table_schema: schema,
+                    table_name: table,
+                    column_name: None,
+                    definition: property(element, "CheckExpressionScript").unwrap_or_default(),
+                    is_disabled: false,
+                    is_not_trusted: flag(element, "WithNoCheck", false),
+                });
+            }
+            "SqlDefaultConstraint" => {
+                let Some((schema, table)) = defining_table(element, "DefiningTable") else { return };
+                let Some(column_name) = column_names(element, "ForColumn").pop() else { return };
+                self.default_constraints.push(DefaultConstraint {
+                    constraint_name: element_name(element, || format!("DF_{}_{}", table, column_name)),
+                    table_schema: schema,
+                    table_name: table,
+                    column_name,
+                    definition: property(element, "DefaultExpressionScript").unwrap_or_default(),
+                });
+            }
+            "SqlExtendedProperty" => {
+                let Some(host) = references(element, "Host").first().map(|h| name_parts(h)) else { return };
+                let owner = match host.as_slice() {
+                    [schema, object] => (schema.clone(), object.clone(), None),
+                    [schema, object, column] => (schema.clone(), object.clone(), Some(column.clone())),
+                    _ => return,
+                };
+                let Some(name) = parts.last() else { return };
+                let value = property(element, "Value").map(|v| unquote_literal(&v)).unwrap_or_default();
+                let entry = self.properties.entry(owner).or_default();
+                if name == "MS_Description" {
+                    entry.description = Some(value);
+                } else {
+                    entry.extended.insert(name.clone(), value);
+                }
+            }
+            _ => {}
+        }
+    }
+    
+    /// Constraint defaults also show on their columns
+    fn finish(&mut self) {
+        for default in &self.default_constraints {
+            if let Some(column) = self.columns.iter_mut().find(|c| {
+                c.table_schema == default.table_schema && c.table_name == default.table_name && c.name == default.column_name
+            }) {
+                column.default_value = Some(default.definition.clone());
+            }
+        }
+    }
+}
+
+/// Read `model.xml` out of a `.dacpac` or `.bacpac` package
+fn read_model_xml(path: &Path) -> Result<String, String> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| format!("{} is not a dacpac or bacpac package: {}", path.display(), e))?;
+    let mut entry = archive.by_name(MODEL_ENTRY)
+        .map_err(|e| format!("Failed to find {} in {}: {}", MODEL_ENTRY, path.display(), e))?;
+    
+    let mut xml = String::new();
+    entry.read_to_string(&mut xml)
+        .map_err(|e| format!("Failed to read {}: {}", MODEL_ENTRY, e))?;
+    Ok(xml)
+}
+
+/// Parse the package's schema model
+fn parse_model(xml: &str) -> Result<Model, String> {
+    let document = Document::parse(xml)
+        .map_err(|e| format!("Failed to parse {}: {}", MODEL_ENTRY, e))?;
+    
+    let root = document.root_element();
+    let mut model = Model {
+        // `Microsoft.Data.Tools.Schema.Sql.Sql160DatabaseSchemaProvider`
+        platform: root.attribute("DspName")
+            .and_then(|d| d.rsplit('.').next())
+            .map(|d| d.trim_end_matches("DatabaseSchemaProvider").to_string())
+            .unwrap_or_default(),
+        ..Default::default()
+    };
+    
+    for element in children(root, "Model").flat_map(|m| children(m, "Element")) {
+        model.add_element(element);
+    }
+    model.finish();
+    
+    Ok(model)
+}
+
+/// A `.dacpac` build artifact or `.bacpac` export, read without a server on the blocking thread
+/// pool. Only the schema model is used; procedures, functions and any table data in the package
+/// are skipped.
+pub struct DacpacSource {
+    package_name: String,
+    model: Model,
+}
+
+impl SchemaSource for DacpacSource {
+    async fn connect(connection: &Connection, _password: Option<&str>) -> Result<Self, ConnectError> {
+        let path = match connection.file_path.as_deref().map(str::trim) {
+            Some(path) if !path.is_empty() => Path::new(path),
+            _ => return Err(ConnectError::new(ConnectErrorKind::Configuration, "Package file is required")),
+        };
+        
+        if !path.is_file() {
+            return Err(ConnectError::new(
+                ConnectErrorKind::DatabaseNotFound,
+                format!("Package file not found: {}", path.display()),
+            ));
+        }
+        
+        println!("Reading schema model from {}", path.display());
+        
+        let package = path.to_path_buf();
+        let model = run_blocking(move |cancellation| {
+            let xml = read_model_xml(&package)?;
+            if cancellation.is_cancelled() {
+                return Err("Reading the package was cancelled".to_string());
+            }
+            parse_model(&xml)
+        })
+        .await
+        .and_then(|model| model)
+        .map_err(|e| ConnectError::new(ConnectErrorKind::Other, e))?;
+        
+        Ok(Self {
+            package_name: path.file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            model,
+        })
+    }
+    
+    async fn probe(&mut self) -> Result<ServerProbe, String> {
+        Ok(ServerProbe {
+            server_version: format!("SQL Server package ({})", self.model.platform),
+            database_name: self.package_name.clone(),
+            login_name: String::new(),
+        })
+    }
+    
+    async fn get_tables(&mut self) -> Result<Vec<Table>, String> {
+        Ok(std::mem::take(&mut self.model.tables))
+    }
+    
+    async fn get_columns(&mut self) -> Result<Vec<Column>, String> {
+        Ok(std::mem::take(&mut self.model.columns))
+    }
+    
+    async fn get_primary_keys(&mut self) -> Result<Vec<PrimaryKey>, String> {
+        Ok(primary_keys_from_indexes(&self.model.indexes))
+    }
+    
+    async fn get_foreign_keys(&mut self) -> Result<Vec<ForeignKey>, String> {
+        Ok(std::mem::take(&mut self.model.foreign_keys))
+    }
+    
+    async fn get_indexes(&mut self) -> Result<Vec<Index>, String> {
+        // Primary keys are read from the indexes too, so they stay in the model
+        Ok(self.model.indexes.clone())
+    }
+    
+    async fn get_check_constraints(&mut self) -> Result<Vec<CheckConstraint>, String> {
+        Ok(std::mem::take(&mut self.model.check_constraints))
+    }
+    
+    async fn get_default_constraints(&mut self) -> Result<Vec<DefaultConstraint>, String> {
+        Ok(std::mem::take(&mut self.model.default_constraints))
+    }
+    
+    async fn get_properties(&mut self) -> Result<HashMap<PropertyOwner, ObjectProperties>, String> {
+        Ok(std::mem::take(&mut self.model.properties))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Customers and their orders, as SqlPackage writes them
+    const MODEL: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<DataSchemaModel FileFormatVersion="1.2" SchemaVersion="2.9" DspName="Microsoft.Data.Tools.Schema.Sql.Sql160DatabaseSchemaProvider" xmlns="http://schemas.microsoft.com/sqlserver/dac/Serialization/2012/02">
+  <Model>
+    <Element Type="SqlTable" Name="[sales].[Customer]">
+      <Relationship Name="Columns">
+        <Entry>
+          <Element Type="SqlSimpleColumn" Name="[sales].[Customer].[Id]">
+            <Property Name="IsNullable" Value="False" />
+            <Property Name="IsIdentity" Value="True" />
+            <Relationship Name="IdentitySpecifier">
+              <Entry>
+                <Element Type="SqlIdentityOptions">
+                  <Property Name="IdentitySeed">
+                    <Value><![CDATA[1000]]></Value>
+                  </Property>
+                  <Property Name="IdentityIncrement" Value="5" />
+                </Element>
+              </Entry>
+            </Relationship>
+            <Relationship Name="TypeSpecifier">
+              <Entry>
+                <Element Type="SqlTypeSpecifier">
+                  <Relationship Name="Type">
+                    <Entry><References ExternalSource="BuiltIns" Name="[int]" /></Entry>
+                  </Relationship>
+                </Element>
+              </Entry>
+            </Relationship>
+          </Element>
+        </Entry>
+        <Entry>
+          <Element Type="SqlSimpleColumn" Name="[sales].[Customer].[Name]">
+            <Relationship Name="TypeSpecifier">
+              <Entry>
+                <Element Type="SqlTypeSpecifier">
+                  <Property Name="Length" Value="100" />
+                  <Relationship Name="Type">
+                    <Entry><References ExternalSource="BuiltIns" Name="[nvarchar]" /></Entry>
+                  </Relationship>
+                </Element>
+              </Entry>
+            </Relationship>
+          </Element>
+        </Entry>
+      </Relationship>
+    </Element>
+    <Element Type="SqlTable" Name="[sales].[Orders]">
+      <Relationship Name="Columns">
+        <Entry>
+          <Element Type="SqlSimpleColumn" Name="[sales].[Orders].[Id]">
+            <Property Name="IsNullable" Value="False" />
+            <Property Name="IsIdentity" Value="True" />
+          </Element>
+        </Entry>
+        <Entry>
+          <Element Type="SqlSimpleColumn" Name="[sales].[Orders].[CustomerId]" />
+        </Entry>
+      </Relationship>
+    </Element>
+    <Element Type="SqlPrimaryKeyConstraint" Name="[sales].[PK_Customer]">
+      <Relationship Name="ColumnSpecifications">
+        <Entry>
+          <Element Type="SqlIndexedColumnSpecification">
+            <Relationship Name="Column">
+              <Entry><References Name="[sales].[Customer].[Id]" /></Entry>
+            </Relationship>
+          </Element>
+        </Entry>
+      </Relationship>
+      <Relationship Name="DefiningTable">
+        <Entry><References Name="[sales].[Customer]" /></Entry>
+      </Relationship>
+    </Element>
+    <Element Type="SqlForeignKeyConstraint" Name="[sales].[FK_Orders_Customer]">
+      <Property Name="DeleteAction" Value="1" />
+      <Relationship Name="Columns">
+        <Entry><References Name="[sales].[Orders].[CustomerId]" /></Entry>
+      </Relationship>
+      <Relationship Name="DefiningTable">
+        <Entry><References Name="[sales].[Orders]" /></Entry>
+      </Relationship>
+      <Relationship Name="ForeignColumns">
+        <Entry><References Name="[sales].[Customer].[Id]" /></Entry>
+      </Relationship>
+      <Relationship Name="ForeignTable">
+        <Entry><References Name="[sales].[Customer]" /></Entry>
+      </Relationship>
+    </Element>
+    <Element Type="SqlIndex" Name="[sales].[Orders].[IX_Orders_CustomerId]">
+      <Relationship Name="ColumnSpecifications">
+        <Entry>
+          <Element Type="SqlIndexedColumnSpecification">
+            <Property Name="IsAscending" Value="False" />
+            <Relationship Name="Column">
+              <Entry><References Name="[sales].[Orders].[CustomerId]" /></Entry>
+            </Relationship>
+          </Element>
+        </Entry>
+      </Relationship>
+      <Relationship Name="IncludedColumns">
+        <Entry><References Name="[sales].[Orders].[Id]" /></Entry>
+      </Relationship>
+      <Relationship Name="IndexedObject">
+        <Entry><References Name="[sales].[Orders]" /></Entry>
+      </Relationship>
+    </Element>
+    <Element Type="SqlView" Name="[sales].[Big]]Customers]">
+      <Property Name="QueryScript">
+        <Value><![CDATA[SELECT Id FROM sales.Customer]]></Value>
+      </Property>
+    </Element>
+  </Model>
+</DataSchemaModel>"#;
+
+    fn column<'m>(model: &'m Model, table: &str, name: &str) -> &'m Column {
+        model.columns.iter().find(|c| c.table_name == table && c.name == name).unwrap()
+    }
+    
+    #[test]
+    fn parses_tables_keys_and_indexes() {
+        let model = parse_model(MODEL).unwrap();
+        assert_eq!(model.platform, "Sql160");
+        
+        let tables: Vec<(&str, &str, ObjectKind)> = model.tables.iter()
+            .map(|t| (t.schema.as_str(), t.name.as_str(), t.object_kind))
+            .collect();
+        assert_eq!(tables, [
+            ("sales", "Customer", ObjectKind::BaseTable),
+            ("sales", "Orders", ObjectKind::BaseTable),
+            ("sales", "Big]Customers", ObjectKind::View),
+        ]);
+        assert_eq!(
+            model.tables[2].definition.as_deref(),
+            Some("CREATE VIEW [sales].[Big]]Customers] AS\nSELECT Id FROM sales.Customer"),
+        );
+        
+        let name = column(&model, "Customer", "Name");
+        assert_eq!((name.ordinal_position, name.full_type.as_str(), name.is_nullable), (2, "nvarchar(100)", true));
+        
+        let keys: Vec<(String, String)> = primary_keys_from_indexes(&model.indexes).into_iter()
+            .map(|pk| (pk.table_name, pk.column_name))
+            .collect();
+        assert_eq!(keys, [("Customer".to_string(), "Id".to_string())]);
+        assert_eq!(model.indexes[0].index_type, "CLUSTERED");
+        
+        let fk = &model.foreign_keys[0];
+        assert_eq!((fk.parent_table.as_str(), fk.referenced_table.as_str()), ("Orders", "Customer"));
+        assert_eq!((fk.columns[0].parent_column.as_str(), fk.columns[0].referenced_column.as_str()), ("CustomerId", "Id"));
+        assert_eq!(fk.delete_referential_action, "CASCADE");
+        
+        let index = &model.indexes[1];
+        assert_eq!((index.name.as_str(), index.index_type.as_str()), ("IX_Orders_CustomerId", "NONCLUSTERED"));
+        assert_eq!(index.key_columns[0].name, "CustomerId");
+        assert!(index.key_columns[0].is_descending);
+        assert_eq!(index.included_columns, ["Id"]);
+    }
+    
+    #[test]
+    fn identity_options_come_from_the_column() {
+        let model = parse_model(MODEL).unwrap();
+        let id = column(&model, "Customer", "Id");
+        assert_eq!((id.identity_seed, id.identity_increment), (Some(1000), Some(5)));
+        assert!(!id.is_nullable);
+        
+        // No options written means SQL Server's defaults
+        let id = column(&model, "Orders", "Id");
+        assert_eq!((id.identity_seed, id.identity_increment), (Some(1), Some(1)));
+        assert_eq!(column(&model, "Orders", "CustomerId").identity_seed, None);
+    }
+}
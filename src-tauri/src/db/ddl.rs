@@ -0,0 +1,1518 @@
+use std::cmp::Ordering;
+use std::path::{Path, PathBuf};
+use crate::config::Connection;
+use crate::commands::schema::{
+    Table, ObjectKind, Column, ForeignKey, ForeignKeyColumn, PrimaryKey, Index, IndexColumn,
+    CheckConstraint, DefaultConstraint, primary_keys_from_indexes,
+};
+use super::{Cancellation, ConnectError, ConnectErrorKind, ServerProbe, SchemaSource, run_blocking};
+use super::sqlserver::format_type;
+
+/// Schema of objects whose name is not qualified
+const DEFAULT_SCHEMA: &str = "dbo";
+
+/// Words that end a `DEFAULT` expression inside a column definition
+const COLUMN_OPTION_WORDS: &[&str] = &[
+    "CONSTRAINT", "NOT", "NULL", "PRIMARY", "UNIQUE", "REFERENCES", "FOREIGN", "CHECK", "COLLATE",
+    "IDENTITY", "FOR", "WITH", "SPARSE", "ROWGUIDCOL", "FILESTREAM", "PERSISTED",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    /// Bare word: keyword or unquoted identifier
+    Word,
+    /// `[bracketed]` or `"double quoted"` identifier
+    Quoted,
+    Number,
+    /// `'string'` or `N'string'`
+    Text,
+    Symbol,
+    /// `GO` on its own line
+    BatchEnd,
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    kind: TokenKind,
+    /// Identifier without its quotes; the symbol character for `Symbol`
+    text: String,
+    /// Byte range in the script
+    start: usize,
+    end: usize,
+}
+
+impl Token {
+    fn is_word(&self, word: &str) -> bool {
+        self.kind == TokenKind::Word && self.text.eq_ignore_ascii_case(word)
+    }
+    
+    fn is_symbol(&self, symbol: char) -> bool {
+        self.kind == TokenKind::Symbol && self.text.starts_with(symbol)
+    }
+}
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || matches!(b, b'_' | b'@' | b'#' | b'$') || b >= 0x80
+}
+
+/// Read a quoted identifier or string starting at `start`, where a doubled closing
+/// character stands for itself. Returns the unquoted text and the end of the token.
+fn read_quoted(source: &str, start: usize, close: u8) -> (String, usize) {
+    let bytes = source.as_bytes();
+    let mut text = String::new();
+    let mut i = start + 1;
+    let mut from = i;
+    while i < bytes.len() {
+        if bytes[i] == close {
+            text.push_str(&source[from..i]);
+            if bytes.get(i + 1) == Some(&close) {
+                text.push(close as char);
+                i += 2;
+                from = i;
+                continue;
+            }
+            return (text, i + 1);
+        }
+        i += 1;
+    }
+    text.push_str(&source[from..]);
+    (text, bytes.len())
+}
+
+/// Split a T-SQL script into tokens, dropping whitespace and comments
+fn tokenize(source: &str) -> Vec<Token> {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    // Nothing but whitespace so far on the current line, so a `GO` here separates batches
+    let mut line_start = true;
+    let mut i = 0;
+    
+    while i < bytes.len() {
+        let b = bytes[i];
+        let start = i;
+        
+        if b == b'\n' {
+            line_start = true;
+            i += 1;
+            continue;
+        }
+        if b.is_ascii_whitespace() {
+            i += 1;
+            continue;
+        }
+        if bytes[i..].starts_with(b"--") {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+            continue;
+        }
+        if bytes[i..].starts_with(b"/*") {
+            // Block comments nest in T-SQL
+            let mut depth = 0;
+            while i < bytes.len() {
+                if bytes[i..].starts_with(b"/*") {
+                    depth += 1;
+                    i += 2;
+                } else if bytes[i..].starts_with(b"*/") {
+                    depth -= 1;
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    i += 1;
+                }
+            }
+            continue;
+        }
+        
+        let (kind, text, end) = match b {
+            b'[' => {
+                let (text, end) = read_quoted(source, i, b']');
+                (TokenKind::Quoted, text, end)
+            }
+            b'"' => {
+                let (text, end) = read_quoted(source, i, b'"');
+                (TokenKind::Quoted, text, end)
+            }
+            b'\'' => {
+                let (text, end) = read_quoted(source, i, b'\'');
+                (TokenKind::Text, text, end)
+            }
+            b'N' | b'n' if bytes.get(i + 1) == Some(&b'\'') => {
+                let (text, end) = read_quoted(source, i + 1, b'\'');
+                (TokenKind::Text, text, end)
+            }
+            _ if b.is_ascii_digit() => {
+                let mut end = i;
+                while end < bytes.len() && (bytes[end].is_ascii_digit() || bytes[end] == b'.') {
+                    end += 1;
+                }
+                (TokenKind::Number, source[i..end].to_string(), end)
+            }
+            _ if is_word_byte(b) => {
+                let mut end = i;
+                while end < bytes.len() && is_word_byte(bytes[end]) {
+                    end += 1;
+                }
+                let word = &source[i..end];
+                let kind = if line_start && word.eq_ignore_ascii_case("GO") { TokenKind::BatchEnd } else { TokenKind::Word };
+                (kind, word.to_string(), end)
+            }
+            _ => {
+                let c = source[i..].chars().next().unwrap_or(' ');
+                (TokenKind::Symbol, c.to_string(), i + c.len_utf8())
+            }
+        };
+        
+        tokens.push(Token { kind, text, start, end });
+        line_start = false;
+        i = end;
+    }
+    
+    tokens
+}
+
+/// Everything the scripts have declared so far. Later statements alter or drop
+/// what earlier ones created, so a folder of migrations replays to its final state.
+#[derive(Debug, Default)]
+struct Model {
+    tables: Vec<Table>,
+    columns: Vec<Column>,
+    foreign_keys: Vec<ForeignKey>,
+    indexes: Vec<Index>,
+    check_constraints: Vec<CheckConstraint>,
+    default_constraints: Vec<DefaultConstraint>,
+}
+
+fn same_table(schema: &str, table: &str, other_schema: &str, other_table: &str) -> bool {
+    schema.eq_ignore_ascii_case(other_schema) && table.eq_ignore_ascii_case(other_table)
+}
+
+impl Model {
+    fn drop_object(&mut self, schema: &str, name: &str) {
+        self.tables.retain(|t| !same_table(&t.schema, &t.name, schema, name));
+        self.columns.retain(|c| !same_table(&c.table_schema, &c.table_name, schema, name));
+        self.foreign_keys.retain(|f| !same_table(&f.parent_schema, &f.parent_table, schema, name));
+        self.indexes.retain(|i| !same_table(&i.table_schema, &i.table_name, schema, name));
+        self.check_constraints.retain(|c| !same_table(&c.table_schema, &c.table_name, schema, name));
+        self.default_constraints.retain(|d| !same_table(&d.table_schema, &d.table_name, schema, name));
+    }
+    
+    fn drop_constraint(&mut self, schema: &str, table: &str, name: &str) {
+        self.indexes.retain(|i| {
+            !(same_table(&i.table_schema, &i.table_name, schema, table) && i.name.eq_ignore_ascii_case(name))
+        });
+        self.foreign_keys.retain(|f| {
+            !(same_table(&f.parent_schema, &f.parent_table, schema, table) && f.constraint_name.eq_ignore_ascii_case(name))
+        });
+        self.check_constraints.retain(|c| {
+            !(same_table(&c.table_schema, &c.table_name, schema, table) && c.constraint_name.eq_ignore_ascii_case(name))
+        });
+        let mut dropped_default = None;
+        self.default_constraints.retain(|d| {
+            let matches = same_table(&d.table_schema, &d.table_name, schema, table) && d.constraint_name.eq_ignore_ascii_case(name);
+            if matches {
+                dropped_default = Some(d.column_name.clone());
+            }
+            !matches
+        });
+        if let Some(column) = dropped_default.and_then(|c| self.column_mut(schema, table, &c)) {
+            column.default_value = None;
+        }
+    }
+    
+    fn drop_column(&mut self, schema: &str, table: &str, name: &str) {
+        self.columns.retain(|c| {
+            !(same_table(&c.table_schema, &c.table_name, schema, table) && c.name.eq_ignore_ascii_case(name))
+        });
+        self.default_constraints.retain(|d| {
+            !(same_table(&d.table_schema, &d.table_name, schema, table) && d.column_name.eq_ignore_ascii_case(name))
+        });
+    }
+    
+    fn column_mut(&mut self, schema: &str, table: &str, name: &str) -> Option<&mut Column> {
+        self.columns.iter_mut().find(|c| {
+            same_table(&c.table_schema, &c.table_name, schema, table) && c.name.eq_ignore_ascii_case(name)
+        })
+    }
+    
+    fn next_ordinal(&self, schema: &str, table: &str) -> i32 {
+        self.columns.iter()
+            .filter(|c| same_table(&c.table_schema, &c.table_name, schema, table))
+            .map(|c| c.ordinal_position)
+            .max()
+            .unwrap_or(0) + 1
+    }
+    
+    /// Enable or disable foreign key and check constraints; `None` means all of the table's.
+    /// Re-enabled constraints are only trusted again when existing rows were `validated`.
+    fn set_constraint_enabled(&mut self, schema: &str, table: &str, names: Option<&[String]>, enabled: bool, validated: bool) {
+        let selected = |name: &str| names.is_none_or(|names| names.iter().any(|n| n.eq_ignore_ascii_case(name)));
+        let trust = |is_not_trusted: &mut bool| {
+            if !enabled {
+                *is_not_trusted = true;
+            } else if validated {
+                *is_not_trusted = false;
+            }
+        };
+        for fk in &mut self.foreign_keys {
+            if same_table(&fk.parent_schema, &fk.parent_table, schema, table) && selected(&fk.constraint_name) {
+                fk.is_disabled = !enabled;
+                trust(&mut fk.is_not_trusted);
+            }
+        }
+        for check in &mut self.check_constraints {
+            if same_table(&check.table_schema, &check.table_name, schema, table) && selected(&check.constraint_name) {
+                check.is_disabled = !enabled;
+                trust(&mut check.is_not_trusted);
+            }
+        }
+    }
+    
+    /// Settle what can only be known once every script has run: references that name no
+    /// columns point at the referenced table's primary key
+    fn finish(&mut self) {
+        let primary_keys = primary_keys_from_indexes(&self.indexes);
+        for fk in &mut self.foreign_keys {
+            if fk.columns.iter().all(|c| c.referenced_column.is_empty()) {
+                let key: Vec<&PrimaryKey> = primary_keys.iter()
+                    .filter(|pk| same_table(&pk.table_schema, &pk.table_name, &fk.referenced_schema, &fk.referenced_table))
+                    .collect();
+                for (column, pk) in fk.columns.iter_mut().zip(key) {
+                    column.referenced_column = pk.column_name.clone();
+                }
+            }
+        }
+        
+        self.tables.sort_by(|a, b| (&a.schema, &a.name).cmp(&(&b.schema, &b.name)));
+        self.columns.sort_by(|a, b| {
+            (&a.table_schema, &a.table_name, a.ordinal_position).cmp(&(&b.table_schema, &b.table_name, b.ordinal_position))
+        });
+    }
+}
+
+/// A data type as (name, declared length, precision, scale)
+type DataType = (String, Option<i32>, Option<u8>, Option<u8>);
+
+/// The target of a foreign key and its actions
+struct Reference {
+    schema: String,
+    table: String,
+    columns: Vec<ForeignKeyColumn>,
+    delete_action: String,
+    update_action: String,
+}
+
+/// Map `CASCADE`, `SET NULL`, ... to the names SQL Server reports (`SET_NULL`)
+fn referential_action(words: &[String]) -> String {
+    words.join("_").to_uppercase()
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(source: &'a str) -> Self {
+        Self { source, tokens: tokenize(source), pos: 0 }
+    }
+    
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+    
+    fn peek_at(&self, offset: usize) -> Option<&Token> {
+        self.tokens.get(self.pos + offset)
+    }
+    
+    fn is_word(&self, word: &str) -> bool {
+        self.peek().is_some_and(|t| t.is_word(word))
+    }
+    
+    fn is_symbol(&self, symbol: char) -> bool {
+        self.peek().is_some_and(|t| t.is_symbol(symbol))
+    }
+    
+    fn eat_word(&mut self, word: &str) -> bool {
+        let found = self.is_word(word);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+    
+    fn eat_symbol(&mut self, symbol: char) -> bool {
+        let found = self.is_symbol(symbol);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+    
+    fn expect_word(&mut self, word: &str) -> Result<(), String> {
+        if self.eat_word(word) {
+            Ok(())
+        } else {
+            Err(format!("expected {} {}", word, self.found()))
+        }
+    }
+    
+    fn expect_symbol(&mut self, symbol: char) -> Result<(), String> {
+        if self.eat_symbol(symbol) {
+            Ok(())
+        } else {
+            Err(format!("expected '{}' {}", symbol, self.found()))
+        }
+    }
+    
+    /// Describe the current token for error messages
+    fn found(&self) -> String {
+        match self.peek() {
+            Some(token) => format!("at line {}, found '{}'", self.line_of(token.start), token.text),
+            None => "at end of script".to_string(),
+        }
+    }
+    
+    fn line_of(&self, offset: usize) -> usize {
+        self.source[..offset].matches('\n').count() + 1
+    }
+    
+    fn identifier(&mut self) -> Result<String, String> {
+        match self.peek() {
+            Some(token) if matches!(token.kind, TokenKind::Word | TokenKind::Quoted) => {
+                let text = token.text.clone();
+                self.pos += 1;
+                Ok(text)
+            }
+            _ => Err(format!("expected a name {}", self.found())),
+        }
+    }
+    
+    /// Read a one- to four-part name as (schema, object)
+    fn object_name(&mut self) -> Result<(String, String), String> {
+        let mut parts = vec![self.identifier()?];
+        while self.eat_symbol('.') {
+            parts.push(self.identifier()?);
+        }
+        let name = parts.pop().unwrap_or_default();
+        let schema = parts.pop().unwrap_or_else(|| DEFAULT_SCHEMA.to_string());
+        Ok((schema, name))
+    }
+    
+    /// A parenthesized, comma-separated list of names
+    fn name_list(&mut self) -> Result<Vec<String>, String> {
+        self.expect_symbol('(')?;
+        let mut names = Vec::new();
+        loop {
+            names.push(self.identifier()?);
+            if !self.eat_symbol(',') {
+                break;
+            }
+        }
+        self.expect_symbol(')')?;
+        Ok(names)
+    }
+    
+    /// Index key columns with their sort direction
+    fn index_columns(&mut self) -> Result<Vec<IndexColumn>, String> {
+        self.expect_symbol('(')?;
+        let mut columns = Vec::new();
+        loop {
+            let name = self.identifier()?;
+            let is_descending = self.eat_word("DESC");
+            self.eat_word("ASC");
+            columns.push(IndexColumn { name, is_descending });
+            if !self.eat_symbol(',') {
+                break;
+            }
+        }
+        self.expect_symbol(')')?;
+        Ok(columns)
+    }
+    
+    /// Skip a balanced parenthesized group and return its source text, parentheses included
+    fn group(&mut self) -> Result<String, String> {
+        let start = self.peek().map(|t| t.start).unwrap_or(0);
+        self.expect_symbol('(')?;
+        let mut depth = 1;
+        while depth > 0 {
+            match self.peek() {
+                Some(token) if token.is_symbol('(') => depth += 1,
+                Some(token) if token.is_symbol(')') => depth -= 1,
+                Some(token) if token.kind == TokenKind::BatchEnd => break,
+                Some(_) => {}
+                None => break,
+            }
+            self.pos += 1;
+        }
+        if depth > 0 {
+            return Err(format!("unbalanced parentheses {}", self.found()));
+        }
+        let end = self.tokens[self.pos - 1].end;
+        Ok(self.source[start..end].to_string())
+    }
+    
+    /// Read an expression up to a `,`, `)` or `;` outside parentheses, or one of `stop_words`
+    fn expression(&mut self, stop_words: &[&str]) -> Result<String, String> {
+        let start_pos = self.pos;
+        loop {
+            match self.peek() {
+                None => break,
+                Some(token) if token.kind == TokenKind::BatchEnd => break,
+                Some(token) if token.is_symbol(',') || token.is_symbol(')') || token.is_symbol(';') => break,
+                Some(token) if stop_words.iter().any(|w| token.is_word(w)) => break,
+                Some(token) if token.is_symbol('(') => {
+                    self.group()?;
+                }
+                Some(_) => self.pos += 1,
+            }
+        }
+        if self.pos == start_pos {
+            return Err(format!("expected an expression {}", self.found()));
+        }
+        let start = self.tokens[start_pos].start;
+        let end = self.tokens[self.pos - 1].end;
+        Ok(self.source[start..end].to_string())
+    }
+    
+    /// Skip `WITH (...)` index options and `ON [filegroup]` storage clauses
+    fn skip_storage_options(&mut self) -> Result<(), String> {
+        loop {
+            if self.is_word("WITH") && self.peek_at(1).is_some_and(|t| t.is_symbol('(')) {
+                self.pos += 1;
+                self.group()?;
+            } else if self.eat_word("ON") || self.eat_word("TEXTIMAGE_ON") || self.eat_word("FILESTREAM_ON") {
+                self.identifier()?;
+                if self.is_symbol('(') {
+                    self.group()?;
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+    
+    /// Skip to the end of the current batch
+    fn skip_batch(&mut self) {
+        while let Some(token) = self.peek() {
+            if token.kind == TokenKind::BatchEnd {
+                break;
+            }
+            self.pos += 1;
+        }
+    }
+    
+    /// Read a data type as (name, declared length, precision, scale)
+    fn data_type(&mut self) -> Result<DataType, String> {
+        let mut parts = vec![self.identifier()?];
+        while self.eat_symbol('.') {
+            parts.push(self.identifier()?);
+        }
+        let is_qualified = parts.len() > 1 && !parts[0].eq_ignore_ascii_case("sys");
+        let declared = parts.pop().unwrap_or_default();
+        // System types are reported in lowercase, as sys.types has them
+        let name = if is_qualified { declared } else { declared.to_lowercase() };
+        
+        let mut arguments = Vec::new();
+        if self.eat_symbol('(') {
+            loop {
+                match self.peek() {
+                    Some(token) if token.is_word("MAX") => arguments.push(-1),
+                    Some(token) if token.kind == TokenKind::Number => arguments.push(token.text.parse::<i64>().unwrap_or(0)),
+                    _ => return Err(format!("expected a type size {}", self.found())),
+                }
+                self.pos += 1;
+                if !self.eat_symbol(',') {
+                    break;
+                }
+            }
+            self.expect_symbol(')')?;
+        }
+        
+        let first = arguments.first().copied();
+        let facets = match name.as_str() {
+            // Without a size these types hold a single character
+            "varchar" | "char" | "varbinary" | "binary" | "nvarchar" | "nchar" => {
+                (Some(first.and_then(|l| i32::try_from(l).ok()).unwrap_or(1)), None, None)
+            }
+            "decimal" | "numeric" => (
+                None,
+                Some(first.and_then(|p| u8::try_from(p).ok()).unwrap_or(18)),
+                Some(arguments.get(1).and_then(|&s| u8::try_from(s).ok()).unwrap_or(0)),
+            ),
+            "datetime2" | "datetimeoffset" | "time" => {
+                (None, None, Some(first.and_then(|s| u8::try_from(s).ok()).unwrap_or(7)))
+            }
+            _ => (None, None, None),
+        };
+        
+        Ok((name, facets.0, facets.1, facets.2))
+    }
+    
+    /// Whether the next item of a table definition is a constraint rather than a column
+    fn at_constraint(&self) -> bool {
+        ["CONSTRAINT", "PRIMARY", "UNIQUE", "FOREIGN", "CHECK", "DEFAULT", "INDEX"]
+            .iter()
+            .any(|w| self.is_word(w))
+    }
+    
+    /// Whether the current item of a table definition is finished
+    fn at_item_end(&self) -> bool {
+        match self.peek() {
+            None => true,
+            Some(token) => token.kind == TokenKind::BatchEnd || token.is_symbol(',') || token.is_symbol(')') || token.is_symbol(';'),
+        }
+    }
+    
+    /// `CLUSTERED` or `NONCLUSTERED`, if given
+    fn index_kind(&mut self, default: &str) -> String {
+        let kind = if self.eat_word("CLUSTERED") {
+            "CLUSTERED"
+        } else if self.eat_word("NONCLUSTERED") {
+            "NONCLUSTERED"
+        } else {
+            default
+        };
+        if self.eat_word("COLUMNSTORE") {
+            format!("{} COLUMNSTORE", kind)
+        } else {
+            kind.to_string()
+        }
+    }
+    
+    /// Read the `REFERENCES` clause of a foreign key, after the keyword
+    fn references(&mut self, parent_columns: Vec<String>) -> Result<Reference, String> {
+        let (referenced_schema, referenced_table) = self.object_name()?;
+        let referenced_columns = if self.is_symbol('(') { self.name_list()? } else { Vec::new() };
+        let columns = parent_columns.into_iter()
+            .enumerate()
+            .map(|(i, parent_column)| ForeignKeyColumn {
+                parent_column,
+                referenced_column: referenced_columns.get(i).cloned().unwrap_or_default(),
+            })
+            .collect();
+        
+        let mut delete_action = "NO_ACTION".to_string();
+        let mut update_action = "NO_ACTION".to_string();
+        loop {
+            if self.eat_word("ON") {
+                let is_delete = self.eat_word("DELETE");
+                if !is_delete {
+                    self.expect_word("UPDATE")?;
+                }
+                let mut words = vec![self.identifier()?];
+                if words[0].eq_ignore_ascii_case("SET") || words[0].eq_ignore_ascii_case("NO") {
+                    words.push(self.identifier()?);
+                }
+                if is_delete {
+                    delete_action = referential_action(&words);
+                } else {
+                    update_action = referential_action(&words);
+                }
+            } else if self.is_word("NOT") && self.peek_at(1).is_some_and(|t| t.is_word("FOR")) {
+                self.pos += 3; // NOT FOR REPLICATION
+            } else {
+                break;
+            }
+        }
+        
+        Ok(Reference {
+            schema: referenced_schema,
+            table: referenced_table,
+            columns,
+            delete_action,
+            update_action,
+        })
+    }
+    
+    /// Read a column definition of `CREATE TABLE` or `ALTER TABLE ... ADD`
+    fn column_definition(&mut self, model: &mut Model, schema: &str, table: &str, ordinal_position: i32) -> Result<(), String> {
+        let name = self.identifier()?;
+        let mut column = Column {
+            table_schema: schema.to_string(),
+            table_name: table.to_string(),
+            name: name.clone(),
+            data_type: String::new(),
+            full_type: String::new(),
+            is_nullable: true,
+            is_primary_key: false,
+            is_foreign_key: false,
+            ordinal_position,
+            description: None,
+            extended_properties: Default::default(),
+            max_length: None,
+            precision: None,
+            scale: None,
+            collation: None,
+            default_value: None,
+            identity_seed: None,
+            identity_increment: None,
+            computed_definition: None,
+            is_sparse: false,
+            is_rowguid: false,
+            is_filestream: false,
+        };
+        
+        if self.eat_word("AS") {
+            column.computed_definition = Some(self.expression(&["PERSISTED", "CONSTRAINT", "NOT", "NULL", "PRIMARY", "UNIQUE", "CHECK"])?);
+        } else {
+            let (data_type, max_length, precision, scale) = self.data_type()?;
+            column.full_type = format_type(&data_type, max_length, precision, scale);
+            column.data_type = data_type;
+            column.max_length = max_length;
+            column.precision = precision;
+            column.scale = scale;
+        }
+        
+        let mut constraint_name: Option<String> = None;
+        while !self.at_item_end() {
+            if self.eat_word("CONSTRAINT") {
+                constraint_name = Some(self.identifier()?);
+            } else if self.eat_word("NULL") {
+                column.is_nullable = true;
+            } else if self.eat_word("NOT") {
+                if self.eat_word("FOR") {
+                    self.expect_word("REPLICATION")?;
+                } else {
+                    self.expect_word("NULL")?;
+                    column.is_nullable = false;
+                }
+            } else if self.eat_word("IDENTITY") {
+                column.identity_seed = Some(1);
+                column.identity_increment = Some(1);
+                if self.eat_symbol('(') {
+                    column.identity_seed = self.expression(&[])?.trim().parse().ok();
+                    self.expect_symbol(',')?;
+                    column.identity_increment = self.expression(&[])?.trim().parse().ok();
+                    self.expect_symbol(')')?;
+                }
+            } else if self.eat_word("DEFAULT") {
+                let definition = self.expression(COLUMN_OPTION_WORDS)?;
+                model.default_constraints.push(DefaultConstraint {
+                    table_schema: schema.to_string(),
+                    table_name: table.to_string(),
+                    column_name: name.clone(),
+                    constraint_name: constraint_name.take().unwrap_or_else(|| format!("DF_{}_{}", table, name)),
+                    definition: definition.clone(),
+                });
+                column.default_value = Some(definition);
+                // `WITH VALUES` fills the new column for existing rows
+                if self.is_word("WITH") && self.peek_at(1).is_some_and(|t| t.is_word("VALUES")) {
+                    self.pos += 2;
+                }
+            } else if self.eat_word("PRIMARY") {
+                self.expect_word("KEY")?;
+                let index_type = self.index_kind("CLUSTERED");
+                self.skip_storage_options()?;
+                column.is_nullable = false;
+                model.indexes.push(Index {
+                    table_schema: schema.to_string(),
+                    table_name: table.to_string(),
+                    name: constraint_name.take().unwrap_or_else(|| format!("PK_{}", table)),
+                    index_type,
+                    is_unique: true,
+                    is_primary_key: true,
+                    is_unique_constraint: false,
+                    key_columns: vec![IndexColumn { name: name.clone(), is_descending: false }],
+                    included_columns: Vec::new(),
+                    filter_definition: None,
+                });
+            } else if self.eat_word("UNIQUE") {
+                let index_type = self.index_kind("NONCLUSTERED");
+                self.skip_storage_options()?;
+                model.indexes.push(Index {
+                    table_schema: schema.to_string(),
+                    table_name: table.to_string(),
+                    name: constraint_name.take().unwrap_or_else(|| format!("UQ_{}_{}", table, name)),
+                    index_type,
+                    is_unique: true,
+                    is_primary_key: false,
+                    is_unique_constraint: true,
+                    key_columns: vec![IndexColumn { name: name.clone(), is_descending: false }],
+                    included_columns: Vec::new(),
+                    filter_definition: None,
+                });
+            } else if self.is_word("FOREIGN") || self.is_word("REFERENCES") {
+                if self.eat_word("FOREIGN") {
+                    self.expect_word("KEY")?;
+                }
+                self.expect_word("REFERENCES")?;
+                let reference = self.references(vec![name.clone()])?;
+                model.foreign_keys.push(ForeignKey {
+                    constraint_name: constraint_name.take().unwrap_or_else(|| format!("FK_{}_{}", table, reference.table)),
+                    parent_schema: schema.to_string(),
+                    parent_table: table.to_string(),
+                    referenced_schema: reference.schema,
+                    referenced_table: reference.table,
+                    columns: reference.columns,
+                    delete_referential_action: reference.delete_action,
+                    update_referential_action: reference.update_action,
+                    is_disabled: false,
+                    is_not_trusted: false,
+                });
+            } else if self.eat_word("CHECK") {
+                if self.eat_word("NOT") {
+                    self.expect_word("FOR")?;
+                    self.expect_word("REPLICATION")?;
+                }
+                let definition = self.group()?;
+                model.check_constraints.push(CheckConstraint {
+                    table_schema: schema.to_string(),
+                    table_name: table.to_string(),
+                    constraint_name: constraint_name.take().unwrap_or_else(|| format!("CK_{}_{}", table, name)),
+                    column_name: Some(name.clone()),
+                    definition,
+                    is_disabled: false,
+                    is_not_trusted: false,
+                });
+            } else if self.eat_word("COLLATE") {
+                column.collation = Some(self.identifier()?);
+            } else if self.eat_word("SPARSE") {
+                column.is_sparse = true;
+            } else if self.eat_word("ROWGUIDCOL") {
+                column.is_rowguid = true;
+            } else if self.eat_word("FILESTREAM") {
+                column.is_filestream = true;
+            } else if self.is_symbol('(') {
+                self.group()?;
+            } else {
+                // PERSISTED, MASKED WITH, GENERATED ALWAYS, ... carry nothing the model keeps
+                self.pos += 1;
+            }
+        }
+        
+        model.columns.push(column);
+        Ok(())
+    }
+    
+    /// Read a table constraint of `CREATE TABLE` or `ALTER TABLE ... ADD`
+    fn table_constraint(&mut self, model: &mut Model, schema: &str, table: &str, not_trusted: bool) -> Result<(), String> {
+        let name = if self.eat_word("CONSTRAINT") { Some(self.identifier()?) } else { None };
+        
+        if self.eat_word("PRIMARY") || self.eat_word("UNIQUE") {
+            let is_primary_key = self.tokens[self.pos - 1].is_word("PRIMARY");
+            if is_primary_key {
+                self.expect_word("KEY")?;
+            }
+            let index_type = self.index_kind(if is_primary_key { "CLUSTERED" } else { "NONCLUSTERED" });
+            let key_columns = self.index_columns()?;
+            self.skip_storage_options()?;
+            
+            if is_primary_key {
+                for key in &key_columns {
+                    if let Some(column) = model.column_mut(schema, table, &key.name) {
+                        column.is_nullable = false;
+                    }
+                }
+            }
+            
+            let name = name.unwrap_or_else(|| {
+                if is_primary_key {
+                    format!("PK_{}", table)
+                } else {
+                    let columns: Vec<&str> = key_columns.iter().map(|c| c.name.as_str()).collect();
+                    format!("UQ_{}_{}", table, columns.join("_"))
+                }
+            });
+            model.indexes.push(Index {
+                table_schema: schema.to_string(),
+                table_name: table.to_string(),
+                name,
+                index_type,
+                is_unique: true,
+                is_primary_key,
+                is_unique_constraint: !is_primary_key,
+                key_columns,
+                included_columns: Vec::new(),
+                filter_definition: None,
+            });
+        } else if self.eat_word("FOREIGN") {
+            self.expect_word("KEY")?;
+            let parent_columns = self.name_list()?;
+            self.expect_word("REFERENCES")?;
+            let reference = self.references(parent_columns)?;
+            model.foreign_keys.push(ForeignKey {
+                constraint_name: name.unwrap_or_else(|| format!("FK_{}_{}", table, reference.table)),
+                parent_schema: schema.to_string(),
+                parent_table: table.to_string(),
+                referenced_schema: reference.schema,
+                referenced_table: reference.table,
+                columns: reference.columns,
+                delete_referential_action: reference.delete_action,
+                update_referential_action: reference.update_action,
+                is_disabled: false,
+                is_not_trusted: not_trusted,
+            });
+        } else if self.eat_word("CHECK") {
+            if self.eat_word("NOT") {
+                self.expect_word("FOR")?;
+                self.expect_word("REPLICATION")?;
+            }
+            let definition = self.group()?;
+            model.check_constraints.push(CheckConstraint {
+                table_schema: schema.to_string(),
+                table_name: table.to_string(),
+                constraint_name: name.unwrap_or_else(|| format!("CK_{}", table)),
+                column_name: None,
+                definition,
+                is_disabled: false,
+                is_not_trusted: not_trusted,
+            });
+        } else if self.eat_word("DEFAULT") {
+            let definition = self.expression(&["FOR"])?;
+            self.expect_word("FOR")?;
+            let column_name = self.identifier()?;
+            if let Some(column) = model.column_mut(schema, table, &column_name) {
+                column.default_value = Some(definition.clone());
+            }
+            model.default_constraints.push(DefaultConstraint {
+                table_schema: schema.to_string(),
+                table_name: table.to_string(),
+                constraint_name: name.unwrap_or_else(|| format!("DF_{}_{}", table, column_name)),
+                column_name,
+                definition,
+            });
+        } else if self.eat_word("INDEX") {
+            // Inline index of CREATE TABLE (SQL Server 2014+)
+            let index_name = self.identifier()?;
+            let is_unique = self.eat_word("UNIQUE");
+            let index_type = self.index_kind("NONCLUSTERED");
+            let key_columns = if self.is_symbol('(') { self.index_columns()? } else { Vec::new() };
+            self.skip_storage_options()?;
+            model.indexes.push(Index {
+                table_schema: schema.to_string(),
+                table_name: table.to_string(),
+                name: index_name,
+                index_type,
+                is_unique,
+                is_primary_key: false,
+                is_unique_constraint: false,
+                key_columns,
+                included_columns: Vec::new(),
+                filter_definition: None,
+            });
+        } else {
+            return Err(format!("expected a constraint {}", self.found()));
+        }
+        
+        Ok(())
+    }
+    
+    /// `CREATE TABLE`, after the keywords
+    fn create_table(&mut self, model: &mut Model) -> Result<(), String> {
+        let (schema, name) = self.object_name()?;
+        // Temporary tables are not part of the schema
+        if name.starts_with('#') {
+            return Ok(());
+        }
+        self.expect_symbol('(')?;
+        
+        model.drop_object(&schema, &name);
+        model.tables.push(Table {
+            schema: schema.clone(),
+            name: name.clone(),
+            description: None,
+            object_kind: ObjectKind::BaseTable,
+            definition: None,
+            synonym_target: None,
+            extended_properties: Default::default(),
+        });
+        
+        let mut ordinal_position = 0;
+        loop {
+            if self.at_constraint() {
+                self.table_constraint(model, &schema, &name, false)?;
+            } else {
+                ordinal_position += 1;
+                self.column_definition(model, &schema, &name, ordinal_position)?;
+            }
+            if !self.eat_symbol(',') {
+                break;
+            }
+            // A trailing comma before the closing parenthesis is tolerated by SSMS scripts
+            if self.is_symbol(')') {
+                break;
+            }
+        }
+        self.expect_symbol(')')
+    }
+    
+    /// `CREATE VIEW`; the definition runs from `statement_start` to the end of the batch
+    fn create_view(&mut self, model: &mut Model, statement_start: usize) -> Result<(), String> {
+        let (schema, name) = self.object_name()?;
+        self.skip_batch();
+        
+        let end = self.tokens[self.pos - 1].end;
+        let definition = self.source[statement_start..end].trim_end_matches(';').trim().to_string();
+        
+        model.drop_object(&schema, &name);
+        model.tables.push(Table {
+            schema,
+            name,
+            description: None,
+            object_kind: ObjectKind::View,
+            definition: Some(definition),
+            synonym_target: None,
+            extended_properties: Default::default(),
+        });
+        Ok(())
+    }
+    
+    /// `CREATE INDEX`, after `CREATE`
+    fn create_index(&mut self, model: &mut Model) -> Result<(), String> {
+        let is_unique = self.eat_word("UNIQUE");
+        let index_type = self.index_kind("NONCLUSTERED");
+        self.expect_word("INDEX")?;
+        let name = self.identifier()?;
+        self.expect_word("ON")?;
+        let (schema, table) = self.object_name()?;
+        // A clustered columnstore index covers every column and lists none
+        let key_columns = if self.is_symbol('(') { self.index_columns()? } else { Vec::new() };
+        let included_columns = if self.eat_word("INCLUDE") { self.name_list()? } else { Vec::new() };
+        let filter_definition = if self.eat_word("WHERE") { Some(self.expression(&["WITH", "ON"])?) } else { None };
+        self.skip_storage_options()?;
+        
+        // Re-creating an index (DROP_EXISTING) replaces it
+        model.indexes.retain(|i| !(same_table(&i.table_schema, &i.table_name, &schema, &table) && i.name.eq_ignore_ascii_case(&name)));
+        model.indexes.push(Index {
+            table_schema: schema,
+            table_name: table,
+            name,
+            index_type,
+            is_unique,
+            is_primary_key: false,
+            is_unique_constraint: false,
+            key_columns,
+            included_columns,
+            filter_definition,
+        });
+        Ok(())
+    }
+    
+    /// `ALTER TABLE`, after the keywords
+    fn alter_table(&mut self, model: &mut Model) -> Result<(), String> {
+        let (schema, table) = self.object_name()?;
+        
+        // `WITH CHECK` validates existing rows, `WITH NOCHECK` skips that; the default
+        // validates new constraints but not re-enabled ones
+        let mut validated = None;
+        if self.eat_word("WITH") {
+            validated = Some(!self.eat_word("NOCHECK"));
+            if validated == Some(true) {
+                self.expect_word("CHECK")?;
+            }
+        }
+        let not_trusted = validated == Some(false);
+        
+        if self.eat_word("ADD") {
+            loop {
+                if self.at_constraint() {
+                    self.table_constraint(model, &schema, &table, not_trusted)?;
+                } else {
+                    let ordinal_position = model.next_ordinal(&schema, &table);
+                    self.column_definition(model, &schema, &table, ordinal_position)?;
+                }
+                if !self.eat_symbol(',') {
+                    break;
+                }
+            }
+        } else if self.eat_word("DROP") {
+            loop {
+                let is_column = self.eat_word("COLUMN");
+                if !is_column {
+                    self.eat_word("CONSTRAINT");
+                }
+                if self.is_word("IF") && self.peek_at(1).is_some_and(|t| t.is_word("EXISTS")) {
+                    self.pos += 2;
+                }
+                loop {
+                    let name = self.identifier()?;
+                    if is_column {
+                        model.drop_column(&schema, &table, &name);
+                    } else {
+                        model.drop_constraint(&schema, &table, &name);
+                    }
+                    // `DROP COLUMN a, b` and `DROP CONSTRAINT a, b` list several names
+                    let continues = self.is_symbol(',')
+                        && !self.peek_at(1).is_some_and(|t| t.is_word("COLUMN") || t.is_word("CONSTRAINT"));
+                    if !continues {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                if !self.eat_symbol(',') {
+                    break;
+                }
+            }
+        } else if self.eat_word("ALTER") {
+            self.expect_word("COLUMN")?;
+            let name = self.identifier()?;
+            let (data_type, max_length, precision, scale) = self.data_type()?;
+            let collation = if self.eat_word("COLLATE") { Some(self.identifier()?) } else { None };
+            let is_nullable = !(self.is_word("NOT") && self.peek_at(1).is_some_and(|t| t.is_word("NULL")));
+            if let Some(column) = model.column_mut(&schema, &table, &name) {
+                column.full_type = format_type(&data_type, max_length, precision, scale);
+                column.data_type = data_type;
+                column.max_length = max_length;
+                column.precision = precision;
+                column.scale = scale;
+                column.is_nullable = is_nullable;
+                if collation.is_some() {
+                    column.collation = collation;
+                }
+            }
+        } else if self.eat_word("NOCHECK") || self.eat_word("CHECK") {
+            let enabled = self.tokens[self.pos - 1].is_word("CHECK");
+            self.expect_word("CONSTRAINT")?;
+            if self.eat_word("ALL") {
+                model.set_constraint_enabled(&schema, &table, None, enabled, validated == Some(true));
+            } else {
+                let mut names = vec![self.identifier()?];
+                while self.eat_symbol(',') {
+                    names.push(self.identifier()?);
+                }
+                model.set_constraint_enabled(&schema, &table, Some(&names), enabled, validated == Some(true));
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// `DROP TABLE`, `DROP VIEW` or `DROP INDEX`, after `DROP`
+    fn drop(&mut self, model: &mut Model) -> Result<(), String> {
+        let is_index = self.eat_word("INDEX");
+        if !is_index && !self.eat_word("TABLE") && !self.eat_word("VIEW") {
+            return Ok(());
+        }
+        if self.is_word("IF") && self.peek_at(1).is_some_and(|t| t.is_word("EXISTS")) {
+            self.pos += 2;
+        }
+        
+        loop {
+            let (schema, name) = self.object_name()?;
+            if is_index {
+                // `DROP INDEX name ON table`, or the older `DROP INDEX table.name`
+                let (schema, table, index) = if self.eat_word("ON") {
+                    let (schema, table) = self.object_name()?;
+                    (schema, table, name)
+                } else {
+                    (DEFAULT_SCHEMA.to_string(), schema, name)
+                };
+                model.indexes.retain(|i| !(same_table(&i.table_schema, &i.table_name, &schema, &table) && i.name.eq_ignore_ascii_case(&index)));
+            } else {
+                model.drop_object(&schema, &name);
+            }
+            if !self.eat_symbol(',') {
+                return Ok(());
+            }
+        }
+    }
+    
+    /// Read the statement at the current `CREATE`, `ALTER` or `DROP`
+    fn statement(&mut self, model: &mut Model) -> Result<(), String> {
+        let statement_start = self.peek().map(|t| t.start).unwrap_or(0);
+        
+        if self.eat_word("DROP") {
+            return self.drop(model);
+        }
+        
+        let is_create = self.eat_word("CREATE");
+        if is_create {
+            if self.is_word("OR") && self.peek_at(1).is_some_and(|t| t.is_word("ALTER")) {
+                self.pos += 2;
+            }
+        } else {
+            self.expect_word("ALTER")?;
+        }
+        
+        if ["PROC", "PROCEDURE", "FUNCTION", "TRIGGER"].iter().any(|w| self.is_word(w)) {
+            // Module bodies may create temporary tables; none of it belongs to the schema
+            self.skip_batch();
+            Ok(())
+        } else if self.eat_word("TABLE") {
+            if is_create { self.create_table(model) } else { self.alter_table(model) }
+        } else if self.eat_word("VIEW") {
+            self.create_view(model, statement_start)
+        } else if is_create && ["UNIQUE", "CLUSTERED", "NONCLUSTERED", "INDEX"].iter().any(|w| self.is_word(w)) {
+            self.create_index(model)
+        } else {
+            Ok(())
+        }
+    }
+    
+    /// Apply every supported statement of the script to the model. Statements that cannot be
+    /// read are skipped and reported in the returned warnings.
+    fn run(&mut self, model: &mut Model) -> Vec<String> {
+        let mut warnings = Vec::new();
+        while let Some(token) = self.peek() {
+            if !(token.is_word("CREATE") || token.is_word("ALTER") || token.is_word("DROP")) {
+                self.pos += 1;
+                continue;
+            }
+            let start = self.pos;
+            let line = self.line_of(token.start);
+            if let Err(e) = self.statement(model) {
+                warnings.push(format!("line {}: {}", line, e));
+                self.pos = start + 1;
+            }
+        }
+        warnings
+    }
+}
+
+/// Read a script, which SSMS may have saved as UTF-16
+fn read_script(path: &Path) -> Result<String, String> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    
+    let utf16 = |bytes: &[u8], decode: fn([u8; 2]) -> u16| -> String {
+        let units: Vec<u16> = bytes.chunks_exact(2).map(|c| decode([c[0], c[1]])).collect();
+        String::from_utf16_lossy(&units)
+    };
+    
+    Ok(match bytes.as_slice() {
+        [0xFF, 0xFE, rest @ ..] => utf16(rest, u16::from_le_bytes),
+        [0xFE, 0xFF, rest @ ..] => utf16(rest, u16::from_be_bytes),
+        [0xEF, 0xBB, 0xBF, rest @ ..] => String::from_utf8_lossy(rest).into_owned(),
+        _ => String::from_utf8_lossy(&bytes).into_owned(),
+    })
+}
+
+/// Compare names with runs of digits taken as numbers, so `V2__a.sql` sorts before `V10__b.sql`
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let digits = |s: &[u8]| s.iter().take_while(|c| c.is_ascii_digit()).count();
+    let (mut a, mut b) = (a.as_bytes(), b.as_bytes());
+    loop {
+        match (a.first(), b.first()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let (a_len, b_len) = (digits(a), digits(b));
+                let a_number = &a[a[..a_len].iter().take_while(|c| **c == b'0').count()..a_len];
+                let b_number = &b[b[..b_len].iter().take_while(|c| **c == b'0').count()..b_len];
+                // Without leading zeros, the longer number is the larger one
+                let order = a_number.len().cmp(&b_number.len()).then_with(|| a_number.cmp(b_number));
+                if order != Ordering::Equal {
+                    return order;
+                }
+                a = &a[a_len..];
+                b = &b[b_len..];
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(y);
+                }
+                a = &a[1..];
+                b = &b[1..];
+            }
+        }
+    }
+}
+
+/// Collect the `.sql` files below a folder in natural path order, so numbered migrations
+/// replay in sequence
+fn script_files(folder: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files = Vec::new();
+    let mut pending = vec![folder.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = std::fs::read_dir(&dir)
+            .map_err(|e| format!("Failed to read folder {}: {}", dir.display(), e))?;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else if path.extension().is_some_and(|e| e.eq_ignore_ascii_case("sql")) {
+                files.push(path);
+            }
+        }
+    }
+    files.sort_by(|a, b| {
+        let (a, b) = (a.to_string_lossy(), b.to_string_lossy());
+        natural_cmp(&a, &b).then_with(|| a.cmp(&b))
+    });
+    Ok(files)
+}
+
+/// Parse every script in the connection's folder into one model
+fn load_folder(connection: &Connection, cancellation: &Cancellation) -> Result<(PathBuf, usize, Model), ConnectError> {
+    let folder = match connection.file_path.as_deref().map(str::trim) {
+        Some(path) if !path.is_empty() => PathBuf::from(path),
+        _ => return Err(ConnectError::new(ConnectErrorKind::Configuration, "Script folder is required")),
+    };
+    
+    if !folder.is_dir() {
+        return Err(ConnectError::new(
+            ConnectErrorKind::DatabaseNotFound,
+            format!("Script folder not found: {}", folder.display()),
+        ));
+    }
+    
+    let files = script_files(&folder)
+        .map_err(|e| ConnectError::new(ConnectErrorKind::Other, e))?;
+    if files.is_empty() {
+        return Err(ConnectError::new(
+            ConnectErrorKind::DatabaseNotFound,
+            format!("No .sql files found in {}", folder.display()),
+        ));
+    }
+    
+    println!("Reading {} scripts from {}", files.len(), folder.display());
+    
+    let mut model = Model::default();
+    for file in &files {
+        if cancellation.is_cancelled() {
+            return Err(ConnectError::new(ConnectErrorKind::Other, "Reading the scripts was cancelled"));
+        }
+        let source = read_script(file)
+            .map_err(|e| ConnectError::new(ConnectErrorKind::Other, e))?;
+        for warning in Parser::new(&source).run(&mut model) {
+            println!("Skipped statement in {}, {}", file.display(), warning);
+        }
+    }
+    model.finish();
+    
+    Ok((folder, files.len(), model))
+}
+
+/// A folder of T-SQL DDL scripts, replayed in file order on the blocking thread pool. Only
+/// tables, views, keys, constraints and indexes are read; procedures and functions are skipped.
+pub struct DdlSource {
+    folder: PathBuf,
+    file_count: usize,
+    model: Model,
+}
+
+impl SchemaSource for DdlSource {
+    async fn connect(connection: &Connection, _password: Option<&str>) -> Result<Self, ConnectError> {
+        let connection = connection.clone();
+        let (folder, file_count, model) = run_blocking(move |cancellation| load_folder(&connection, cancellation))
+            .await
+            .unwrap_or_else(|e| Err(ConnectError::new(ConnectErrorKind::Other, e)))?;
+        Ok(Self { folder, file_count, model })
+    }
+    
+    async fn probe(&mut self) -> Result<ServerProbe, String> {
+        Ok(ServerProbe {
+            server_version: format!("T-SQL scripts ({} files)", self.file_count),
+            database_name: self.folder.file_name()
+                .map(|n| n.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            login_name: String::new(),
+        })
+    }
+    
+    async fn get_tables(&mut self) -> Result<Vec<Table>, String> {
+        Ok(std::mem::take(&mut self.model.tables))
+    }
+    
+    async fn get_columns(&mut self) -> Result<Vec<Column>, String> {
+        Ok(std::mem::take(&mut self.model.columns))
+    }
+    
+    async fn get_primary_keys(&mut self) -> Result<Vec<PrimaryKey>, String> {
+        Ok(primary_keys_from_indexes(&self.model.indexes))
+    }
+    
+    async fn get_foreign_keys(&mut self) -> Result<Vec<ForeignKey>, String> {
+        Ok(std::mem::take(&mut self.model.foreign_keys))
+    }
+    
+    async fn get_indexes(&mut self) -> Result<Vec<Index>, String> {
+        // Primary keys are read from the indexes too, so they stay in the model
+        Ok(self.model.indexes.clone())
+    }
+    
+    async fn get_check_constraints(&mut self) -> Result<Vec<CheckConstraint>, String> {
+        Ok(std::mem::take(&mut self.model.check_constraints))
+    }
+    
+    async fn get_default_constraints(&mut self) -> Result<Vec<DefaultConstraint>, String> {
+        Ok(std::mem::take(&mut self.model.default_constraints))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn parse(source: &str) -> (Model, Vec<String>) {
+        let mut model = Model::default();
+        let warnings = Parser::new(source).run(&mut model);
+        model.finish();
+        (model, warnings)
+    }
+    
+    fn table<'m>(model: &'m Model, name: &str) -> Vec<&'m Column> {
+        model.columns.iter().filter(|c| c.table_name == name).collect()
+    }
+    
+    fn index<'m>(model: &'m Model, name: &str) -> &'m Index {
+        model.indexes.iter().find(|i| i.name == name).unwrap()
+    }
+    
+    /// A folder below the temp directory, emptied for the test
+    fn scratch_folder(name: &str) -> PathBuf {
+        let folder = std::env::temp_dir().join(format!("glance-ddl-{}-{}", name, std::process::id()));
+        let _ = std::fs::remove_dir_all(&folder);
+        std::fs::create_dir_all(&folder).unwrap();
+        folder
+    }
+    
+    #[test]
+    fn create_table_with_inline_constraints() {
+        let (model, warnings) = parse(r#"
+            CREATE TABLE [sales].[Customer] (
+                Id int IDENTITY(1,1) NOT NULL CONSTRAINT PK_Customer PRIMARY KEY,
+                Name nvarchar(100) NOT NULL CONSTRAINT DF_Customer_Name DEFAULT (N'it''s'),
+                Age int NULL CHECK (Age >= 0),
+                Email varchar(200) NULL UNIQUE,
+                Balance decimal(18, 2)
+            );
+        "#);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        
+        let columns = table(&model, "Customer");
+        let names: Vec<&str> = columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["Id", "Name", "Age", "Email", "Balance"]);
+        assert_eq!((columns[0].identity_seed, columns[0].identity_increment), (Some(1), Some(1)));
+        assert!(!columns[1].is_nullable);
+        assert_eq!(columns[1].full_type, "nvarchar(100)");
+        assert_eq!(columns[1].default_value.as_deref(), Some("(N'it''s')"));
+        assert_eq!(columns[4].full_type, "decimal(18,2)");
+        assert!(columns[4].is_nullable);
+        
+        let pk = index(&model, "PK_Customer");
+        assert!(pk.is_primary_key);
+        assert_eq!(pk.key_columns[0].name, "Id");
+        assert!(index(&model, "UQ_Customer_Email").is_unique_constraint);
+        assert_eq!(model.check_constraints[0].column_name.as_deref(), Some("Age"));
+        assert_eq!(model.check_constraints[0].definition, "(Age >= 0)");
+        assert_eq!(model.default_constraints[0].constraint_name, "DF_Customer_Name");
+    }
+    
+    #[test]
+    fn out_of_line_and_altered_constraints() {
+        let (model, warnings) = parse(r#"
+            CREATE TABLE dbo.Customer (Id int NOT NULL PRIMARY KEY);
+            CREATE TABLE dbo.Orders (
+                OrderId int NOT NULL,
+                Line int NOT NULL,
+                CustomerId int NOT NULL REFERENCES dbo.Customer ON DELETE CASCADE,
+                Total decimal(18, 2) NULL,
+                CONSTRAINT PK_Orders PRIMARY KEY CLUSTERED (OrderId, Line),
+                CONSTRAINT CK_Total CHECK (Total > 0)
+            );
+            CREATE TABLE dbo.Shipment (OrderId int NOT NULL, Line int NOT NULL);
+            ALTER TABLE dbo.Shipment ADD CONSTRAINT FK_Shipment_Orders
+                FOREIGN KEY (OrderId, Line) REFERENCES dbo.Orders (OrderId, Line);
+            ALTER TABLE dbo.Orders NOCHECK CONSTRAINT CK_Total;
+        "#);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        
+        let pk = index(&model, "PK_Orders");
+        let key: Vec<&str> = pk.key_columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(key, ["OrderId", "Line"]);
+        
+        // A bare REFERENCES points at the referenced table's primary key
+        let customer = model.foreign_keys.iter().find(|f| f.referenced_table == "Customer").unwrap();
+        assert_eq!(customer.delete_referential_action, "CASCADE");
+        assert_eq!(customer.columns[0].referenced_column, "Id");
+        
+        let shipment = model.foreign_keys.iter().find(|f| f.constraint_name == "FK_Shipment_Orders").unwrap();
+        let pairs: Vec<(&str, &str)> = shipment.columns.iter()
+            .map(|c| (c.parent_column.as_str(), c.referenced_column.as_str()))
+            .collect();
+        assert_eq!(pairs, [("OrderId", "OrderId"), ("Line", "Line")]);
+        
+        let check = model.check_constraints.iter().find(|c| c.constraint_name == "CK_Total").unwrap();
+        assert!(check.column_name.is_none());
+        assert!(check.is_disabled && check.is_not_trusted);
+    }
+    
+    #[test]
+    fn create_index_with_include_and_filter() {
+        let (model, warnings) = parse(r#"
+            CREATE TABLE dbo.Orders (Id int NOT NULL, CustomerId int, Total money);
+            CREATE UNIQUE NONCLUSTERED INDEX IX_Orders_Customer ON dbo.Orders (CustomerId DESC, Id)
+                INCLUDE (Total) WHERE Total IS NOT NULL WITH (FILLFACTOR = 90);
+        "#);
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        
+        let ix = index(&model, "IX_Orders_Customer");
+        assert!(ix.is_unique && !ix.is_primary_key);
+        assert_eq!(ix.index_type, "NONCLUSTERED");
+        assert!(ix.key_columns[0].is_descending && !ix.key_columns[1].is_descending);
+        assert_eq!(ix.included_columns, ["Total"]);
+        assert_eq!(ix.filter_definition.as_deref(), Some("Total IS NOT NULL"));
+    }
+    
+    #[test]
+    fn views_end_at_go() {
+        let (model, warnings) = parse("CREATE TABLE dbo.Orders (Total int)\nGO\nCREATE VIEW dbo.BigOrders AS\n    SELECT Total FROM dbo.Orders WHERE Total > 100;\ngo\nCREATE TABLE dbo.After (x int)\n");
+        assert!(warnings.is_empty(), "{:?}", warnings);
+        
+        let view = model.tables.iter().find(|t| t.name == "BigOrders").unwrap();
+        assert_eq!(view.object_kind, ObjectKind::View);
+        assert_eq!(
+            view.definition.as_deref(),
+            Some("CREATE VIEW dbo.BigOrders AS\n    SELECT Total FROM dbo.Orders WHERE Total > 100"),
+        );
+        assert!(model.tables.iter().any(|t| t.name == "After"));
+    }
+    
+    #[test]
+    fn comments_and_strings_hide_statements() {
+        let (model, _) = parse(r#"
+            /* outer /* inner */ CREATE TABLE dbo.Hidden (x int) */
+            -- CREATE TABLE dbo.Commented (x int)
+            CREATE TABLE dbo.Shown (Note varchar(50) DEFAULT 'CREATE TABLE dbo.Quoted (x int)');
+        "#);
+        let names: Vec<&str> = model.tables.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, ["Shown"]);
+    }
+    
+    #[test]
+    fn utf16_scripts() {
+        let folder = scratch_folder("utf16");
+        let script = "CREATE TABLE dbo.Ünïcode (Id int)";
+        let units: Vec<u16> = script.encode_utf16().collect();
+        
+        let little: Vec<u8> = [0xFF, 0xFE].into_iter().chain(units.iter().flat_map(|u| u.to_le_bytes())).collect();
+        let big: Vec<u8> = [0xFE, 0xFF].into_iter().chain(units.iter().flat_map(|u| u.to_be_bytes())).collect();
+        std::fs::write(folder.join("le.sql"), little).unwrap();
+        std::fs::write(folder.join("be.sql"), big).unwrap();
+        
+        assert_eq!(read_script(&folder.join("le.sql")).unwrap(), script);
+        assert_eq!(read_script(&folder.join("be.sql")).unwrap(), script);
+        let _ = std::fs::remove_dir_all(&folder);
+    }
+    
+    #[test]
+    fn migrations_replay_in_version_order() {
+        assert_eq!(natural_cmp("V2__b.sql", "V10__a.sql"), Ordering::Less);
+        assert_eq!(natural_cmp("V010__a.sql", "V9__a.sql"), Ordering::Greater);
+        assert_eq!(natural_cmp("V1_2__a.sql", "V1_10__a.sql"), Ordering::Less);
+        
+        let folder = scratch_folder("migrations");
+        std::fs::write(folder.join("V1__create.sql"), "CREATE TABLE dbo.T (a int)").unwrap();
+        std::fs::write(folder.join("V2__add.sql"), "ALTER TABLE dbo.T ADD b int").unwrap();
+        std::fs::write(folder.join("V10__drop.sql"), "ALTER TABLE dbo.T DROP COLUMN a").unwrap();
+        
+        let files = script_files(&folder).unwrap();
+        let names: Vec<String> = files.iter().map(|f| f.file_name().unwrap().to_string_lossy().into_owned()).collect();
+        assert_eq!(names, ["V1__create.sql", "V2__add.sql", "V10__drop.sql"]);
+        
+        let mut model = Model::default();
+        for file in &files {
+            let warnings = Parser::new(&read_script(file).unwrap()).run(&mut model);
+            assert!(warnings.is_empty(), "{:?}", warnings);
+        }
+        let columns: Vec<&str> = model.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(columns, ["b"]);
+        let _ = std::fs::remove_dir_all(&folder);
+    }
+}
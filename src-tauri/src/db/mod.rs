@@ -1,2 +1,737 @@
 pub mod sqlserver;
+pub mod postgres;
+pub mod mysql;
+pub mod sqlite;
+pub mod ddl;
+pub mod dacpac;
+pub mod ssh;
 pub mod cache;
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::future::Future;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use crate::config::{Connection, EncryptionMode, Provider};
+use crate::commands::schema::{
+    Table, Column, ForeignKey, PrimaryKey, Index, CheckConstraint, DefaultConstraint, Routine,
+    SchemaData, unique_constraints_from_indexes,
+};
+use dacpac::DacpacSource;
+use ddl::DdlSource;
+use mysql::MySqlSource;
+use postgres::PostgresSource;
+use sqlite::SqliteSource;
+use sqlserver::SqlServerSource;
+
+/// Broad category of a failed connection attempt, so the UI can tell users what to fix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ConnectErrorKind {
+    /// The connection settings themselves are invalid (missing username, unsupported auth, ...)
+    Configuration,
+    /// The server host name could not be resolved
+    Dns,
+    /// The host was reached but nothing is listening on the port
+    TcpRefused,
+    /// Any other network failure (unreachable host, reset, timeout)
+    Network,
+    /// The TLS handshake with the server failed
+    Tls,
+    /// The server rejected the credentials
+    LoginFailed,
+    /// The login succeeded but the database does not exist or is not accessible
+    DatabaseNotFound,
+    /// The SSH tunnel could not be opened (bastion host key or login rejected, forward refused)
+    Ssh,
+    Other,
+}
+
+/// A connection failure together with its category
+#[derive(Debug, Clone)]
+pub struct ConnectError {
+    pub kind: ConnectErrorKind,
+    pub message: String,
+}
+
+impl ConnectError {
+    pub(crate) fn new(kind: ConnectErrorKind, message: impl Into<String>) -> Self {
+        Self { kind, message: message.into() }
+    }
+    
+    /// Whether the same attempt may succeed a moment later (a dropped link, a server restarting)
+    fn is_transient(&self) -> bool {
+        matches!(self.kind, ConnectErrorKind::Network | ConnectErrorKind::TcpRefused)
+    }
+}
+
+/// Wait before the first retry; doubled for every further attempt
+const RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Open a session within the connection's timeout, retrying transient failures with backoff
+async fn connect_with_retry<S: SchemaSource>(connection: &Connection, password: Option<&str>) -> Result<S, ConnectError> {
+    let timeout = connection.connect_timeout();
+    let retries = connection.connect_retries();
+    let mut attempt = 0;
+    
+    loop {
+        let result = tokio::time::timeout(timeout, S::connect(connection, password))
+            .await
+            .unwrap_or_else(|_| Err(ConnectError::new(
+                ConnectErrorKind::Network,
+                format!("Timed out after {} seconds waiting for the server", timeout.as_secs()),
+            )));
+        
+        match result {
+            Err(e) if e.is_transient() && attempt < retries => {
+                let delay = RETRY_DELAY * 2u32.pow(attempt);
+                attempt += 1;
+                println!("Connection attempt {} failed: {}; retrying in {}s", attempt, e.message, delay.as_secs());
+                tokio::time::sleep(delay).await;
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Run one catalog query, giving up once the connection's query timeout has passed
+async fn query<T>(timeout: Duration, what: &str, query: impl Future<Output = Result<T, String>>) -> Result<T, String> {
+    tokio::time::timeout(timeout, query)
+        .await
+        .unwrap_or_else(|_| Err(format!("Timed out after {} seconds reading {}", timeout.as_secs(), what)))
+}
+
+/// Raised once nobody waits for a `run_blocking` job any more, e.g. because indexing was cancelled
+#[derive(Clone, Default)]
+pub(crate) struct Cancellation(Arc<AtomicBool>);
+
+impl Cancellation {
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Raises the cancellation when the future waiting for the job is dropped
+struct CancelOnDrop(Cancellation);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        (self.0).0.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Run blocking work, such as reading local files or the OS secret store, on the blocking
+/// thread pool so the caller can be cancelled meanwhile. The work should check its
+/// `Cancellation` between files or queries and give up once it is raised. Fails only if the
+/// work panicked.
+pub(crate) async fn run_blocking<T: Send + 'static>(work: impl FnOnce(&Cancellation) -> T + Send + 'static) -> Result<T, String> {
+    let cancellation = Cancellation::default();
+    let _guard = CancelOnDrop(cancellation.clone());
+    
+    tokio::task::spawn_blocking(move || work(&cancellation))
+        .await
+        .map_err(|e| format!("Background task failed: {}", e))
+}
+
+/// Basic facts about the session, used to confirm a connection actually works
+#[derive(Debug, Clone)]
+pub struct ServerProbe {
+    pub server_version: String,
+    pub database_name: String,
+    pub login_name: String,
+}
+
+/// Object (`column` is `None`) or column that descriptive properties are attached to,
+/// as (schema, object, column)
+pub type PropertyOwner = (String, String, Option<String>);
+
+/// Documentation attached to an object or column
+#[derive(Debug, Clone, Default)]
+pub struct ObjectProperties {
+    pub description: Option<String>,
+    /// Any other named properties
+    pub extended: BTreeMap<String, String>,
+}
+
+/// Creation and modification stamp of one object, compared between refreshes to find what changed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ObjectVersion {
+    pub object_id: i64,
+    pub schema: String,
+    pub name: String,
+    /// Opaque; differs whenever the object or anything attached to it was altered
+    pub stamp: String,
+}
+
+/// The schema as cached by the last refresh, for the next one to start from
+pub struct CachedSchema {
+    pub schema: SchemaData,
+    pub versions: Vec<ObjectVersion>,
+}
+
+/// A schema read from a connection
+pub struct Extraction {
+    pub schema: SchemaData,
+    pub server_version: String,
+    /// Object stamps to compare against on the next refresh; empty if the provider has none
+    pub versions: Vec<ObjectVersion>,
+}
+
+/// A database the schema can be read from.
+/// Each provider maps its own catalog onto the Glance model; metadata it has no
+/// equivalent for is left to the default methods, which report nothing.
+pub trait SchemaSource: Sized + Send {
+    /// Open a session for the connection
+    fn connect(connection: &Connection, password: Option<&str>) -> impl Future<Output = Result<Self, ConnectError>> + Send;
+    
+    /// Report the server version, current database and login
+    fn probe(&mut self) -> impl Future<Output = Result<ServerProbe, String>> + Send;
+    
+    fn get_tables(&mut self) -> impl Future<Output = Result<Vec<Table>, String>> + Send;
+    
+    fn get_columns(&mut self) -> impl Future<Output = Result<Vec<Column>, String>> + Send;
+    
+    fn get_primary_keys(&mut self) -> impl Future<Output = Result<Vec<PrimaryKey>, String>> + Send;
+    
+    fn get_foreign_keys(&mut self) -> impl Future<Output = Result<Vec<ForeignKey>, String>> + Send;
+    
+    fn get_indexes(&mut self) -> impl Future<Output = Result<Vec<Index>, String>> + Send;
+    
+    fn get_check_constraints(&mut self) -> impl Future<Output = Result<Vec<CheckConstraint>, String>> + Send {
+        async { Ok(Vec::new()) }
+    }
+    
+    fn get_default_constraints(&mut self) -> impl Future<Output = Result<Vec<DefaultConstraint>, String>> + Send {
+        async { Ok(Vec::new()) }
+    }
+    
+    fn get_routines(&mut self) -> impl Future<Output = Result<Vec<Routine>, String>> + Send {
+        async { Ok(Vec::new()) }
+    }
+    
+    /// Creation and modification stamps of every object, so a refresh can re-read only what
+    /// changed. Providers that report them must implement `set_scope` and return descriptions
+    /// from `get_properties`; `None` makes every refresh a full one.
+    fn get_object_versions(&mut self) -> impl Future<Output = Result<Option<Vec<ObjectVersion>>, String>> + Send {
+        async { Ok(None) }
+    }
+    
+    /// Limit the following catalog queries, except `get_properties`, to these objects
+    fn set_scope(&mut self, _object_ids: &[i64]) -> impl Future<Output = Result<(), String>> + Send {
+        async { Err("This provider cannot refresh incrementally".to_string()) }
+    }
+    
+    /// Descriptions kept apart from the objects themselves; providers that read them
+    /// together with tables and columns leave this empty
+    fn get_properties(&mut self) -> impl Future<Output = Result<HashMap<PropertyOwner, ObjectProperties>, String>> + Send {
+        async { Ok(HashMap::new()) }
+    }
+}
+
+/// Extract the complete schema for a connection from whichever provider it uses.
+/// Given the previously cached schema, only objects changed since are re-read where the
+/// provider can tell which those are.
+pub async fn extract_schema(connection: &Connection, password: Option<&str>, previous: Option<&CachedSchema>) -> Result<Extraction, String> {
+    match connection.provider {
+        Provider::SqlServer => extract_from::<SqlServerSource>(connection, password, previous).await,
+        Provider::Postgres => extract_from::<PostgresSource>(connection, password, previous).await,
+        Provider::MySql => extract_from::<MySqlSource>(connection, password, previous).await,
+        Provider::Sqlite => extract_from::<SqliteSource>(connection, password, previous).await,
+        Provider::DdlScripts => extract_from::<DdlSource>(connection, password, previous).await,
+        Provider::Dacpac => extract_from::<DacpacSource>(connection, password, previous).await,
+    }
+}
+
+/// Connect and run the provider's probe query
+pub async fn probe(connection: &Connection, password: Option<&str>) -> Result<ServerProbe, ConnectError> {
+    match connection.provider {
+        Provider::SqlServer => probe_with::<SqlServerSource>(connection, password).await,
+        Provider::Postgres => probe_with::<PostgresSource>(connection, password).await,
+        Provider::MySql => probe_with::<MySqlSource>(connection, password).await,
+        Provider::Sqlite => probe_with::<SqliteSource>(connection, password).await,
+        Provider::DdlScripts => probe_with::<DdlSource>(connection, password).await,
+        Provider::Dacpac => probe_with::<DacpacSource>(connection, password).await,
+    }
+}
+
+async fn probe_with<S: SchemaSource>(connection: &Connection, password: Option<&str>) -> Result<ServerProbe, ConnectError> {
+    let mut source = connect_with_retry::<S>(connection, password).await?;
+    query(connection.query_timeout(), "server details", source.probe())
+        .await
+        .map_err(|e| ConnectError::new(ConnectErrorKind::Other, e))
+}
+
+/// Objects an incremental refresh re-reads, and the cached entries it replaces
+struct RefreshPlan {
+    object_ids: Vec<i64>,
+    /// Lowercased (schema, name) of every cached object that changed, moved or disappeared
+    stale: HashSet<(String, String)>,
+}
+
+fn lowercase_name(schema: &str, name: &str) -> (String, String) {
+    (schema.to_lowercase(), name.to_lowercase())
+}
+
+/// Work out what changed since the cached schema; `None` if a full refresh is the better choice
+fn plan_refresh(previous: &CachedSchema, versions: &[ObjectVersion]) -> Option<RefreshPlan> {
+    if previous.versions.is_empty() {
+        return None;
+    }
+    
+    let old: HashMap<i64, &ObjectVersion> = previous.versions.iter().map(|v| (v.object_id, v)).collect();
+    let current: HashMap<i64, &ObjectVersion> = versions.iter().map(|v| (v.object_id, v)).collect();
+    let mut object_ids: HashSet<i64> = HashSet::new();
+    let mut stale = HashSet::new();
+    // Names that no longer exist, because the object was renamed, moved or dropped
+    let mut gone = HashSet::new();
+    
+    for version in versions {
+        match old.get(&version.object_id) {
+            Some(old) if *old == version => {}
+            Some(old) => {
+                stale.insert(lowercase_name(&old.schema, &old.name));
+                stale.insert(lowercase_name(&version.schema, &version.name));
+                if !old.schema.eq_ignore_ascii_case(&version.schema) || !old.name.eq_ignore_ascii_case(&version.name) {
+                    gone.insert(lowercase_name(&old.schema, &old.name));
+                }
+                object_ids.insert(version.object_id);
+            }
+            None => {
+                stale.insert(lowercase_name(&version.schema, &version.name));
+                object_ids.insert(version.object_id);
+            }
+        }
+    }
+    for old in previous.versions.iter().filter(|v| !current.contains_key(&v.object_id)) {
+        stale.insert(lowercase_name(&old.schema, &old.name));
+        gone.insert(lowercase_name(&old.schema, &old.name));
+    }
+    
+    // Foreign keys name the table they reference, so tables pointing at one that is gone are re-read
+    let by_name: HashMap<(String, String), i64> = versions.iter()
+        .map(|v| (lowercase_name(&v.schema, &v.name), v.object_id))
+        .collect();
+    for fk in &previous.schema.foreign_keys {
+        if gone.contains(&lowercase_name(&fk.referenced_schema, &fk.referenced_table)) {
+            let parent = lowercase_name(&fk.parent_schema, &fk.parent_table);
+            if let Some(&id) = by_name.get(&parent) {
+                object_ids.insert(id);
+                stale.insert(parent);
+            }
+        }
+    }
+    
+    // Re-reading most of the database costs more than reading all of it at once
+    if object_ids.len() * 2 > versions.len() {
+        return None;
+    }
+    
+    Some(RefreshPlan { object_ids: object_ids.into_iter().collect(), stale })
+}
+
+/// Replace the stale entries of the cached schema with the freshly read ones
+fn merge(previous: &SchemaData, fresh: SchemaData, stale: &HashSet<(String, String)>) -> SchemaData {
+    let keep = |schema: &str, name: &str| !stale.contains(&lowercase_name(schema, name));
+    fn combine<T: Clone>(old: &[T], fresh: Vec<T>, keep: impl Fn(&T) -> bool) -> Vec<T> {
+        old.iter().filter(|item| keep(item)).cloned().chain(fresh).collect()
+    }
+    
+    let mut schema = SchemaData {
+        tables: combine(&previous.tables, fresh.tables, |t| keep(&t.schema, &t.name)),
+        columns: combine(&previous.columns, fresh.columns, |c| keep(&c.table_schema, &c.table_name)),
+        primary_keys: combine(&previous.primary_keys, fresh.primary_keys, |pk| keep(&pk.table_schema, &pk.table_name)),
+        foreign_keys: combine(&previous.foreign_keys, fresh.foreign_keys, |fk| keep(&fk.parent_schema, &fk.parent_table)),
+        indexes: combine(&previous.indexes, fresh.indexes, |i| keep(&i.table_schema, &i.table_name)),
+        unique_constraints: Vec::new(),
+        check_constraints: combine(&previous.check_constraints, fresh.check_constraints, |c| keep(&c.table_schema, &c.table_name)),
+        default_constraints: combine(&previous.default_constraints, fresh.default_constraints, |d| keep(&d.table_schema, &d.table_name)),
+        routines: combine(&previous.routines, fresh.routines, |r| keep(&r.schema, &r.name)),
+    };
+    
+    // Keep the order a full read returns
+    schema.tables.sort_by(|a, b| (&a.schema, &a.name).cmp(&(&b.schema, &b.name)));
+    schema.columns.sort_by(|a, b| {
+        (&a.table_schema, &a.table_name, a.ordinal_position).cmp(&(&b.table_schema, &b.table_name, b.ordinal_position))
+    });
+    schema.routines.sort_by(|a, b| (&a.schema, &a.name).cmp(&(&b.schema, &b.name)));
+    schema
+}
+
+/// Run every catalog query except the descriptions
+async fn read_catalog<S: SchemaSource>(source: &mut S, timeout: Duration) -> Result<SchemaData, String> {
+    println!("Extracting tables...");
+    let tables = query(timeout, "tables", source.get_tables()).await?;
+    println!("Found {} tables", tables.len());
+    
+    println!("Extracting columns...");
+    let columns = query(timeout, "columns", source.get_columns()).await?;
+    println!("Found {} columns", columns.len());
+    
+    println!("Extracting primary keys...");
+    let primary_keys = query(timeout, "primary keys", source.get_primary_keys()).await?;
+    
+    println!("Extracting foreign keys...");
+    let foreign_keys = query(timeout, "foreign keys", source.get_foreign_keys()).await?;
+    
+    println!("Extracting indexes and constraints...");
+    let indexes = query(timeout, "indexes", source.get_indexes()).await?;
+    let check_constraints = query(timeout, "check constraints", source.get_check_constraints()).await?;
+    let default_constraints = query(timeout, "default constraints", source.get_default_constraints()).await?;
+    
+    println!("Extracting procedures, functions and triggers...");
+    let routines = query(timeout, "routines", source.get_routines()).await?;
+    println!("Found {} routines", routines.len());
+    
+    Ok(SchemaData {
+        tables,
+        columns,
+        primary_keys,
+        foreign_keys,
+        unique_constraints: Vec::new(),
+        indexes,
+        check_constraints,
+        default_constraints,
+        routines,
+    })
+}
+
+async fn extract_from<S: SchemaSource>(connection: &Connection, password: Option<&str>, previous: Option<&CachedSchema>) -> Result<Extraction, String> {
+    let mut source = connect_with_retry::<S>(connection, password)
+        .await
+        .map_err(|e| e.message)?;
+    let timeout = connection.query_timeout();
+    
+    let server_version = query(timeout, "server details", source.probe()).await?.server_version;
+    let versions = query(timeout, "object versions", source.get_object_versions()).await?;
+    
+    let plan = match (&versions, previous) {
+        (Some(versions), Some(previous)) => plan_refresh(previous, versions).map(|plan| (plan, previous)),
+        _ => None,
+    };
+    let incremental = plan.is_some();
+    let mut schema = match plan {
+        Some((plan, previous)) => {
+            println!("Refreshing {} changed objects", plan.object_ids.len());
+            let fresh = if plan.object_ids.is_empty() {
+                SchemaData::default()
+            } else {
+                query(timeout, "changed objects", source.set_scope(&plan.object_ids)).await?;
+                read_catalog(&mut source, timeout).await?
+            };
+            merge(&previous.schema, fresh, &plan.stale)
+        }
+        None => read_catalog(&mut source, timeout).await?,
+    };
+    
+    println!("Extracting extended properties...");
+    let properties = query(timeout, "extended properties", source.get_properties()).await?;
+    
+    // Descriptions are always read in full; cached ones may have been dropped since
+    let describe = |description: &mut Option<String>, extended: &mut BTreeMap<String, String>, owner: PropertyOwner| {
+        match properties.get(&owner) {
+            Some(props) => {
+                *description = props.description.clone();
+                *extended = props.extended.clone();
+            }
+            None if incremental => {
+                *description = None;
+                extended.clear();
+            }
+            None => {}
+        }
+    };
+    for table in &mut schema.tables {
+        describe(&mut table.description, &mut table.extended_properties, (table.schema.clone(), table.name.clone(), None));
+    }
+    for routine in &mut schema.routines {
+        describe(&mut routine.description, &mut routine.extended_properties, (routine.schema.clone(), routine.name.clone(), None));
+    }
+    
+    // Mark primary key and foreign key columns
+    let primary_key_columns: HashSet<(String, String, String)> = schema.primary_keys.iter()
+        .map(|pk| (pk.table_schema.to_lowercase(), pk.table_name.to_lowercase(), pk.column_name.to_lowercase()))
+        .collect();
+    let foreign_key_columns: HashSet<(String, String, String)> = schema.foreign_keys.iter()
+        .flat_map(|fk| fk.columns.iter().map(move |c| {
+            (fk.parent_schema.to_lowercase(), fk.parent_table.to_lowercase(), c.parent_column.to_lowercase())
+        }))
+        .collect();
+    for col in &mut schema.columns {
+        describe(
+            &mut col.description,
+            &mut col.extended_properties,
+            (col.table_schema.clone(), col.table_name.clone(), Some(col.name.clone())),
+        );
+        
+        let column = (col.table_schema.to_lowercase(), col.table_name.to_lowercase(), col.name.to_lowercase());
+        col.is_primary_key = primary_key_columns.contains(&column);
+        col.is_foreign_key = foreign_key_columns.contains(&column);
+    }
+    
+    schema.unique_constraints = unique_constraints_from_indexes(&schema.indexes);
+    Ok(Extraction {
+        schema,
+        server_version,
+        versions: versions.unwrap_or_default(),
+    })
+}
+
+/// Validate the connection's certificate options and return the CA or pinned certificate
+/// file to trust, if any. Shared by every provider that speaks TLS.
+pub(crate) fn trusted_certificate_path(connection: &Connection) -> Result<Option<&Path>, String> {
+    let certificate_path = match (&connection.ca_certificate_path, &connection.pinned_certificate_path) {
+        (Some(_), Some(_)) => {
+            return Err("A CA certificate and a pinned certificate cannot be used together".to_string());
+        }
+        (Some(path), None) | (None, Some(path)) => Some(Path::new(path)),
+        (None, None) => None,
+    };
+    
+    if connection.trust_server_certificate {
+        if connection.encryption == EncryptionMode::Strict {
+            return Err("Strict encryption requires the server certificate to be validated".to_string());
+        }
+        if certificate_path.is_some() {
+            return Err("Trusting the server certificate cannot be combined with a CA or pinned certificate".to_string());
+        }
+        return Ok(None);
+    }
+    
+    if let Some(path) = certificate_path {
+        if !path.is_file() {
+            return Err(format!("Certificate file not found: {}", path.display()));
+        }
+        let extension = path.extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        if !matches!(extension.as_deref(), Some("pem" | "crt" | "der")) {
+            return Err("Certificate files must be .pem, .crt or .der".to_string());
+        }
+    }
+    
+    Ok(certificate_path)
+}
+
+/// Split `host` or `host:port`; IPv6 literals need brackets (`[::1]:5432`) to carry a port
+pub(crate) fn parse_host(server: &str) -> Result<(String, Option<u16>), String> {
+    let server = server.trim();
+    
+    let (host, port) = if let Some(rest) = server.strip_prefix('[') {
+        match rest.split_once(']') {
+            Some((host, "")) => (host, None),
+            Some((host, port)) => (host, Some(port.trim_start_matches(':'))),
+            None => return Err(format!("Invalid server '{}'", server)),
+        }
+    } else {
+        match server.split_once(':') {
+            Some((host, port)) if !port.contains(':') => (host, Some(port)),
+            _ => (server, None),
+        }
+    };
+    
+    if host.is_empty() {
+        return Err("Server cannot be empty".to_string());
+    }
+    
+    let port = port
+        .map(|p| p.parse::<u16>().map_err(|_| format!("Invalid port '{}' in server '{}'", p, server)))
+        .transpose()?;
+    
+    Ok((host.to_string(), port))
+}
+
+/// Resolve the host before connecting so DNS failures can be told apart from refused connections
+pub(crate) async fn resolve_host(host: &str, port: u16) -> Result<(), ConnectError> {
+    match tokio::net::lookup_host((host, port)).await {
+        Ok(_) => Ok(()),
+        Err(e) => Err(ConnectError::new(ConnectErrorKind::Dns, format!("Failed to resolve server '{}': {}", host, e))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::schema::{ForeignKeyColumn, ObjectKind};
+    use std::sync::mpsc;
+    
+    fn version(object_id: i64, name: &str, stamp: &str) -> ObjectVersion {
+        ObjectVersion { object_id, schema: "dbo".to_string(), name: name.to_string(), stamp: stamp.to_string() }
+    }
+    
+    fn table(name: &str) -> Table {
+        Table {
+            schema: "dbo".to_string(),
+            name: name.to_string(),
+            description: None,
+            object_kind: ObjectKind::BaseTable,
+            definition: None,
+            synonym_target: None,
+            extended_properties: Default::default(),
+        }
+    }
+    
+    fn primary_key(table: &str, column: &str) -> PrimaryKey {
+        PrimaryKey { table_schema: "dbo".to_string(), table_name: table.to_string(), column_name: column.to_string() }
+    }
+    
+    fn foreign_key(parent: &str, referenced: &str) -> ForeignKey {
+        ForeignKey {
+            constraint_name: format!("FK_{}_{}", parent, referenced),
+            parent_schema: "dbo".to_string(),
+            parent_table: parent.to_string(),
+            referenced_schema: "dbo".to_string(),
+            referenced_table: referenced.to_string(),
+            columns: vec![ForeignKeyColumn { parent_column: format!("{}Id", referenced), referenced_column: "Id".to_string() }],
+            delete_referential_action: "NO_ACTION".to_string(),
+            update_referential_action: "NO_ACTION".to_string(),
+            is_disabled: false,
+            is_not_trusted: false,
+        }
+    }
+    
+    /// Seven tables, all at stamp `a`; orders reference customers
+    fn cached() -> CachedSchema {
+        let names = ["Customer", "Orders", "OrderLine", "Product", "Supplier", "Region", "Audit"];
+        CachedSchema {
+            schema: SchemaData {
+                tables: names.iter().map(|n| table(n)).collect(),
+                foreign_keys: vec![foreign_key("Orders", "Customer")],
+                ..Default::default()
+            },
+            versions: names.iter().enumerate().map(|(i, n)| version(i as i64 + 1, n, "a")).collect(),
+        }
+    }
+    
+    fn planned_ids(plan: &RefreshPlan) -> Vec<i64> {
+        let mut ids = plan.object_ids.clone();
+        ids.sort();
+        ids
+    }
+    
+    fn names(stale: &[(&str, &str)]) -> HashSet<(String, String)> {
+        stale.iter().map(|(schema, name)| (schema.to_string(), name.to_string())).collect()
+    }
+    
+    #[test]
+    fn plan_covers_changed_added_dropped_and_renamed_objects() {
+        let previous = cached();
+        let versions = vec![
+            version(1, "Customer", "b"),
+            version(2, "Orders", "a"),
+            version(3, "OrderLine", "a"),
+            version(4, "Item", "a"),
+            version(6, "Region", "a"),
+            version(7, "Audit", "a"),
+            version(8, "Invoice", "a"),
+        ];
+        
+        let plan = plan_refresh(&previous, &versions).unwrap();
+        // Supplier is dropped: nothing to read, but its cached entries go
+        assert_eq!(planned_ids(&plan), vec![1, 4, 8]);
+        assert_eq!(plan.stale, names(&[
+            ("dbo", "customer"),
+            ("dbo", "product"),
+            ("dbo", "item"),
+            ("dbo", "supplier"),
+            ("dbo", "invoice"),
+        ]));
+    }
+    
+    #[test]
+    fn plan_rereads_tables_referencing_one_that_is_gone() {
+        let previous = cached();
+        let mut versions = previous.versions.clone();
+        
+        // Dropped
+        versions.retain(|v| v.name != "Customer");
+        let plan = plan_refresh(&previous, &versions).unwrap();
+        assert_eq!(planned_ids(&plan), vec![2]);
+        assert_eq!(plan.stale, names(&[("dbo", "customer"), ("dbo", "orders")]));
+        
+        // Moved to another schema
+        let mut versions = previous.versions.clone();
+        versions[0].schema = "sales".to_string();
+        let plan = plan_refresh(&previous, &versions).unwrap();
+        assert_eq!(planned_ids(&plan), vec![1, 2]);
+        assert_eq!(plan.stale, names(&[("dbo", "customer"), ("sales", "customer"), ("dbo", "orders")]));
+        
+        // A new stamp alone leaves the foreign key valid
+        let mut versions = previous.versions.clone();
+        versions[0].stamp = "b".to_string();
+        let plan = plan_refresh(&previous, &versions).unwrap();
+        assert_eq!(planned_ids(&plan), vec![1]);
+    }
+    
+    #[test]
+    fn plan_falls_back_to_a_full_refresh() {
+        let previous = cached();
+        assert_eq!(plan_refresh(&previous, &previous.versions).map(|p| p.object_ids), Some(Vec::new()));
+        
+        // Four of seven changed
+        let mut versions = previous.versions.clone();
+        for v in versions.iter_mut().take(4) {
+            v.stamp = "b".to_string();
+        }
+        assert!(plan_refresh(&previous, &versions).is_none());
+        
+        // Three of seven is still incremental
+        versions[3].stamp = "a".to_string();
+        assert!(plan_refresh(&previous, &versions).is_some());
+        
+        // Nothing to compare against
+        let unversioned = CachedSchema { schema: previous.schema, versions: Vec::new() };
+        assert!(plan_refresh(&unversioned, &versions).is_none());
+    }
+    
+    #[test]
+    fn merge_replaces_only_stale_entries() {
+        let previous = SchemaData {
+            tables: vec![table("Customer"), table("Orders"), table("Product")],
+            primary_keys: vec![primary_key("Customer", "Id"), primary_key("Orders", "Id"), primary_key("Product", "Id")],
+            foreign_keys: vec![foreign_key("Orders", "Customer"), foreign_key("Product", "Supplier")],
+            ..Default::default()
+        };
+        // Customer gained a composite key, Product was dropped and Invoice added
+        let fresh = SchemaData {
+            tables: vec![table("Invoice"), table("Customer")],
+            primary_keys: vec![primary_key("Customer", "Id"), primary_key("Customer", "Region"), primary_key("Invoice", "Id")],
+            foreign_keys: vec![foreign_key("Invoice", "Customer")],
+            ..Default::default()
+        };
+        let stale = names(&[("dbo", "customer"), ("dbo", "product"), ("dbo", "invoice")]);
+        
+        let merged = merge(&previous, fresh, &stale);
+        let tables: Vec<&str> = merged.tables.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(tables, vec!["Customer", "Invoice", "Orders"]);
+        let keys: Vec<(&str, &str)> = merged.primary_keys.iter()
+            .map(|pk| (pk.table_name.as_str(), pk.column_name.as_str()))
+            .collect();
+        assert_eq!(keys, vec![("Orders", "Id"), ("Customer", "Id"), ("Customer", "Region"), ("Invoice", "Id")]);
+        let fks: Vec<&str> = merged.foreign_keys.iter().map(|fk| fk.constraint_name.as_str()).collect();
+        assert_eq!(fks, vec!["FK_Orders_Customer", "FK_Invoice_Customer"]);
+    }
+    
+    #[tokio::test]
+    async fn blocking_work_stops_once_nobody_waits() {
+        let (steps_tx, steps) = mpsc::channel();
+        let work = run_blocking(move |cancellation| {
+            let mut step = 0;
+            // One file or query per step
+            while !cancellation.is_cancelled() && step < 1000 {
+                step += 1;
+                steps_tx.send(step).ok();
+                std::thread::sleep(Duration::from_millis(5));
+            }
+            step
+        });
+        
+        // As when indexing is cancelled: the select drops the extraction
+        assert!(tokio::time::timeout(Duration::from_millis(50), work).await.is_err());
+        let stopped_after = steps.iter().last().unwrap();
+        assert!(stopped_after < 100, "ran {} steps", stopped_after);
+        
+        assert_eq!(run_blocking(|_| 42).await, Ok(42));
+    }
+}
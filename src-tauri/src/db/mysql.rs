@@ -0,0 +1,652 @@
+use std::collections::{BTreeMap, HashMap};
+use mysql_async::prelude::Queryable;
+use mysql_async::{Conn, DriverError, IoError, OptsBuilder, Row, SslOpts};
+use crate::config::{Connection, EncryptionMode};
+use crate::commands::schema::{
+    Table, ObjectKind, Column, ForeignKey, ForeignKeyColumn, PrimaryKey, Index, IndexColumn,
+    CheckConstraint, Routine, RoutineKind, RoutineParameter, ParameterDirection,
+};
+use super::{ConnectError, ConnectErrorKind, ServerProbe, SchemaSource, parse_host, resolve_host, trusted_certificate_path};
+
+/// Default TCP port of a MySQL or MariaDB server
+const DEFAULT_PORT: u16 = 3306;
+
+/// Read a nullable text value. Catalog values arrive as bytes over the text protocol,
+/// and reading NULL as a plain `String` would panic.
+fn text(row: &Row, index: usize) -> Option<String> {
+    row.get_opt::<Option<String>, usize>(index)
+        .and_then(|v| v.ok())
+        .flatten()
+}
+
+/// Read a nullable integer value
+fn number(row: &Row, index: usize) -> Option<i64> {
+    row.get_opt::<Option<i64>, usize>(index)
+        .and_then(|v| v.ok())
+        .flatten()
+}
+
+/// Classify an error returned while connecting to MySQL or MariaDB
+fn classify_mysql_error(error: &mysql_async::Error) -> ConnectErrorKind {
+    match error {
+        // 1045: access denied, 1698: access denied without a password, 1862: password expired
+        mysql_async::Error::Server(e) if matches!(e.code, 1045 | 1698 | 1862) => ConnectErrorKind::LoginFailed,
+        // 1049: unknown database, 1044: access denied to the database
+        mysql_async::Error::Server(e) if matches!(e.code, 1049 | 1044) => ConnectErrorKind::DatabaseNotFound,
+        mysql_async::Error::Driver(DriverError::NoClientSslFlagFromServer) => ConnectErrorKind::Tls,
+        mysql_async::Error::Io(IoError::Tls(_)) => ConnectErrorKind::Tls,
+        mysql_async::Error::Io(IoError::Io(e)) if e.kind() == std::io::ErrorKind::ConnectionRefused => ConnectErrorKind::TcpRefused,
+        mysql_async::Error::Io(_) => ConnectErrorKind::Network,
+        _ => ConnectErrorKind::Other,
+    }
+}
+
+/// Build the TLS options for the connection's certificate trust settings.
+/// A CA or pinned certificate becomes the only trust anchor.
+fn ssl_opts(connection: &Connection) -> Result<SslOpts, String> {
+    let certificate_path = trusted_certificate_path(connection)?;
+    
+    let mut opts = SslOpts::default();
+    if connection.trust_server_certificate {
+        opts = opts
+            .with_danger_accept_invalid_certs(true)
+            .with_danger_skip_domain_validation(true);
+    } else if let Some(path) = certificate_path {
+        opts = opts
+            .with_root_certs(vec![path.to_path_buf().into()])
+            .with_disable_built_in_roots(true);
+    }
+    
+    Ok(opts)
+}
+
+/// Connect to MySQL or MariaDB, reporting failures with a `ConnectErrorKind`
+pub async fn connect(connection: &Connection, password: Option<&str>) -> Result<Conn, ConnectError> {
+    if connection.ssh_tunnel.is_some() {
+        return Err(ConnectError::new(ConnectErrorKind::Configuration, "SSH tunnels are only supported for SQL Server connections"));
+    }
+    
+    let (host, embedded_port) = parse_host(&connection.server)
+        .map_err(|e| ConnectError::new(ConnectErrorKind::Configuration, e))?;
+    let port = connection.port.or(embedded_port).unwrap_or(DEFAULT_PORT);
+    
+    let username = match (connection.auth_type.as_str(), &connection.username) {
+        ("sql", Some(username)) => username,
+        ("sql", None) => {
+            return Err(ConnectError::new(ConnectErrorKind::Configuration, "Username is required for password authentication"));
+        }
+        _ => {
+            return Err(ConnectError::new(ConnectErrorKind::Configuration, "MySQL connections only support password authentication"));
+        }
+    };
+    
+    let ssl = match connection.encryption {
+        EncryptionMode::Off => None,
+        _ => Some(ssl_opts(connection).map_err(|e| ConnectError::new(ConnectErrorKind::Configuration, e))?),
+    };
+    
+    let opts = OptsBuilder::default()
+        .ip_or_hostname(host.as_str())
+        .tcp_port(port)
+        .user(Some(username.as_str()))
+        .pass(password)
+        .db_name(Some(connection.database.as_str()))
+        .prefer_socket(false);
+    
+    println!("Connecting to {}:{}", host, port);
+    
+    resolve_host(&host, port).await?;
+    
+    let result = match Conn::new(opts.clone().ssl_opts(ssl)).await {
+        // `On` only encrypts when the server supports it
+        Err(mysql_async::Error::Driver(DriverError::NoClientSslFlagFromServer)) if connection.encryption == EncryptionMode::On => {
+            println!("Server does not support TLS, connecting unencrypted");
+            Conn::new(opts).await
+        }
+        result => result,
+    };
+    
+    let conn = result
+        .map_err(|e| ConnectError::new(classify_mysql_error(&e), format!("Failed to connect to MySQL: {}", e)))?;
+    
+    println!("Connected successfully!");
+    Ok(conn)
+}
+
+/// Run a query, mapping failures to an error that names what was being read
+async fn query_rows(conn: &mut Conn, query: &str, what: &str) -> Result<Vec<Row>, String> {
+    conn.query(query)
+        .await
+        .map_err(|e| format!("Failed to query {}: {}", what, e))
+}
+
+/// Run a trivial query that reports the server version, current database and login
+pub async fn probe_server(conn: &mut Conn) -> Result<ServerProbe, String> {
+    let rows = query_rows(conn, "SELECT VERSION(), DATABASE(), CURRENT_USER()", "server info").await?;
+    let row = rows.first().ok_or("Server info query returned no rows")?;
+    
+    let version = text(row, 0).unwrap_or_default();
+    
+    Ok(ServerProbe {
+        // MariaDB names itself in the version string (`10.11.6-MariaDB-log`)
+        server_version: if version.contains("MariaDB") { version } else { format!("MySQL {}", version) },
+        database_name: text(row, 1).unwrap_or_default(),
+        login_name: text(row, 2).unwrap_or_default(),
+    })
+}
+
+/// Extract tables and views of the current database. Storage engine, character set and
+/// collation are reported as extended properties.
+pub async fn get_tables(conn: &mut Conn) -> Result<Vec<Table>, String> {
+    let query = r#"
+        SELECT
+            t.TABLE_SCHEMA,
+            t.TABLE_NAME,
+            t.TABLE_TYPE,
+            v.VIEW_DEFINITION,
+            t.TABLE_COMMENT,
+            t.ENGINE,
+            coll.CHARACTER_SET_NAME,
+            t.TABLE_COLLATION
+        FROM information_schema.TABLES t
+        LEFT JOIN information_schema.VIEWS v
+            ON v.TABLE_SCHEMA = t.TABLE_SCHEMA AND v.TABLE_NAME = t.TABLE_NAME
+        LEFT JOIN information_schema.COLLATIONS coll ON coll.COLLATION_NAME = t.TABLE_COLLATION
+        WHERE t.TABLE_SCHEMA = DATABASE()
+            AND t.TABLE_TYPE IN ('BASE TABLE', 'VIEW')
+        ORDER BY t.TABLE_SCHEMA, t.TABLE_NAME
+    "#;
+    
+    let rows = query_rows(conn, query, "tables").await?;
+    
+    let mut tables = Vec::new();
+    for row in rows {
+        let is_view = text(&row, 2).as_deref() == Some("VIEW");
+        
+        let mut extended_properties = BTreeMap::new();
+        for (name, index) in [("Engine", 5), ("Charset", 6), ("Collation", 7)] {
+            if let Some(value) = text(&row, index) {
+                extended_properties.insert(name.to_string(), value);
+            }
+        }
+        
+        tables.push(Table {
+            schema: text(&row, 0).unwrap_or_default(),
+            name: text(&row, 1).unwrap_or_default(),
+            // MySQL fills the comment of every view with `VIEW`
+            description: text(&row, 4).filter(|c| !c.is_empty() && !is_view),
+            object_kind: if is_view { ObjectKind::View } else { ObjectKind::BaseTable },
+            definition: text(&row, 3),
+            synonym_target: None,
+            extended_properties,
+        });
+    }
+    
+    Ok(tables)
+}
+
+/// Extract all columns of the current database
+pub async fn get_columns(conn: &mut Conn) -> Result<Vec<Column>, String> {
+    let query = r#"
+        SELECT
+            TABLE_SCHEMA,
+            TABLE_NAME,
+            COLUMN_NAME,
+            DATA_TYPE,
+            COLUMN_TYPE,
+            IS_NULLABLE,
+            ORDINAL_POSITION,
+            CHARACTER_MAXIMUM_LENGTH,
+            NUMERIC_PRECISION,
+            NUMERIC_SCALE,
+            DATETIME_PRECISION,
+            CHARACTER_SET_NAME,
+            COLLATION_NAME,
+            COLUMN_DEFAULT,
+            EXTRA,
+            GENERATION_EXPRESSION,
+            COLUMN_COMMENT
+        FROM information_schema.COLUMNS
+        WHERE TABLE_SCHEMA = DATABASE()
+        ORDER BY TABLE_SCHEMA, TABLE_NAME, ORDINAL_POSITION
+    "#;
+    
+    let rows = query_rows(conn, query, "columns").await?;
+    
+    let mut columns = Vec::new();
+    for row in rows {
+        let data_type = text(&row, 3).unwrap_or_default().to_lowercase();
+        let extra = text(&row, 14).unwrap_or_default().to_lowercase();
+        
+        // Only declared facets are reported: integer types carry a display precision
+        // and text types an implied maximum length
+        let (max_length, precision, scale) = match data_type.as_str() {
+            "char" | "varchar" | "binary" | "varbinary" => {
+                (number(&row, 7).and_then(|l| i32::try_from(l).ok()), None, None)
+            }
+            "decimal" | "numeric" => (None, number(&row, 8), number(&row, 9)),
+            "datetime" | "timestamp" | "time" => (None, None, number(&row, 10)),
+            _ => (None, None, None),
+        };
+        
+        // auto_increment has no per-column seed or step; report the defaults
+        let is_auto_increment = extra.contains("auto_increment");
+        
+        let mut extended_properties = BTreeMap::new();
+        if let Some(charset) = text(&row, 11) {
+            extended_properties.insert("Charset".to_string(), charset);
+        }
+        
+        columns.push(Column {
+            table_schema: text(&row, 0).unwrap_or_default(),
+            table_name: text(&row, 1).unwrap_or_default(),
+            name: text(&row, 2).unwrap_or_default(),
+            full_type: text(&row, 4).unwrap_or_else(|| data_type.clone()),
+            data_type,
+            is_nullable: text(&row, 5).as_deref() != Some("NO"),
+            is_primary_key: false, // Will be updated later
+            is_foreign_key: false, // Will be updated later
+            ordinal_position: number(&row, 6).and_then(|p| i32::try_from(p).ok()).unwrap_or(0),
+            description: text(&row, 16).filter(|c| !c.is_empty()),
+            extended_properties,
+            max_length,
+            precision: precision.and_then(|p| u8::try_from(p).ok()),
+            scale: scale.and_then(|s| u8::try_from(s).ok()),
+            collation: text(&row, 12),
+            default_value: text(&row, 13),
+            identity_seed: is_auto_increment.then_some(1),
+            identity_increment: is_auto_increment.then_some(1),
+            computed_definition: text(&row, 15).filter(|g| !g.is_empty()),
+            is_sparse: false,
+            is_rowguid: false,
+            is_filestream: false,
+        });
+    }
+    
+    Ok(columns)
+}
+
+/// Extract primary keys
+pub async fn get_primary_keys(conn: &mut Conn) -> Result<Vec<PrimaryKey>, String> {
+    let query = r#"
+        SELECT TABLE_SCHEMA, TABLE_NAME, COLUMN_NAME
+        FROM information_schema.KEY_COLUMN_USAGE
+        WHERE TABLE_SCHEMA = DATABASE() AND CONSTRAINT_NAME = 'PRIMARY'
+        ORDER BY TABLE_SCHEMA, TABLE_NAME, ORDINAL_POSITION
+    "#;
+    
+    let rows = query_rows(conn, query, "primary keys").await?;
+    
+    let mut pks = Vec::new();
+    for row in rows {
+        pks.push(PrimaryKey {
+            table_schema: text(&row, 0).unwrap_or_default(),
+            table_name: text(&row, 1).unwrap_or_default(),
+            column_name: text(&row, 2).unwrap_or_default(),
+        });
+    }
+    
+    Ok(pks)
+}
+
+/// Map a MySQL referential rule (`SET NULL`) to the names SQL Server uses (`SET_NULL`)
+fn referential_action(rule: Option<String>) -> String {
+    rule.map(|r| r.replace(' ', "_"))
+        .unwrap_or_else(|| "NO_ACTION".to_string())
+}
+
+/// Extract foreign keys, with the column pairs of each constraint in key order
+pub async fn get_foreign_keys(conn: &mut Conn) -> Result<Vec<ForeignKey>, String> {
+    let query = r#"
+        SELECT
+            k.CONSTRAINT_NAME,
+            k.TABLE_SCHEMA,
+            k.TABLE_NAME,
+            k.REFERENCED_TABLE_SCHEMA,
+            k.REFERENCED_TABLE_NAME,
+            r.DELETE_RULE,
+            r.UPDATE_RULE,
+            k.COLUMN_NAME,
+            k.REFERENCED_COLUMN_NAME
+        FROM information_schema.KEY_COLUMN_USAGE k
+        INNER JOIN information_schema.REFERENTIAL_CONSTRAINTS r
+            ON r.CONSTRAINT_SCHEMA = k.CONSTRAINT_SCHEMA
+            AND r.TABLE_NAME = k.TABLE_NAME
+            AND r.CONSTRAINT_NAME = k.CONSTRAINT_NAME
+        WHERE k.TABLE_SCHEMA = DATABASE() AND k.REFERENCED_TABLE_NAME IS NOT NULL
+        ORDER BY k.TABLE_SCHEMA, k.TABLE_NAME, k.CONSTRAINT_NAME, k.ORDINAL_POSITION
+    "#;
+    
+    let rows = query_rows(conn, query, "foreign keys").await?;
+    
+    let mut fks: Vec<ForeignKey> = Vec::new();
+    for row in rows {
+        let constraint_name = text(&row, 0).unwrap_or_default();
+        let parent_schema = text(&row, 1).unwrap_or_default();
+        let parent_table = text(&row, 2).unwrap_or_default();
+        
+        let column = ForeignKeyColumn {
+            parent_column: text(&row, 7).unwrap_or_default(),
+            referenced_column: text(&row, 8).unwrap_or_default(),
+        };
+        
+        // Rows are ordered by constraint, so further columns of a composite key follow directly
+        if let Some(fk) = fks.last_mut() {
+            if fk.constraint_name == constraint_name && fk.parent_schema == parent_schema && fk.parent_table == parent_table {
+                fk.columns.push(column);
+                continue;
+            }
+        }
+        
+        fks.push(ForeignKey {
+            constraint_name,
+            parent_schema,
+            parent_table,
+            referenced_schema: text(&row, 3).unwrap_or_default(),
+            referenced_table: text(&row, 4).unwrap_or_default(),
+            columns: vec![column],
+            delete_referential_action: referential_action(text(&row, 5)),
+            update_referential_action: referential_action(text(&row, 6)),
+            is_disabled: false,
+            is_not_trusted: false,
+        });
+    }
+    
+    Ok(fks)
+}
+
+/// Extract indexes with their key columns. Every unique index doubles as a unique constraint.
+pub async fn get_indexes(conn: &mut Conn) -> Result<Vec<Index>, String> {
+    let query = r#"
+        SELECT
+            TABLE_SCHEMA,
+            TABLE_NAME,
+            INDEX_NAME,
+            INDEX_TYPE,
+            NON_UNIQUE,
+            COLUMN_NAME,
+            SUB_PART,
+            COLLATION
+        FROM information_schema.STATISTICS
+        WHERE TABLE_SCHEMA = DATABASE()
+        ORDER BY TABLE_SCHEMA, TABLE_NAME, INDEX_NAME, SEQ_IN_INDEX
+    "#;
+    
+    let rows = query_rows(conn, query, "indexes").await?;
+    
+    let mut indexes: Vec<Index> = Vec::new();
+    for row in rows {
+        let table_schema = text(&row, 0).unwrap_or_default();
+        let table_name = text(&row, 1).unwrap_or_default();
+        let index_name = text(&row, 2).unwrap_or_default();
+        
+        // Rows are ordered by index, so a new name starts a new index
+        let is_same_index = indexes.last().is_some_and(|index| {
+            index.name == index_name && index.table_schema == table_schema && index.table_name == table_name
+        });
+        if !is_same_index {
+            let is_primary_key = index_name == "PRIMARY";
+            let is_unique = number(&row, 4) == Some(0);
+            
+            indexes.push(Index {
+                table_schema,
+                table_name,
+                index_type: text(&row, 3).unwrap_or_default(),
+                is_unique,
+                is_primary_key,
+                is_unique_constraint: is_unique && !is_primary_key,
+                name: index_name,
+                key_columns: Vec::new(),
+                included_columns: Vec::new(),
+                filter_definition: None,
+            });
+        }
+        
+        // Functional key parts (MySQL 8.0.13+) have no column name
+        let Some(column_name) = text(&row, 5) else {
+            continue;
+        };
+        
+        if let Some(index) = indexes.last_mut() {
+            index.key_columns.push(IndexColumn {
+                // Prefix indexes cover only the first characters of the column
+                name: match number(&row, 6) {
+                    Some(length) => format!("{}({})", column_name, length),
+                    None => column_name,
+                },
+                is_descending: text(&row, 7).as_deref() == Some("D"),
+            });
+        }
+    }
+    
+    Ok(indexes)
+}
+
+/// Extract check constraints. Servers without enforced checks (MySQL before 8.0.16)
+/// have no `CHECK_CONSTRAINTS` view, so none are reported for them.
+pub async fn get_check_constraints(conn: &mut Conn) -> Result<Vec<CheckConstraint>, String> {
+    let version = query_rows(conn, "SELECT VERSION()", "server version").await?
+        .first()
+        .and_then(|row| text(row, 0))
+        .unwrap_or_default();
+    // MariaDB names check constraints per table, so two tables may each have their own
+    // `CONSTRAINT_1`; MySQL names them per schema and has no TABLE_NAME to match on
+    let same_table = if version.contains("MariaDB") { "AND cc.TABLE_NAME = tc.TABLE_NAME" } else { "" };
+    let query = format!(r#"
+        SELECT
+            tc.TABLE_SCHEMA,
+            tc.TABLE_NAME,
+            tc.CONSTRAINT_NAME,
+            cc.CHECK_CLAUSE
+        FROM information_schema.TABLE_CONSTRAINTS tc
+        INNER JOIN information_schema.CHECK_CONSTRAINTS cc
+            ON cc.CONSTRAINT_SCHEMA = tc.CONSTRAINT_SCHEMA AND cc.CONSTRAINT_NAME = tc.CONSTRAINT_NAME
+            {}
+        WHERE tc.TABLE_SCHEMA = DATABASE() AND tc.CONSTRAINT_TYPE = 'CHECK'
+        ORDER BY tc.TABLE_SCHEMA, tc.TABLE_NAME, tc.CONSTRAINT_NAME
+    "#, same_table);
+    
+    let rows = match query_rows(conn, &query, "check constraints").await {
+        Ok(rows) => rows,
+        Err(e) => {
+            println!("Skipping check constraints: {}", e);
+            return Ok(Vec::new());
+        }
+    };
+    
+    let mut constraints = Vec::new();
+    for row in rows {
+        constraints.push(CheckConstraint {
+            table_schema: text(&row, 0).unwrap_or_default(),
+            table_name: text(&row, 1).unwrap_or_default(),
+            constraint_name: text(&row, 2).unwrap_or_default(),
+            column_name: None,
+            definition: text(&row, 3).unwrap_or_default(),
+            is_disabled: false,
+            is_not_trusted: false,
+        });
+    }
+    
+    Ok(constraints)
+}
+
+/// Extract procedures, functions and triggers of the current database
+pub async fn get_routines(conn: &mut Conn) -> Result<Vec<Routine>, String> {
+    let query = r#"
+        SELECT
+            ROUTINE_SCHEMA,
+            ROUTINE_NAME,
+            ROUTINE_TYPE,
+            ROUTINE_DEFINITION,
+            ROUTINE_COMMENT
+        FROM information_schema.ROUTINES
+        WHERE ROUTINE_SCHEMA = DATABASE()
+        ORDER BY ROUTINE_SCHEMA, ROUTINE_NAME
+    "#;
+    
+    let rows = query_rows(conn, query, "routines").await?;
+    
+    let mut routines = Vec::new();
+    // (schema, name) -> position in `routines`, to attach parameters
+    let mut positions: HashMap<(String, String), usize> = HashMap::new();
+    for row in rows {
+        let schema = text(&row, 0).unwrap_or_default();
+        let name = text(&row, 1).unwrap_or_default();
+        
+        positions.insert((schema.clone(), name.clone()), routines.len());
+        routines.push(Routine {
+            schema,
+            name,
+            // MySQL functions always return a single value
+            routine_kind: match text(&row, 2).as_deref() {
+                Some("PROCEDURE") => RoutineKind::StoredProcedure,
+                _ => RoutineKind::ScalarFunction,
+            },
+            description: text(&row, 4).filter(|c| !c.is_empty()),
+            extended_properties: Default::default(),
+            parent_schema: None,
+            parent_table: None,
+            definition: text(&row, 3),
+            parameters: Vec::new(),
+        });
+    }
+    
+    let query = r#"
+        SELECT
+            SPECIFIC_SCHEMA,
+            SPECIFIC_NAME,
+            ORDINAL_POSITION,
+            PARAMETER_MODE,
+            PARAMETER_NAME,
+            DTD_IDENTIFIER
+        FROM information_schema.PARAMETERS
+        WHERE SPECIFIC_SCHEMA = DATABASE()
+        ORDER BY SPECIFIC_SCHEMA, SPECIFIC_NAME, ORDINAL_POSITION
+    "#;
+    
+    let rows = query_rows(conn, query, "routine parameters").await?;
+    
+    for row in rows {
+        let schema = text(&row, 0).unwrap_or_default();
+        let name = text(&row, 1).unwrap_or_default();
+        let Some(&i) = positions.get(&(schema, name)) else {
+            continue;
+        };
+        
+        // A function's return value is listed at position 0 without a mode
+        let ordinal_position = number(&row, 2).and_then(|p| i32::try_from(p).ok()).unwrap_or(0);
+        let direction = match text(&row, 3).as_deref() {
+            Some("OUT") => ParameterDirection::Out,
+            Some("INOUT") => ParameterDirection::InOut,
+            None if ordinal_position == 0 => ParameterDirection::Return,
+            _ => ParameterDirection::In,
+        };
+        
+        routines[i].parameters.push(RoutineParameter {
+            name: text(&row, 4).unwrap_or_default(),
+            data_type: text(&row, 5).unwrap_or_default(),
+            direction,
+            default_value: None,
+            ordinal_position,
+        });
+    }
+    
+    routines.extend(get_triggers(conn).await?);
+    
+    Ok(routines)
+}
+
+/// Extract triggers of the current database
+async fn get_triggers(conn: &mut Conn) -> Result<Vec<Routine>, String> {
+    let query = r#"
+        SELECT
+            TRIGGER_SCHEMA,
+            TRIGGER_NAME,
+            EVENT_OBJECT_SCHEMA,
+            EVENT_OBJECT_TABLE,
+            ACTION_TIMING,
+            EVENT_MANIPULATION,
+            ACTION_STATEMENT
+        FROM information_schema.TRIGGERS
+        WHERE TRIGGER_SCHEMA = DATABASE()
+        ORDER BY TRIGGER_SCHEMA, TRIGGER_NAME
+    "#;
+    
+    let rows = query_rows(conn, query, "triggers").await?;
+    
+    let mut triggers = Vec::new();
+    for row in rows {
+        let name = text(&row, 1).unwrap_or_default();
+        let table_name = text(&row, 3).unwrap_or_default();
+        
+        // The catalog only keeps the trigger body; rebuild the statement around it
+        let definition = format!(
+            "CREATE TRIGGER {} {} {} ON {} FOR EACH ROW {}",
+            name,
+            text(&row, 4).unwrap_or_default(),
+            text(&row, 5).unwrap_or_default(),
+            table_name,
+            text(&row, 6).unwrap_or_default(),
+        );
+        
+        triggers.push(Routine {
+            schema: text(&row, 0).unwrap_or_default(),
+            name,
+            routine_kind: RoutineKind::Trigger,
+            description: None,
+            extended_properties: Default::default(),
+            parent_schema: text(&row, 2),
+            parent_table: Some(table_name),
+            definition: Some(definition),
+            parameters: Vec::new(),
+        });
+    }
+    
+    Ok(triggers)
+}
+
+/// A MySQL or MariaDB session the schema is read from. Only the connection's database is
+/// indexed; it plays the part of the schema. Comments are read together with the objects
+/// they describe, so `get_properties` keeps its default.
+pub struct MySqlSource {
+    conn: Conn,
+}
+
+impl SchemaSource for MySqlSource {
+    async fn connect(connection: &Connection, password: Option<&str>) -> Result<Self, ConnectError> {
+        let conn = connect(connection, password).await?;
+        Ok(Self { conn })
+    }
+    
+    async fn probe(&mut self) -> Result<ServerProbe, String> {
+        probe_server(&mut self.conn).await
+    }
+    
+    async fn get_tables(&mut self) -> Result<Vec<Table>, String> {
+        get_tables(&mut self.conn).await
+    }
+    
+    async fn get_columns(&mut self) -> Result<Vec<Column>, String> {
+        get_columns(&mut self.conn).await
+    }
+    
+    async fn get_primary_keys(&mut self) -> Result<Vec<PrimaryKey>, String> {
+        get_primary_keys(&mut self.conn).await
+    }
+    
+    async fn get_foreign_keys(&mut self) -> Result<Vec<ForeignKey>, String> {
+        get_foreign_keys(&mut self.conn).await
+    }
+    
+    async fn get_indexes(&mut self) -> Result<Vec<Index>, String> {
+        get_indexes(&mut self.conn).await
+    }
+    
+    async fn get_check_constraints(&mut self) -> Result<Vec<CheckConstraint>, String> {
+        get_check_constraints(&mut self.conn).await
+    }
+    
+    async fn get_routines(&mut self) -> Result<Vec<Routine>, String> {
+        get_routines(&mut self.conn).await
+    }
+}
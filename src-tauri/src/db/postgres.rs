@@ -0,0 +1,831 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{verify_tls12_signature, verify_tls13_signature, CryptoProvider};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme};
+use tokio_postgres::config::SslMode;
+use tokio_postgres::error::SqlState;
+use tokio_postgres::Client;
+use tokio_postgres_rustls::MakeRustlsConnect;
+use crate::config::{Connection, EncryptionMode};
+use crate::commands::schema::{
+    Table, ObjectKind, Column, ForeignKey, ForeignKeyColumn, PrimaryKey, Index, IndexColumn,
+    CheckConstraint, Routine, RoutineKind, RoutineParameter, ParameterDirection,
+};
+use super::{ConnectError, ConnectErrorKind, ServerProbe, SchemaSource, parse_host, resolve_host, trusted_certificate_path};
+
+/// Default TCP port of a PostgreSQL server
+const DEFAULT_PORT: u16 = 5432;
+
+/// Classify an error returned while connecting to PostgreSQL
+fn classify_postgres_error(error: &tokio_postgres::Error) -> ConnectErrorKind {
+    match error.code() {
+        Some(code) if *code == SqlState::INVALID_PASSWORD || *code == SqlState::INVALID_AUTHORIZATION_SPECIFICATION => {
+            return ConnectErrorKind::LoginFailed;
+        }
+        Some(code) if *code == SqlState::INVALID_CATALOG_NAME => return ConnectErrorKind::DatabaseNotFound,
+        Some(_) => return ConnectErrorKind::Other,
+        None => {}
+    }
+    
+    // Transport failures are wrapped; look through the source chain
+    let mut source = std::error::Error::source(error);
+    while let Some(e) = source {
+        if e.is::<rustls::Error>() {
+            return ConnectErrorKind::Tls;
+        }
+        if let Some(io) = e.downcast_ref::<std::io::Error>() {
+            if io.get_ref().is_some_and(|inner| inner.is::<rustls::Error>()) {
+                return ConnectErrorKind::Tls;
+            }
+            return match io.kind() {
+                std::io::ErrorKind::ConnectionRefused => ConnectErrorKind::TcpRefused,
+                _ => ConnectErrorKind::Network,
+            };
+        }
+        source = e.source();
+    }
+    
+    ConnectErrorKind::Other
+}
+
+/// Read the certificates in a pem, crt or der file
+fn load_certificates(path: &std::path::Path) -> Result<Vec<CertificateDer<'static>>, String> {
+    let bytes = std::fs::read(path)
+        .map_err(|e| format!("Failed to read certificate file {}: {}", path.display(), e))?;
+    
+    if !bytes.starts_with(b"-----BEGIN") {
+        return Ok(vec![CertificateDer::from(bytes)]);
+    }
+    
+    rustls_pemfile::certs(&mut bytes.as_slice())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to parse certificate file {}: {}", path.display(), e))
+}
+
+/// Verifier for certificates that are not validated against a CA: either any certificate is
+/// accepted (`TrustServerCertificate`), or only the exact pinned one. Handshake signatures are
+/// still checked, so the server must hold the certificate's private key.
+#[derive(Debug)]
+struct UncheckedCertificate {
+    pinned: Option<CertificateDer<'static>>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for UncheckedCertificate {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        match &self.pinned {
+            Some(pinned) if pinned.as_ref() != end_entity.as_ref() => Err(rustls::Error::InvalidCertificate(
+                rustls::CertificateError::ApplicationVerificationFailure,
+            )),
+            _ => Ok(ServerCertVerified::assertion()),
+        }
+    }
+    
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+    
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.provider.signature_verification_algorithms)
+    }
+    
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+/// Build the TLS connector for the connection's certificate trust settings
+fn tls_connector(connection: &Connection) -> Result<MakeRustlsConnect, String> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("Failed to set up TLS: {}", e))?;
+    
+    let certificate_path = trusted_certificate_path(connection)?;
+    
+    let config = if connection.trust_server_certificate {
+        builder.dangerous()
+            .with_custom_certificate_verifier(Arc::new(UncheckedCertificate { pinned: None, provider }))
+            .with_no_client_auth()
+    } else if let (Some(path), Some(_)) = (certificate_path, &connection.pinned_certificate_path) {
+        let pinned = load_certificates(path)?
+            .into_iter()
+            .next()
+            .ok_or_else(|| format!("No certificate found in {}", path.display()))?;
+        builder.dangerous()
+            .with_custom_certificate_verifier(Arc::new(UncheckedCertificate { pinned: Some(pinned), provider }))
+            .with_no_client_auth()
+    } else {
+        let mut roots = RootCertStore::empty();
+        match certificate_path {
+            // A CA file replaces the OS trust store
+            Some(path) => {
+                for cert in load_certificates(path)? {
+                    roots.add(cert).map_err(|e| format!("Invalid CA certificate: {}", e))?;
+                }
+            }
+            None => {
+                roots.add_parsable_certificates(rustls_native_certs::load_native_certs().certs);
+            }
+        }
+        builder.with_root_certificates(roots).with_no_client_auth()
+    };
+    
+    Ok(MakeRustlsConnect::new(config))
+}
+
+/// Connect to PostgreSQL, reporting failures with a `ConnectErrorKind`
+pub async fn connect(connection: &Connection, password: Option<&str>) -> Result<Client, ConnectError> {
+    if connection.ssh_tunnel.is_some() {
+        return Err(ConnectError::new(ConnectErrorKind::Configuration, "SSH tunnels are only supported for SQL Server connections"));
+    }
+    
+    let (host, embedded_port) = parse_host(&connection.server)
+        .map_err(|e| ConnectError::new(ConnectErrorKind::Configuration, e))?;
+    let port = connection.port.or(embedded_port).unwrap_or(DEFAULT_PORT);
+    
+    let username = match (connection.auth_type.as_str(), &connection.username) {
+        ("sql", Some(username)) => username,
+        ("sql", None) => {
+            return Err(ConnectError::new(ConnectErrorKind::Configuration, "Username is required for password authentication"));
+        }
+        _ => {
+            return Err(ConnectError::new(ConnectErrorKind::Configuration, "PostgreSQL connections only support password authentication"));
+        }
+    };
+    
+    let mut config = tokio_postgres::Config::new();
+    config.host(&host)
+        .port(port)
+        .dbname(&connection.database)
+        .user(username)
+        .application_name("Glance")
+        .ssl_mode(match connection.encryption {
+            EncryptionMode::Off => SslMode::Disable,
+            EncryptionMode::On => SslMode::Prefer,
+            EncryptionMode::Required | EncryptionMode::Strict => SslMode::Require,
+        });
+    if let Some(pwd) = password {
+        config.password(pwd);
+    }
+    
+    let tls = tls_connector(connection)
+        .map_err(|e| ConnectError::new(ConnectErrorKind::Configuration, e))?;
+    
+    println!("Connecting to {}:{}", host, port);
+    
+    resolve_host(&host, port).await?;
+    
+    let (client, session) = config.connect(tls)
+        .await
+        .map_err(|e| ConnectError::new(classify_postgres_error(&e), format!("Failed to connect to PostgreSQL: {}", e)))?;
+    
+    // The session drives the socket; it ends when the client is dropped
+    tokio::spawn(async move {
+        if let Err(e) = session.await {
+            eprintln!("PostgreSQL connection error: {}", e);
+        }
+    });
+    
+    println!("Connected successfully!");
+    Ok(client)
+}
+
+/// Run a trivial query that reports the server version, current database and login
+pub async fn probe_server(client: &Client) -> Result<ServerProbe, String> {
+    let row = client.query_one("SELECT version(), current_database()::text, current_user::text", &[])
+        .await
+        .map_err(|e| format!("Failed to query server info: {}", e))?;
+    
+    let server_version: &str = row.try_get(0).unwrap_or("");
+    let database_name: &str = row.try_get(1).unwrap_or("");
+    let login_name: &str = row.try_get(2).unwrap_or("");
+    
+    Ok(ServerProbe {
+        // version() goes on to name the compiler; the first part names the product and build
+        server_version: server_version.split(',').next().unwrap_or("").trim().to_string(),
+        database_name: database_name.to_string(),
+        login_name: login_name.to_string(),
+    })
+}
+
+/// Extract tables, partitioned tables, views, materialized views and foreign tables
+pub async fn get_tables(client: &Client) -> Result<Vec<Table>, String> {
+    let query = r#"
+        SELECT
+            n.nspname AS table_schema,
+            c.relname AS table_name,
+            c.relkind::text AS relkind,
+            CASE WHEN c.relkind IN ('v', 'm') THEN pg_get_viewdef(c.oid, true) END AS definition,
+            obj_description(c.oid, 'pg_class') AS description
+        FROM pg_class c
+        INNER JOIN pg_namespace n ON c.relnamespace = n.oid
+        WHERE c.relkind IN ('r', 'p', 'v', 'm', 'f')
+            AND NOT c.relispartition
+            AND n.nspname NOT IN ('pg_catalog', 'information_schema') AND n.nspname NOT LIKE 'pg\_%'
+        ORDER BY 1, 2
+    "#;
+    
+    let rows = client.query(query, &[])
+        .await
+        .map_err(|e| format!("Failed to query tables: {}", e))?;
+    
+    let mut tables = Vec::new();
+    for row in rows {
+        let schema: &str = row.try_get(0).unwrap_or("public");
+        let name: &str = row.try_get(1).unwrap_or("");
+        let relkind: &str = row.try_get(2).unwrap_or("r");
+        let definition: Option<&str> = row.try_get(3).unwrap_or(None);
+        let description: Option<&str> = row.try_get(4).unwrap_or(None);
+        
+        let object_kind = match relkind {
+            "v" | "m" => ObjectKind::View,
+            "f" => ObjectKind::ExternalTable,
+            _ => ObjectKind::BaseTable,
+        };
+        
+        tables.push(Table {
+            schema: schema.to_string(),
+            name: name.to_string(),
+            description: description.map(|d| d.to_string()),
+            object_kind,
+            definition: definition.map(|d| d.to_string()),
+            synonym_target: None,
+            extended_properties: Default::default(),
+        });
+    }
+    
+    Ok(tables)
+}
+
+/// Extract all columns, with type facets from `information_schema`
+pub async fn get_columns(client: &Client) -> Result<Vec<Column>, String> {
+    let query = r#"
+        SELECT
+            n.nspname AS table_schema,
+            c.relname AS table_name,
+            a.attname AS column_name,
+            format_type(a.atttypid, NULL) AS data_type,
+            format_type(a.atttypid, a.atttypmod) AS full_type,
+            NOT a.attnotnull AS is_nullable,
+            a.attnum::int4 AS ordinal_position,
+            col.character_maximum_length::int4,
+            col.numeric_precision::int4,
+            col.numeric_scale::int4,
+            col.collation_name::text,
+            col.column_default::text,
+            col.identity_start::text,
+            col.identity_increment::text,
+            col.generation_expression::text,
+            col_description(c.oid, a.attnum) AS description
+        FROM pg_attribute a
+        INNER JOIN pg_class c ON a.attrelid = c.oid
+        INNER JOIN pg_namespace n ON c.relnamespace = n.oid
+        -- Materialized views are missing from information_schema, so facets stay empty for them
+        LEFT JOIN information_schema.columns col
+            ON col.table_schema = n.nspname AND col.table_name = c.relname AND col.column_name = a.attname
+        WHERE a.attnum > 0 AND NOT a.attisdropped
+            AND c.relkind IN ('r', 'p', 'v', 'm', 'f')
+            AND NOT c.relispartition
+            AND n.nspname NOT IN ('pg_catalog', 'information_schema') AND n.nspname NOT LIKE 'pg\_%'
+        ORDER BY 1, 2, a.attnum
+    "#;
+    
+    let rows = client.query(query, &[])
+        .await
+        .map_err(|e| format!("Failed to query columns: {}", e))?;
+    
+    let mut columns = Vec::new();
+    for row in rows {
+        let table_schema: &str = row.try_get(0).unwrap_or("public");
+        let table_name: &str = row.try_get(1).unwrap_or("");
+        let name: &str = row.try_get(2).unwrap_or("");
+        let data_type: &str = row.try_get(3).unwrap_or("");
+        let full_type: &str = row.try_get(4).unwrap_or(data_type);
+        let max_length: Option<i32> = row.try_get(7).unwrap_or(None);
+        let precision: Option<i32> = row.try_get(8).unwrap_or(None);
+        let scale: Option<i32> = row.try_get(9).unwrap_or(None);
+        let collation: Option<&str> = row.try_get(10).unwrap_or(None);
+        let default_value: Option<&str> = row.try_get(11).unwrap_or(None);
+        let identity_start: Option<&str> = row.try_get(12).unwrap_or(None);
+        let identity_increment: Option<&str> = row.try_get(13).unwrap_or(None);
+        let computed_definition: Option<&str> = row.try_get(14).unwrap_or(None);
+        let description: Option<&str> = row.try_get(15).unwrap_or(None);
+        
+        // information_schema reports a binary precision for integer and float types; only
+        // numeric carries a declared precision and scale
+        let is_numeric = data_type == "numeric";
+        
+        columns.push(Column {
+            table_schema: table_schema.to_string(),
+            table_name: table_name.to_string(),
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            full_type: full_type.to_string(),
+            is_nullable: row.try_get(5).unwrap_or(true),
+            is_primary_key: false, // Will be updated later
+            is_foreign_key: false, // Will be updated later
+            ordinal_position: row.try_get(6).unwrap_or(0),
+            description: description.map(|d| d.to_string()),
+            extended_properties: Default::default(),
+            max_length,
+            precision: precision.filter(|_| is_numeric).and_then(|p| u8::try_from(p).ok()),
+            scale: scale.filter(|_| is_numeric).and_then(|s| u8::try_from(s).ok()),
+            collation: collation.map(|c| c.to_string()),
+            default_value: default_value.map(|d| d.to_string()),
+            identity_seed: identity_start.and_then(|s| s.parse().ok()),
+            identity_increment: identity_increment.and_then(|s| s.parse().ok()),
+            computed_definition: computed_definition.map(|d| d.to_string()),
+            is_sparse: false,
+            is_rowguid: false,
+            is_filestream: false,
+        });
+    }
+    
+    Ok(columns)
+}
+
+/// Extract primary keys
+pub async fn get_primary_keys(client: &Client) -> Result<Vec<PrimaryKey>, String> {
+    let query = r#"
+        SELECT
+            n.nspname AS table_schema,
+            c.relname AS table_name,
+            a.attname AS column_name
+        FROM pg_constraint con
+        INNER JOIN pg_class c ON con.conrelid = c.oid
+        INNER JOIN pg_namespace n ON c.relnamespace = n.oid
+        CROSS JOIN LATERAL unnest(con.conkey) WITH ORDINALITY AS k(attnum, ord)
+        INNER JOIN pg_attribute a ON a.attrelid = c.oid AND a.attnum = k.attnum
+        WHERE con.contype = 'p'
+            AND NOT c.relispartition
+            AND n.nspname NOT IN ('pg_catalog', 'information_schema') AND n.nspname NOT LIKE 'pg\_%'
+        ORDER BY 1, 2, k.ord
+    "#;
+    
+    let rows = client.query(query, &[])
+        .await
+        .map_err(|e| format!("Failed to query primary keys: {}", e))?;
+    
+    let mut pks = Vec::new();
+    for row in rows {
+        let table_schema: &str = row.try_get(0).unwrap_or("public");
+        let table_name: &str = row.try_get(1).unwrap_or("");
+        let column_name: &str = row.try_get(2).unwrap_or("");
+        
+        pks.push(PrimaryKey {
+            table_schema: table_schema.to_string(),
+            table_name: table_name.to_string(),
+            column_name: column_name.to_string(),
+        });
+    }
+    
+    Ok(pks)
+}
+
+/// Map a `pg_constraint` action code to the names SQL Server uses
+fn referential_action(code: &str) -> &'static str {
+    match code {
+        "c" => "CASCADE",
+        "n" => "SET_NULL",
+        "d" => "SET_DEFAULT",
+        "r" => "RESTRICT",
+        _ => "NO_ACTION",
+    }
+}
+
+/// Extract foreign keys, with the column pairs of each constraint in key order
+pub async fn get_foreign_keys(client: &Client) -> Result<Vec<ForeignKey>, String> {
+    let query = r#"
+        SELECT
+            con.conname AS constraint_name,
+            pn.nspname AS parent_schema,
+            pc.relname AS parent_table,
+            rn.nspname AS referenced_schema,
+            rc.relname AS referenced_table,
+            con.confdeltype::text,
+            con.confupdtype::text,
+            NOT con.convalidated AS is_not_trusted,
+            pa.attname AS parent_column,
+            ra.attname AS referenced_column
+        FROM pg_constraint con
+        INNER JOIN pg_class pc ON con.conrelid = pc.oid
+        INNER JOIN pg_namespace pn ON pc.relnamespace = pn.oid
+        INNER JOIN pg_class rc ON con.confrelid = rc.oid
+        INNER JOIN pg_namespace rn ON rc.relnamespace = rn.oid
+        CROSS JOIN LATERAL unnest(con.conkey, con.confkey) WITH ORDINALITY AS k(parent_attnum, referenced_attnum, ord)
+        INNER JOIN pg_attribute pa ON pa.attrelid = pc.oid AND pa.attnum = k.parent_attnum
+        INNER JOIN pg_attribute ra ON ra.attrelid = rc.oid AND ra.attnum = k.referenced_attnum
+        WHERE con.contype = 'f'
+            -- Partitions carry clones of the constraints declared on their parent
+            AND NOT pc.relispartition AND NOT rc.relispartition
+            AND pn.nspname NOT IN ('pg_catalog', 'information_schema') AND pn.nspname NOT LIKE 'pg\_%'
+        ORDER BY con.oid, k.ord
+    "#;
+    
+    let rows = client.query(query, &[])
+        .await
+        .map_err(|e| format!("Failed to query foreign keys: {}", e))?;
+    
+    let mut fks: Vec<ForeignKey> = Vec::new();
+    for row in rows {
+        let constraint_name: &str = row.try_get(0).unwrap_or("");
+        let parent_schema: &str = row.try_get(1).unwrap_or("public");
+        let parent_table: &str = row.try_get(2).unwrap_or("");
+        let parent_column: &str = row.try_get(8).unwrap_or("");
+        let referenced_column: &str = row.try_get(9).unwrap_or("");
+        
+        let column = ForeignKeyColumn {
+            parent_column: parent_column.to_string(),
+            referenced_column: referenced_column.to_string(),
+        };
+        
+        // Rows are ordered by constraint, so further columns of a composite key follow directly.
+        // Constraint names are only unique per table in PostgreSQL.
+        if let Some(fk) = fks.last_mut() {
+            if fk.constraint_name == constraint_name && fk.parent_schema == parent_schema && fk.parent_table == parent_table {
+                fk.columns.push(column);
+                continue;
+            }
+        }
+        
+        let referenced_schema: &str = row.try_get(3).unwrap_or("public");
+        let referenced_table: &str = row.try_get(4).unwrap_or("");
+        let delete_action: &str = row.try_get(5).unwrap_or("a");
+        let update_action: &str = row.try_get(6).unwrap_or("a");
+        
+        fks.push(ForeignKey {
+            constraint_name: constraint_name.to_string(),
+            parent_schema: parent_schema.to_string(),
+            parent_table: parent_table.to_string(),
+            referenced_schema: referenced_schema.to_string(),
+            referenced_table: referenced_table.to_string(),
+            columns: vec![column],
+            delete_referential_action: referential_action(delete_action).to_string(),
+            update_referential_action: referential_action(update_action).to_string(),
+            is_disabled: false,
+            is_not_trusted: row.try_get(7).unwrap_or(false),
+        });
+    }
+    
+    Ok(fks)
+}
+
+/// Extract indexes with their key and included columns
+pub async fn get_indexes(client: &Client) -> Result<Vec<Index>, String> {
+    let query = r#"
+        SELECT
+            ix.indexrelid::int8 AS index_oid,
+            n.nspname AS table_schema,
+            t.relname AS table_name,
+            i.relname AS index_name,
+            upper(am.amname) AS index_type,
+            ix.indisunique,
+            ix.indisprimary,
+            EXISTS (
+                SELECT 1 FROM pg_constraint con WHERE con.conindid = ix.indexrelid AND con.contype = 'u'
+            ) AS is_unique_constraint,
+            pg_get_expr(ix.indpred, ix.indrelid) AS filter_definition,
+            pg_get_indexdef(ix.indexrelid, k.ord, true) AS column_name,
+            (ix.indoption[k.ord - 1] & 1) = 1 AS is_descending,
+            k.ord > ix.indnkeyatts AS is_included
+        FROM pg_index ix
+        INNER JOIN pg_class i ON ix.indexrelid = i.oid
+        INNER JOIN pg_class t ON ix.indrelid = t.oid
+        INNER JOIN pg_namespace n ON t.relnamespace = n.oid
+        INNER JOIN pg_am am ON i.relam = am.oid
+        CROSS JOIN LATERAL generate_series(1, ix.indnatts::int4) AS k(ord)
+        WHERE NOT t.relispartition
+            AND n.nspname NOT IN ('pg_catalog', 'information_schema') AND n.nspname NOT LIKE 'pg\_%'
+        ORDER BY 1, k.ord
+    "#;
+    
+    let rows = client.query(query, &[])
+        .await
+        .map_err(|e| format!("Failed to query indexes: {}", e))?;
+    
+    let mut indexes: Vec<Index> = Vec::new();
+    let mut current: Option<i64> = None;
+    for row in rows {
+        let index_oid: i64 = row.try_get(0).unwrap_or(0);
+        let column_name: &str = row.try_get(9).unwrap_or("");
+        let is_descending: bool = row.try_get(10).unwrap_or(false);
+        let is_included: bool = row.try_get(11).unwrap_or(false);
+        
+        // Rows are ordered by index, so a new oid starts a new index
+        if current != Some(index_oid) {
+            current = Some(index_oid);
+            
+            let table_schema: &str = row.try_get(1).unwrap_or("public");
+            let table_name: &str = row.try_get(2).unwrap_or("");
+            let index_name: &str = row.try_get(3).unwrap_or("");
+            let index_type: &str = row.try_get(4).unwrap_or("");
+            let filter_definition: Option<&str> = row.try_get(8).unwrap_or(None);
+            
+            indexes.push(Index {
+                table_schema: table_schema.to_string(),
+                table_name: table_name.to_string(),
+                name: index_name.to_string(),
+                index_type: index_type.to_string(),
+                is_unique: row.try_get(5).unwrap_or(false),
+                is_primary_key: row.try_get(6).unwrap_or(false),
+                is_unique_constraint: row.try_get(7).unwrap_or(false),
+                key_columns: Vec::new(),
+                included_columns: Vec::new(),
+                filter_definition: filter_definition.map(|f| f.to_string()),
+            });
+        }
+        
+        if let Some(index) = indexes.last_mut() {
+            if is_included {
+                index.included_columns.push(column_name.to_string());
+            } else {
+                index.key_columns.push(IndexColumn {
+                    name: column_name.to_string(),
+                    is_descending,
+                });
+            }
+        }
+    }
+    
+    Ok(indexes)
+}
+
+/// Extract check constraints
+pub async fn get_check_constraints(client: &Client) -> Result<Vec<CheckConstraint>, String> {
+    let query = r#"
+        SELECT
+            n.nspname AS table_schema,
+            c.relname AS table_name,
+            con.conname AS constraint_name,
+            -- Constraints on a single column are reported as column-level
+            CASE WHEN cardinality(con.conkey) = 1 THEN (
+                SELECT a.attname FROM pg_attribute a WHERE a.attrelid = c.oid AND a.attnum = con.conkey[1]
+            ) END AS column_name,
+            pg_get_constraintdef(con.oid, true) AS definition,
+            NOT con.convalidated AS is_not_trusted
+        FROM pg_constraint con
+        INNER JOIN pg_class c ON con.conrelid = c.oid
+        INNER JOIN pg_namespace n ON c.relnamespace = n.oid
+        WHERE con.contype = 'c'
+            AND NOT c.relispartition
+            AND n.nspname NOT IN ('pg_catalog', 'information_schema') AND n.nspname NOT LIKE 'pg\_%'
+        ORDER BY 1, 2, 3
+    "#;
+    
+    let rows = client.query(query, &[])
+        .await
+        .map_err(|e| format!("Failed to query check constraints: {}", e))?;
+    
+    let mut constraints = Vec::new();
+    for row in rows {
+        let table_schema: &str = row.try_get(0).unwrap_or("public");
+        let table_name: &str = row.try_get(1).unwrap_or("");
+        let constraint_name: &str = row.try_get(2).unwrap_or("");
+        let column_name: Option<&str> = row.try_get(3).unwrap_or(None);
+        let definition: &str = row.try_get(4).unwrap_or("");
+        
+        constraints.push(CheckConstraint {
+            table_schema: table_schema.to_string(),
+            table_name: table_name.to_string(),
+            constraint_name: constraint_name.to_string(),
+            column_name: column_name.map(|c| c.to_string()),
+            definition: definition.to_string(),
+            is_disabled: false,
+            is_not_trusted: row.try_get(5).unwrap_or(false),
+        });
+    }
+    
+    Ok(constraints)
+}
+
+/// Extract functions, procedures and triggers. Functions installed by extensions are skipped.
+pub async fn get_routines(client: &Client) -> Result<Vec<Routine>, String> {
+    let query = r#"
+        SELECT
+            n.nspname AS routine_schema,
+            p.proname AS routine_name,
+            p.proname || '_' || p.oid AS specific_name,
+            p.prokind::text AS prokind,
+            p.proretset,
+            format_type(p.prorettype, NULL) AS return_type,
+            pg_get_functiondef(p.oid) AS definition,
+            obj_description(p.oid, 'pg_proc') AS description
+        FROM pg_proc p
+        INNER JOIN pg_namespace n ON p.pronamespace = n.oid
+        WHERE p.prokind IN ('f', 'p')
+            AND n.nspname NOT IN ('pg_catalog', 'information_schema') AND n.nspname NOT LIKE 'pg\_%'
+            AND NOT EXISTS (
+                SELECT 1 FROM pg_depend d
+                WHERE d.classid = 'pg_proc'::regclass AND d.objid = p.oid AND d.deptype = 'e'
+            )
+        ORDER BY 1, 2
+    "#;
+    
+    let rows = client.query(query, &[])
+        .await
+        .map_err(|e| format!("Failed to query routines: {}", e))?;
+    
+    let mut routines = Vec::new();
+    // (schema, specific name) -> position in `routines`, to attach parameters
+    let mut positions: HashMap<(String, String), usize> = HashMap::new();
+    for row in rows {
+        let schema: &str = row.try_get(0).unwrap_or("public");
+        let name: &str = row.try_get(1).unwrap_or("");
+        let specific_name: &str = row.try_get(2).unwrap_or("");
+        let prokind: &str = row.try_get(3).unwrap_or("f");
+        let returns_set: bool = row.try_get(4).unwrap_or(false);
+        let return_type: &str = row.try_get(5).unwrap_or("void");
+        let definition: Option<&str> = row.try_get(6).unwrap_or(None);
+        let description: Option<&str> = row.try_get(7).unwrap_or(None);
+        
+        let routine_kind = match prokind {
+            "p" => RoutineKind::StoredProcedure,
+            _ if returns_set || return_type == "record" => RoutineKind::TableValuedFunction,
+            _ => RoutineKind::ScalarFunction,
+        };
+        
+        // `RETURNS TABLE (...)` columns arrive as OUT parameters; other results become a return value
+        let mut parameters = Vec::new();
+        if prokind == "f" && return_type != "void" && return_type != "record" {
+            parameters.push(RoutineParameter {
+                name: String::new(),
+                data_type: if returns_set { format!("setof {}", return_type) } else { return_type.to_string() },
+                direction: ParameterDirection::Return,
+                default_value: None,
+                ordinal_position: 0,
+            });
+        }
+        
+        positions.insert((schema.to_string(), specific_name.to_string()), routines.len());
+        routines.push(Routine {
+            schema: schema.to_string(),
+            name: name.to_string(),
+            routine_kind,
+            description: description.map(|d| d.to_string()),
+            extended_properties: Default::default(),
+            parent_schema: None,
+            parent_table: None,
+            definition: definition.map(|d| d.to_string()),
+            parameters,
+        });
+    }
+    
+    let query = r#"
+        SELECT
+            par.specific_schema::text,
+            par.specific_name::text,
+            par.ordinal_position::int4,
+            par.parameter_mode::text,
+            COALESCE(par.parameter_name::text, '') AS parameter_name,
+            COALESCE(format_type(t.oid, NULL), par.data_type::text) AS data_type,
+            par.parameter_default::text
+        FROM information_schema.parameters par
+        LEFT JOIN (pg_type t INNER JOIN pg_namespace tn ON t.typnamespace = tn.oid)
+            ON t.typname = par.udt_name AND tn.nspname = par.udt_schema
+        WHERE par.specific_schema NOT IN ('pg_catalog', 'information_schema')
+        ORDER BY 1, 2, 3
+    "#;
+    
+    let rows = client.query(query, &[])
+        .await
+        .map_err(|e| format!("Failed to query routine parameters: {}", e))?;
+    
+    for row in rows {
+        let schema: &str = row.try_get(0).unwrap_or("");
+        let specific_name: &str = row.try_get(1).unwrap_or("");
+        let Some(&i) = positions.get(&(schema.to_string(), specific_name.to_string())) else {
+            continue;
+        };
+        
+        let mode: &str = row.try_get(3).unwrap_or("IN");
+        let name: &str = row.try_get(4).unwrap_or("");
+        let data_type: &str = row.try_get(5).unwrap_or("");
+        let default_value: Option<&str> = row.try_get(6).unwrap_or(None);
+        
+        routines[i].parameters.push(RoutineParameter {
+            name: name.to_string(),
+            data_type: data_type.to_string(),
+            direction: match mode {
+                "OUT" => ParameterDirection::Out,
+                "INOUT" => ParameterDirection::InOut,
+                _ => ParameterDirection::In,
+            },
+            default_value: default_value.map(|d| d.to_string()),
+            ordinal_position: row.try_get(2).unwrap_or(0),
+        });
+    }
+    
+    routines.extend(get_triggers(client).await?);
+    
+    Ok(routines)
+}
+
+/// Extract user-defined triggers
+async fn get_triggers(client: &Client) -> Result<Vec<Routine>, String> {
+    let query = r#"
+        SELECT
+            n.nspname AS table_schema,
+            t.tgname AS trigger_name,
+            c.relname AS table_name,
+            pg_get_triggerdef(t.oid, true) AS definition,
+            obj_description(t.oid, 'pg_trigger') AS description
+        FROM pg_trigger t
+        INNER JOIN pg_class c ON t.tgrelid = c.oid
+        INNER JOIN pg_namespace n ON c.relnamespace = n.oid
+        WHERE NOT t.tgisinternal
+            AND n.nspname NOT IN ('pg_catalog', 'information_schema') AND n.nspname NOT LIKE 'pg\_%'
+        ORDER BY 1, 2
+    "#;
+    
+    let rows = client.query(query, &[])
+        .await
+        .map_err(|e| format!("Failed to query triggers: {}", e))?;
+    
+    let mut triggers = Vec::new();
+    for row in rows {
+        let schema: &str = row.try_get(0).unwrap_or("public");
+        let name: &str = row.try_get(1).unwrap_or("");
+        let table_name: &str = row.try_get(2).unwrap_or("");
+        let definition: Option<&str> = row.try_get(3).unwrap_or(None);
+        let description: Option<&str> = row.try_get(4).unwrap_or(None);
+        
+        triggers.push(Routine {
+            schema: schema.to_string(),
+            name: name.to_string(),
+            routine_kind: RoutineKind::Trigger,
+            description: description.map(|d| d.to_string()),
+            extended_properties: Default::default(),
+            parent_schema: Some(schema.to_string()),
+            parent_table: Some(table_name.to_string()),
+            definition: definition.map(|d| d.to_string()),
+            parameters: Vec::new(),
+        });
+    }
+    
+    Ok(triggers)
+}
+
+/// A PostgreSQL session the schema is read from. Comments are read together with the
+/// objects they describe, so `get_properties` keeps its default.
+pub struct PostgresSource {
+    client: Client,
+}
+
+impl SchemaSource for PostgresSource {
+    async fn connect(connection: &Connection, password: Option<&str>) -> Result<Self, ConnectError> {
+        let client = connect(connection, password).await?;
+        Ok(Self { client })
+    }
+    
+    async fn probe(&mut self) -> Result<ServerProbe, String> {
+        probe_server(&self.client).await
+    }
+    
+    async fn get_tables(&mut self) -> Result<Vec<Table>, String> {
+        get_tables(&self.client).await
+    }
+    
+    async fn get_columns(&mut self) -> Result<Vec<Column>, String> {
+        get_columns(&self.client).await
+    }
+    
+    async fn get_primary_keys(&mut self) -> Result<Vec<PrimaryKey>, String> {
+        get_primary_keys(&self.client).await
+    }
+    
+    async fn get_foreign_keys(&mut self) -> Result<Vec<ForeignKey>, String> {
+        get_foreign_keys(&self.client).await
+    }
+    
+    async fn get_indexes(&mut self) -> Result<Vec<Index>, String> {
+        get_indexes(&self.client).await
+    }
+    
+    async fn get_check_constraints(&mut self) -> Result<Vec<CheckConstraint>, String> {
+        get_check_constraints(&self.client).await
+    }
+    
+    async fn get_routines(&mut self) -> Result<Vec<Routine>, String> {
+        get_routines(&self.client).await
+    }
+}
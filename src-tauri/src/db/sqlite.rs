@@ -0,0 +1,545 @@
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use rusqlite::{Connection as SqliteConnection, OpenFlags, Row};
+use crate::config::Connection;
+use crate::commands::schema::{
+    Table, ObjectKind, Column, ForeignKey, ForeignKeyColumn, PrimaryKey, Index, IndexColumn,
+    Routine, RoutineKind,
+};
+use super::{ConnectError, ConnectErrorKind, ServerProbe, SchemaSource, run_blocking};
+
+/// SQLite has a single namespace per file; objects are reported in the `main` schema
+const SCHEMA: &str = "main";
+
+/// Excludes SQLite's own bookkeeping tables and indexes
+const USER_OBJECTS: &str = r"m.name NOT LIKE 'sqlite\_%' ESCAPE '\'";
+
+/// Open the connection's database file read-only
+pub fn connect(connection: &Connection) -> Result<SqliteConnection, ConnectError> {
+    let path = match connection.file_path.as_deref().map(str::trim) {
+        Some(path) if !path.is_empty() => Path::new(path),
+        _ => return Err(ConnectError::new(ConnectErrorKind::Configuration, "Database file is required")),
+    };
+    
+    if !path.is_file() {
+        return Err(ConnectError::new(
+            ConnectErrorKind::DatabaseNotFound,
+            format!("Database file not found: {}", path.display()),
+        ));
+    }
+    
+    println!("Opening {}", path.display());
+    
+    let conn = SqliteConnection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX)
+        .map_err(|e| ConnectError::new(ConnectErrorKind::Other, format!("Failed to open database file: {}", e)))?;
+    
+    // Opening is lazy; read the schema once so files that are not databases fail here
+    conn.query_row("SELECT count(*) FROM sqlite_schema", [], |row| row.get::<_, i64>(0))
+        .map_err(|e| ConnectError::new(ConnectErrorKind::Other, format!("Failed to read database file: {}", e)))?;
+    
+    println!("Opened successfully!");
+    Ok(conn)
+}
+
+/// Report the SQLite library version and the file name
+pub fn probe_file(conn: &SqliteConnection) -> Result<ServerProbe, String> {
+    let version: String = conn.query_row("SELECT sqlite_version()", [], |row| row.get(0))
+        .map_err(|e| format!("Failed to query SQLite version: {}", e))?;
+    
+    let database_name = conn.path()
+        .and_then(|p| Path::new(p).file_name())
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    
+    Ok(ServerProbe {
+        server_version: format!("SQLite {}", version),
+        database_name,
+        login_name: String::new(),
+    })
+}
+
+/// Split a declared type such as `VARCHAR(50)` or `DECIMAL(10, 2)` into its lowercase
+/// name and facets. SQLite does not enforce either; they are reported as declared.
+fn type_facets(declared: &str) -> (String, Option<i32>, Option<u8>, Option<u8>) {
+    let (name, arguments) = match declared.split_once('(') {
+        Some((name, rest)) => (name, rest.trim_end().trim_end_matches(')')),
+        None => (declared, ""),
+    };
+    let name = name.trim().to_lowercase();
+    let mut arguments = arguments.split(',').map(|a| a.trim());
+    let first = arguments.next().and_then(|a| a.parse::<i64>().ok());
+    let second = arguments.next().and_then(|a| a.parse::<i64>().ok());
+    
+    if name.contains("char") || name.contains("binary") {
+        (name, first.and_then(|l| i32::try_from(l).ok()), None, None)
+    } else if name.contains("decimal") || name.contains("numeric") {
+        let precision = first.and_then(|p| u8::try_from(p).ok());
+        let scale = second.and_then(|s| u8::try_from(s).ok());
+        (name, None, precision, scale)
+    } else {
+        (name, None, None, None)
+    }
+}
+
+/// Extract tables and views
+pub fn get_tables(conn: &SqliteConnection) -> Result<Vec<Table>, String> {
+    let query = format!(
+        "SELECT m.type, m.name, m.sql FROM sqlite_schema m WHERE m.type IN ('table', 'view') AND {} ORDER BY m.name",
+        USER_OBJECTS,
+    );
+    let mut stmt = conn.prepare(&query)
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    
+    let tables: Vec<Table> = stmt.query_map([], |row| {
+        let is_view = row.get::<_, String>(0)? == "view";
+        Ok(Table {
+            schema: SCHEMA.to_string(),
+            name: row.get(1)?,
+            description: None,
+            object_kind: if is_view { ObjectKind::View } else { ObjectKind::BaseTable },
+            definition: if is_view { row.get(2)? } else { None },
+            synonym_target: None,
+            extended_properties: Default::default(),
+        })
+    }).map_err(|e| format!("Failed to query tables: {}", e))?
+    .filter_map(|r| r.ok())
+    .collect();
+    
+    Ok(tables)
+}
+
+/// Extract all columns of tables and views, including generated columns
+pub fn get_columns(conn: &SqliteConnection) -> Result<Vec<Column>, String> {
+    let query = format!(
+        r#"SELECT m.name, p.cid, p.name, p.type, p."notnull", p.dflt_value, p.pk,
+                  -- The rowid alias of an AUTOINCREMENT table is its only key column
+                  p.pk = 1 AND upper(p.type) = 'INTEGER' AND upper(m.sql) LIKE '%AUTOINCREMENT%'
+                      AND (SELECT count(*) FROM pragma_table_info(m.name) k WHERE k.pk > 0) = 1
+           FROM sqlite_schema m
+           INNER JOIN pragma_table_xinfo(m.name) p
+           WHERE m.type IN ('table', 'view') AND {}
+           ORDER BY m.name, p.cid"#,
+        USER_OBJECTS,
+    );
+    let mut stmt = conn.prepare(&query)
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    
+    let columns: Vec<Column> = stmt.query_map([], |row| {
+        let full_type: String = row.get(3)?;
+        let (data_type, max_length, precision, scale) = type_facets(&full_type);
+        let is_auto_increment = row.get::<_, i32>(7)? != 0;
+        
+        Ok(Column {
+            table_schema: SCHEMA.to_string(),
+            table_name: row.get(0)?,
+            name: row.get(2)?,
+            data_type,
+            full_type,
+            is_nullable: row.get::<_, i32>(4)? == 0 && row.get::<_, i32>(6)? == 0,
+            is_primary_key: false, // Will be updated later
+            is_foreign_key: false, // Will be updated later
+            ordinal_position: row.get::<_, i32>(1)? + 1,
+            description: None,
+            extended_properties: Default::default(),
+            max_length,
+            precision,
+            scale,
+            collation: None,
+            default_value: row.get(5)?,
+            identity_seed: is_auto_increment.then_some(1),
+            identity_increment: is_auto_increment.then_some(1),
+            computed_definition: None,
+            is_sparse: false,
+            is_rowguid: false,
+            is_filestream: false,
+        })
+    }).map_err(|e| format!("Failed to query columns: {}", e))?
+    .filter_map(|r| r.ok())
+    .collect();
+    
+    Ok(columns)
+}
+
+/// Extract primary keys in key order
+pub fn get_primary_keys(conn: &SqliteConnection) -> Result<Vec<PrimaryKey>, String> {
+    let query = format!(
+        "SELECT m.name, p.name FROM sqlite_schema m INNER JOIN pragma_table_info(m.name) p
+         WHERE m.type = 'table' AND p.pk > 0 AND {} ORDER BY m.name, p.pk",
+        USER_OBJECTS,
+    );
+    let mut stmt = conn.prepare(&query)
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    
+    let pks: Vec<PrimaryKey> = stmt.query_map([], |row| {
+        Ok(PrimaryKey {
+            table_schema: SCHEMA.to_string(),
+            table_name: row.get(0)?,
+            column_name: row.get(1)?,
+        })
+    }).map_err(|e| format!("Failed to query primary keys: {}", e))?
+    .filter_map(|r| r.ok())
+    .collect();
+    
+    Ok(pks)
+}
+
+/// Extract foreign keys. SQLite does not name them, so names are made up from the table
+/// and the key's position in `pragma_foreign_key_list`.
+pub fn get_foreign_keys(conn: &SqliteConnection) -> Result<Vec<ForeignKey>, String> {
+    let query = format!(
+        r#"SELECT m.name, f.id, f."table", f."from",
+                  -- A reference without columns points at the parent's primary key
+                  COALESCE(f."to", (SELECT k.name FROM pragma_table_info(f."table") k WHERE k.pk = f.seq + 1)),
+                  f.on_delete, f.on_update
+           FROM sqlite_schema m
+           INNER JOIN pragma_foreign_key_list(m.name) f
+           WHERE m.type = 'table' AND {}
+           ORDER BY m.name, f.id, f.seq"#,
+        USER_OBJECTS,
+    );
+    let mut stmt = conn.prepare(&query)
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let mut rows = stmt.query([])
+        .map_err(|e| format!("Failed to query foreign keys: {}", e))?;
+    
+    let mut fks: Vec<ForeignKey> = Vec::new();
+    while let Some(row) = rows.next().map_err(|e| format!("Failed to read foreign keys: {}", e))? {
+        let parent_table: String = row.get(0).unwrap_or_default();
+        let id: i64 = row.get(1).unwrap_or(0);
+        let constraint_name = format!("fk_{}_{}", parent_table, id);
+        
+        let column = ForeignKeyColumn {
+            parent_column: row.get(3).unwrap_or_default(),
+            referenced_column: row.get::<_, Option<String>>(4).unwrap_or(None).unwrap_or_default(),
+        };
+        
+        // Rows are ordered by key, so further columns of a composite key follow directly
+        if let Some(fk) = fks.last_mut() {
+            if fk.constraint_name == constraint_name && fk.parent_table == parent_table {
+                fk.columns.push(column);
+                continue;
+            }
+        }
+        
+        fks.push(ForeignKey {
+            constraint_name,
+            parent_schema: SCHEMA.to_string(),
+            parent_table,
+            referenced_schema: SCHEMA.to_string(),
+            referenced_table: row.get(2).unwrap_or_default(),
+            columns: vec![column],
+            delete_referential_action: referential_action(row, 5),
+            update_referential_action: referential_action(row, 6),
+            is_disabled: false,
+            is_not_trusted: false,
+        });
+    }
+    
+    Ok(fks)
+}
+
+/// Map an action reported by SQLite (`SET NULL`) to the names SQL Server uses (`SET_NULL`)
+fn referential_action(row: &Row, index: usize) -> String {
+    row.get::<_, String>(index)
+        .map(|a| a.replace(' ', "_"))
+        .unwrap_or_else(|_| "NO_ACTION".to_string())
+}
+
+/// Extract indexes with their key columns. Tables whose key is the rowid have no
+/// primary key index.
+pub fn get_indexes(conn: &SqliteConnection) -> Result<Vec<Index>, String> {
+    let query = format!(
+        r#"SELECT m.name, l.name, l."unique", l.origin, l.partial, s.sql, x.name, x."desc"
+           FROM sqlite_schema m
+           INNER JOIN pragma_index_list(m.name) l
+           INNER JOIN pragma_index_xinfo(l.name) x
+           LEFT JOIN sqlite_schema s ON s.type = 'index' AND s.name = l.name
+           WHERE m.type = 'table' AND x.key AND {}
+           ORDER BY m.name, l.name, x.seqno"#,
+        USER_OBJECTS,
+    );
+    let mut stmt = conn.prepare(&query)
+        .map_err(|e| format!("Failed to prepare query: {}", e))?;
+    let mut rows = stmt.query([])
+        .map_err(|e| format!("Failed to query indexes: {}", e))?;
+    
+    let mut indexes: Vec<Index> = Vec::new();
+    while let Some(row) = rows.next().map_err(|e| format!("Failed to read indexes: {}", e))? {
+        let table_name: String = row.get(0).unwrap_or_default();
+        let index_name: String = row.get(1).unwrap_or_default();
+        
+        // Rows are ordered by index, so a new name starts a new index
+        if !indexes.last().is_some_and(|i| i.name == index_name && i.table_name == table_name) {
+            let origin: String = row.get(3).unwrap_or_default();
+            let is_partial = row.get::<_, i32>(4).unwrap_or(0) != 0;
+            let sql: Option<String> = row.get(5).unwrap_or(None);
+            
+            indexes.push(Index {
+                table_schema: SCHEMA.to_string(),
+                table_name,
+                name: index_name,
+                index_type: "BTREE".to_string(),
+                is_unique: row.get::<_, i32>(2).unwrap_or(0) != 0,
+                is_primary_key: origin == "pk",
+                is_unique_constraint: origin == "u",
+                key_columns: Vec::new(),
+                included_columns: Vec::new(),
+                filter_definition: sql.filter(|_| is_partial).and_then(|s| partial_index_predicate(&s)),
+            });
+        }
+        
+        if let Some(index) = indexes.last_mut() {
+            index.key_columns.push(IndexColumn {
+                // Expression key parts have no column name
+                name: row.get::<_, Option<String>>(6).unwrap_or(None).unwrap_or_else(|| "<expression>".to_string()),
+                is_descending: row.get::<_, i32>(7).unwrap_or(0) != 0,
+            });
+        }
+    }
+    
+    Ok(indexes)
+}
+
+/// The `WHERE` clause of a `CREATE INDEX` statement: the first `WHERE` keyword outside
+/// parentheses, quotes and comments, whatever whitespace surrounds it
+fn partial_index_predicate(sql: &str) -> Option<String> {
+    let bytes = sql.as_bytes();
+    let is_word = |i: usize| bytes.get(i).is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_' || *b == b'$');
+    let mut depth = 0;
+    let mut i = 0;
+    
+    while i < bytes.len() {
+        let closing = match bytes[i] {
+            b'\'' => Some(b'\''),
+            b'"' => Some(b'"'),
+            b'`' => Some(b'`'),
+            b'[' => Some(b']'),
+            _ => None,
+        };
+        if let Some(closing) = closing {
+            // A doubled quote inside is an escaped one and is skipped as two quoted parts
+            i = sql[i + 1..].find(closing as char).map_or(bytes.len(), |end| i + 1 + end + 1);
+            continue;
+        }
+        if sql[i..].starts_with("--") {
+            i = sql[i..].find('\n').map_or(bytes.len(), |end| i + end + 1);
+            continue;
+        }
+        if sql[i..].starts_with("/*") {
+            i = sql[i + 2..].find("*/").map_or(bytes.len(), |end| i + 2 + end + 2);
+            continue;
+        }
+        
+        match bytes[i] {
+            b'(' => depth += 1,
+            b')' => depth -= 1,
+            _ if depth == 0
+                && !is_word(i.wrapping_sub(1))
+                && bytes[i..].len() >= 5
+                && bytes[i..i + 5].eq_ignore_ascii_case(b"WHERE")
+                && !is_word(i + 5) =>
+            {
+                return Some(sql[i + 5..].trim().to_string());
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    
+    None
+}
+
+/// Extract triggers; SQLite has no stored procedures or functions
+pub fn get_triggers(conn: &SqliteConnection) -> Result<Vec<Routine>, String> {
+    let mut stmt = conn.prepare(
+        "SELECT name, tbl_name, sql FROM sqlite_schema WHERE type = 'trigger' ORDER BY name"
+    ).map_err(|e| format!("Failed to prepare query: {}", e))?;
+    
+    let triggers: Vec<Routine> = stmt.query_map([], |row| {
+        Ok(Routine {
+            schema: SCHEMA.to_string(),
+            name: row.get(0)?,
+            routine_kind: RoutineKind::Trigger,
+            description: None,
+            extended_properties: Default::default(),
+            parent_schema: Some(SCHEMA.to_string()),
+            parent_table: row.get(1)?,
+            definition: row.get(2)?,
+            parameters: Vec::new(),
+        })
+    }).map_err(|e| format!("Failed to query triggers: {}", e))?
+    .filter_map(|r| r.ok())
+    .collect();
+    
+    Ok(triggers)
+}
+
+/// A local SQLite database file the schema is read from. Each query runs on the blocking
+/// thread pool, so a cancelled refresh stops before the next one.
+pub struct SqliteSource {
+    conn: Arc<Mutex<SqliteConnection>>,
+}
+
+impl SqliteSource {
+    async fn read<T: Send + 'static>(&self, query: fn(&SqliteConnection) -> Result<T, String>) -> Result<T, String> {
+        let conn = self.conn.clone();
+        run_blocking(move |cancellation| {
+            if cancellation.is_cancelled() {
+                return Err("Reading the database was cancelled".to_string());
+            }
+            query(&conn.lock().unwrap())
+        })
+        .await
+        .and_then(|result| result)
+    }
+}
+
+impl SchemaSource for SqliteSource {
+    async fn connect(connection: &Connection, _password: Option<&str>) -> Result<Self, ConnectError> {
+        let connection = connection.clone();
+        let conn = run_blocking(move |_| connect(&connection))
+            .await
+            .unwrap_or_else(|e| Err(ConnectError::new(ConnectErrorKind::Other, e)))?;
+        Ok(Self { conn: Arc::new(Mutex::new(conn)) })
+    }
+    
+    async fn probe(&mut self) -> Result<ServerProbe, String> {
+        self.read(probe_file).await
+    }
+    
+    async fn get_tables(&mut self) -> Result<Vec<Table>, String> {
+        self.read(get_tables).await
+    }
+    
+    async fn get_columns(&mut self) -> Result<Vec<Column>, String> {
+        self.read(get_columns).await
+    }
+    
+    async fn get_primary_keys(&mut self) -> Result<Vec<PrimaryKey>, String> {
+        self.read(get_primary_keys).await
+    }
+    
+    async fn get_foreign_keys(&mut self) -> Result<Vec<ForeignKey>, String> {
+        self.read(get_foreign_keys).await
+    }
+    
+    async fn get_indexes(&mut self) -> Result<Vec<Index>, String> {
+        self.read(get_indexes).await
+    }
+    
+    async fn get_routines(&mut self) -> Result<Vec<Routine>, String> {
+        self.read(get_triggers).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{cache, extract_schema};
+    
+    #[test]
+    fn predicate_after_any_whitespace() {
+        assert_eq!(partial_index_predicate("CREATE INDEX i ON t (a) WHERE a > 0").as_deref(), Some("a > 0"));
+        assert_eq!(partial_index_predicate("CREATE INDEX i ON t (a)\n\twhere\n a > 0").as_deref(), Some("a > 0"));
+        assert_eq!(partial_index_predicate("CREATE INDEX i ON t(a)WHERE(a > 0)").as_deref(), Some("(a > 0)"));
+        assert_eq!(
+            partial_index_predicate(r#"CREATE INDEX "where" ON t ("x where", a) WHERE b = 'WHERE'"#).as_deref(),
+            Some("b = 'WHERE'"),
+        );
+        assert_eq!(partial_index_predicate("CREATE INDEX nowhere ON t (somewhere_else)"), None);
+    }
+    
+    #[tokio::test]
+    async fn schema_round_trips_through_the_cache() {
+        let path = std::env::temp_dir().join(format!("glance-sqlite-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        SqliteConnection::open(&path).unwrap().execute_batch("
+            CREATE TABLE customer (id INTEGER PRIMARY KEY AUTOINCREMENT, email VARCHAR(200) NOT NULL UNIQUE);
+            CREATE TABLE orders (
+                customer_id INTEGER NOT NULL REFERENCES customer,
+                number INTEGER NOT NULL,
+                total DECIMAL(10, 2) DEFAULT 0,
+                PRIMARY KEY (customer_id, number)
+            );
+            CREATE TABLE shipment (
+                id INTEGER PRIMARY KEY,
+                customer_id INTEGER,
+                order_number INTEGER,
+                FOREIGN KEY (customer_id, order_number) REFERENCES orders (customer_id, number) ON DELETE CASCADE
+            );
+            CREATE INDEX ix_orders_big ON orders (total DESC)
+                WHERE total > 100;
+            CREATE TRIGGER trg_orders AFTER INSERT ON orders
+            BEGIN
+                UPDATE customer SET email = email WHERE id = NEW.customer_id;
+            END;
+        ").unwrap();
+        
+        let connection: Connection = serde_json::from_value(serde_json::json!({
+            "id": "sqlite-test",
+            "name": "SQLite test",
+            "provider": "sqlite",
+            "server": "",
+            "database": "",
+            "filePath": path.to_string_lossy(),
+            "authType": "none",
+            "isDefault": false,
+        })).unwrap();
+        let extraction = extract_schema(&connection, None, None).await.unwrap();
+        let extracted = extraction.schema;
+        
+        let mut cache_db = SqliteConnection::open_in_memory().unwrap();
+        cache::migrate(&mut cache_db).unwrap();
+        cache::store_schema(&mut cache_db, &extracted, &connection.id, &extraction.server_version, &extraction.versions).unwrap();
+        let cached = cache::read_schema(&mut cache_db, &connection.id).unwrap();
+        let _ = std::fs::remove_file(&path);
+        
+        for schema in [&extracted, &cached] {
+            let mut tables: Vec<&str> = schema.tables.iter().map(|t| t.name.as_str()).collect();
+            tables.sort();
+            assert_eq!(tables, ["customer", "orders", "shipment"]);
+            
+            let column = |table: &str, name: &str| {
+                schema.columns.iter().find(|c| c.table_name == table && c.name == name).unwrap()
+            };
+            let id = column("customer", "id");
+            assert!(id.is_primary_key && !id.is_nullable);
+            assert_eq!((id.identity_seed, id.identity_increment), (Some(1), Some(1)));
+            assert_eq!(column("shipment", "id").identity_seed, None);
+            assert_eq!(column("customer", "email").max_length, Some(200));
+            let total = column("orders", "total");
+            assert_eq!((total.precision, total.scale), (Some(10), Some(2)));
+            assert_eq!(total.default_value.as_deref(), Some("0"));
+            assert!(column("orders", "customer_id").is_foreign_key);
+            
+            let mut order_key: Vec<&str> = schema.primary_keys.iter()
+                .filter(|k| k.table_name == "orders")
+                .map(|k| k.column_name.as_str())
+                .collect();
+            order_key.sort();
+            assert_eq!(order_key, ["customer_id", "number"]);
+            
+            let shipment = schema.foreign_keys.iter().find(|f| f.parent_table == "shipment").unwrap();
+            assert_eq!(shipment.referenced_table, "orders");
+            assert_eq!(shipment.delete_referential_action, "CASCADE");
+            let pairs: Vec<(&str, &str)> = shipment.columns.iter()
+                .map(|c| (c.parent_column.as_str(), c.referenced_column.as_str()))
+                .collect();
+            assert_eq!(pairs, [("customer_id", "customer_id"), ("order_number", "number")]);
+            
+            // A bare REFERENCES resolves to the parent's primary key
+            let orders = schema.foreign_keys.iter().find(|f| f.parent_table == "orders").unwrap();
+            assert_eq!(orders.columns[0].referenced_column, "id");
+            
+            let partial = schema.indexes.iter().find(|i| i.name == "ix_orders_big").unwrap();
+            assert_eq!(partial.filter_definition.as_deref(), Some("total > 100"));
+            assert!(partial.key_columns[0].is_descending);
+            assert!(schema.indexes.iter().any(|i| i.table_name == "customer" && i.is_unique_constraint));
+            assert!(schema.indexes.iter().any(|i| i.table_name == "orders" && i.is_primary_key));
+            
+            let trigger = schema.routines.iter().find(|r| r.name == "trg_orders").unwrap();
+            assert_eq!(trigger.routine_kind, RoutineKind::Trigger);
+            assert_eq!(trigger.parent_table.as_deref(), Some("orders"));
+        }
+    }
+}
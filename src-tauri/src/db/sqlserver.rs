@@ -1,69 +1,432 @@
-use tiberius::{Client, Config, AuthMethod};
+use std::collections::{BTreeMap, HashMap};
+use std::time::Duration;
+use tiberius::{Client, Config, AuthMethod, EncryptionLevel, SqlBrowser};
 use tokio::net::TcpStream;
 use tokio_util::compat::TokioAsyncWriteCompatExt;
-use crate::config::Connection;
-use crate::commands::schema::{Table, Column, ForeignKey, PrimaryKey, SchemaData};
+use crate::config::{Connection, EncryptionMode};
+use crate::commands::schema::{
+    Table, ObjectKind, Column, ForeignKey, ForeignKeyColumn, PrimaryKey, Index, IndexColumn,
+    CheckConstraint, DefaultConstraint, Routine, RoutineKind, RoutineParameter, ParameterDirection,
+};
+use super::{
+    ConnectError, ConnectErrorKind, ServerProbe, PropertyOwner, ObjectProperties, ObjectVersion, SchemaSource,
+    ssh, trusted_certificate_path,
+};
 
-/// Connect to SQL Server using the provided connection config and password
-pub async fn connect(connection: &Connection, password: Option<&str>) -> Result<Client<tokio_util::compat::Compat<TcpStream>>, String> {
+/// Classify an error returned by tiberius during the TDS handshake
+fn classify_tiberius_error(error: &tiberius::error::Error) -> ConnectErrorKind {
+    use tiberius::error::{Error, IoErrorKind};
+    
+    match error {
+        // 18456: login failed, 18452: untrusted domain, 18486-18488: locked/expired/must change password
+        Error::Server(e) if matches!(e.code(), 18456 | 18452 | 18486 | 18487 | 18488) => ConnectErrorKind::LoginFailed,
+        // 4060: cannot open database requested by the login
+        Error::Server(e) if e.code() == 4060 => ConnectErrorKind::DatabaseNotFound,
+        Error::Tls(_) => ConnectErrorKind::Tls,
+        Error::Io { kind: IoErrorKind::ConnectionRefused, .. } => ConnectErrorKind::TcpRefused,
+        Error::Io { .. } => ConnectErrorKind::Network,
+        _ => ConnectErrorKind::Other,
+    }
+}
+
+/// Default TCP port of a SQL Server default instance
+const DEFAULT_PORT: u16 = 1433;
+
+/// Where to reach a SQL Server, as parsed from `Connection::server`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerAddress {
+    pub host: String,
+    pub instance: Option<String>,
+    pub port: Option<u16>,
+}
+
+/// Parse a server string in any of the forms SQL Server tools accept:
+/// `host`, `host\instance`, `host,port`, `host\instance,port`, optionally prefixed with `tcp:`.
+/// `.` and `(local)` are treated as `localhost`.
+pub fn parse_server_address(server: &str) -> Result<ServerAddress, String> {
+    let mut rest = server.trim();
+    
+    if let Some((protocol, remainder)) = rest.split_once(':') {
+        // Only protocol prefixes are stripped; other colons (IPv6 literals) are left alone
+        if protocol.eq_ignore_ascii_case("tcp") {
+            rest = remainder;
+        } else if protocol.eq_ignore_ascii_case("np") || protocol.eq_ignore_ascii_case("lpc") {
+            return Err(format!("Protocol '{}:' is not supported, only TCP connections are", protocol));
+        }
+    }
+    
+    let (rest, port) = match rest.rsplit_once(',') {
+        Some((rest, port)) => {
+            let port = port.trim().parse::<u16>()
+                .map_err(|_| format!("Invalid port '{}' in server '{}'", port.trim(), server))?;
+            (rest, Some(port))
+        }
+        None => (rest, None),
+    };
+    
+    let (host, instance) = match rest.split_once('\\') {
+        Some((host, instance)) if !instance.trim().is_empty() => (host, Some(instance.trim().to_string())),
+        Some((host, _)) => (host, None),
+        None => (rest, None),
+    };
+    
+    let host = match host.trim() {
+        "" => return Err("Server cannot be empty".to_string()),
+        "." | "(local)" => "localhost".to_string(),
+        h => h.to_string(),
+    };
+    
+    Ok(ServerAddress { host, instance, port })
+}
+
+/// Apply the connection's encryption mode and certificate trust settings
+fn apply_tls_policy(config: &mut Config, connection: &Connection) -> Result<(), String> {
+    config.encryption(match connection.encryption {
+        EncryptionMode::Off => EncryptionLevel::Off,
+        EncryptionMode::On => EncryptionLevel::On,
+        EncryptionMode::Required => EncryptionLevel::Required,
+        // tiberius only speaks TDS 7.x, where the TLS handshake follows prelogin
+        EncryptionMode::Strict => {
+            return Err("Strict encryption (TDS 8.0) is not supported for SQL Server connections; use Required".to_string());
+        }
+    });
+    
+    // tiberius validates the certificate itself and takes no custom verifier, so a certificate
+    // can only be trusted as a CA, never matched exactly
+    if connection.pinned_certificate_path.is_some() {
+        return Err("Pinned certificates are not supported for SQL Server connections; use a CA certificate instead".to_string());
+    }
+    
+    let certificate_path = trusted_certificate_path(connection)?;
+    if connection.trust_server_certificate {
+        config.trust_cert();
+    } else if let Some(path) = certificate_path {
+        // The CA becomes the only trust anchor; a self-signed server certificate can be its own CA
+        config.trust_cert_ca(path.display());
+    }
+    
+    Ok(())
+}
+
+/// How long a token command may run, e.g. while `az` refreshes its session
+const TOKEN_COMMAND_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Take the token out of `az account get-access-token` JSON, or the trimmed text as is
+fn parse_access_token(output: &str) -> Result<String, String> {
+    let output = output.trim();
+    let token = if output.starts_with('{') {
+        let json: serde_json::Value = serde_json::from_str(output)
+            .map_err(|e| format!("Failed to parse access token output: {}", e))?;
+        json.get("accessToken")
+            .or_else(|| json.get("access_token"))
+            .and_then(|t| t.as_str())
+            .ok_or_else(|| "Access token output has no accessToken field".to_string())?
+            .to_string()
+    } else {
+        output.to_string()
+    };
+    
+    if token.is_empty() {
+        return Err("Access token is empty".to_string());
+    }
+    Ok(token)
+}
+
+/// Read the Azure AD access token from the connection's token file or command
+async fn access_token(connection: &Connection) -> Result<String, ConnectError> {
+    let configuration = |message: String| ConnectError::new(ConnectErrorKind::Configuration, message);
+    let non_empty = |value: &Option<String>| value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+    
+    let output = match (non_empty(&connection.access_token_file), non_empty(&connection.access_token_command)) {
+        (Some(_), Some(_)) => {
+            return Err(configuration("Set either an access token file or a token command, not both".to_string()));
+        }
+        (Some(path), None) => tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| configuration(format!("Failed to read access token file {}: {}", path, e)))?,
+        (None, Some(command)) => {
+            println!("Running access token command: {}", command);
+            
+            #[cfg(windows)]
+            let mut process = tokio::process::Command::new("cmd");
+            #[cfg(windows)]
+            process.arg("/C").arg(&command);
+            #[cfg(not(windows))]
+            let mut process = tokio::process::Command::new("sh");
+            #[cfg(not(windows))]
+            process.arg("-c").arg(&command);
+            process.kill_on_drop(true);
+            
+            let output = tokio::time::timeout(TOKEN_COMMAND_TIMEOUT, process.output())
+                .await
+                .map_err(|_| configuration(format!("Access token command timed out after {} seconds", TOKEN_COMMAND_TIMEOUT.as_secs())))?
+                .map_err(|e| configuration(format!("Failed to run access token command: {}", e)))?;
+            if !output.status.success() {
+                return Err(configuration(format!(
+                    "Access token command failed ({}): {}",
+                    output.status,
+                    String::from_utf8_lossy(&output.stderr).trim(),
+                )));
+            }
+            String::from_utf8(output.stdout)
+                .map_err(|e| configuration(format!("Access token command printed invalid UTF-8: {}", e)))?
+        }
+        (None, None) => {
+            return Err(configuration("An access token file or token command is required for Azure AD authentication".to_string()));
+        }
+    };
+    
+    parse_access_token(&output).map_err(configuration)
+}
+
+/// Connect to SQL Server, reporting failures with a `ConnectErrorKind`
+pub async fn connect(connection: &Connection, password: Option<&str>) -> Result<Client<tokio_util::compat::Compat<TcpStream>>, ConnectError> {
     let mut config = Config::new();
     
-    // Parse server - handle instance names like "localhost\SQLEXPRESS"
-    let server_parts: Vec<&str> = connection.server.split('\\').collect();
-    let host = server_parts[0];
+    let address = parse_server_address(&connection.server)
+        .map_err(|e| ConnectError::new(ConnectErrorKind::Configuration, e))?;
+    // An explicit port on the connection wins over one embedded in the server string
+    let port = connection.port.or(address.port);
     
-    config.host(host);
-    config.port(1433); // Default SQL Server port
+    config.host(&address.host);
+    match (port, &address.instance) {
+        (Some(port), _) => config.port(port),
+        // Without a port, named instances are resolved through the SQL Server Browser
+        (None, Some(instance)) => config.instance_name(instance),
+        (None, None) => config.port(DEFAULT_PORT),
+    }
     config.database(&connection.database);
-    config.trust_cert(); // For development - remove in production
+    config.readonly(connection.read_only_intent);
+    apply_tls_policy(&mut config, connection)
+        .map_err(|e| ConnectError::new(ConnectErrorKind::Configuration, e))?;
     
     // Set authentication
-    if connection.auth_type == "sql" {
-        if let Some(ref username) = connection.username {
+    match connection.auth_type.as_str() {
+        "sql" => {
+            let Some(ref username) = connection.username else {
+                return Err(ConnectError::new(ConnectErrorKind::Configuration, "Username is required for SQL authentication"));
+            };
             let pwd = password.unwrap_or("");
             println!("Authenticating as SQL user: {}", username);
             config.authentication(AuthMethod::sql_server(username, pwd));
-        } else {
-            return Err("Username is required for SQL authentication".to_string());
         }
-    } else {
+        "azureToken" => {
+            let token = access_token(connection).await?;
+            println!("Authenticating with an Azure AD access token");
+            config.authentication(AuthMethod::aad_token(token));
+        }
         // Windows authentication - use integrated security via SSPI
         #[cfg(windows)]
-        {
+        "windows" | "kerberos" => {
+            config.authentication(AuthMethod::Integrated);
+        }
+        // Kerberos via GSSAPI, using the ticket cache filled by `kinit`
+        #[cfg(target_os = "linux")]
+        "kerberos" => {
+            println!("Authenticating with the Kerberos ticket cache");
             config.authentication(AuthMethod::Integrated);
         }
         #[cfg(not(windows))]
-        {
-            return Err("Windows authentication is only available on Windows".to_string());
+        "windows" => {
+            return Err(ConnectError::new(
+                ConnectErrorKind::Configuration,
+                "Windows authentication is only available on Windows; use Kerberos authentication instead",
+            ));
+        }
+        #[cfg(not(any(windows, target_os = "linux")))]
+        "kerberos" => {
+            return Err(ConnectError::new(ConnectErrorKind::Configuration, "Kerberos authentication is only available on Windows and Linux"));
+        }
+        other => {
+            return Err(ConnectError::new(ConnectErrorKind::Configuration, format!("Unsupported authentication type '{}'", other)));
         }
     }
     
-    println!("Connecting to {}:{}", host, 1433);
+    println!("Connecting to {}", config.get_addr());
     
     // Connect
-    let tcp = TcpStream::connect(config.get_addr())
-        .await
-        .map_err(|e| format!("Failed to connect to SQL Server: {}", e))?;
+    let tcp = if let Some(tunnel) = &connection.ssh_tunnel {
+        // Named instances are looked up through the SQL Server Browser over UDP, which SSH can't forward
+        if port.is_none() && address.instance.is_some() {
+            return Err(ConnectError::new(
+                ConnectErrorKind::Configuration,
+                "Named instances need an explicit port when connecting through an SSH tunnel",
+            ));
+        }
+        ssh::open_forward(tunnel, &address.host, port.unwrap_or(DEFAULT_PORT)).await?
+    } else {
+        // Resolve the host first so DNS failures can be told apart from refused connections
+        let addrs: Vec<_> = tokio::net::lookup_host(config.get_addr())
+            .await
+            .map_err(|e| ConnectError::new(ConnectErrorKind::Dns, format!("Failed to resolve server '{}': {}", address.host, e)))?
+            .collect();
+        
+        if port.is_none() && address.instance.is_some() {
+            TcpStream::connect_named(&config)
+                .await
+                .map_err(|e| ConnectError::new(
+                    classify_tiberius_error(&e),
+                    format!("Failed to reach SQL Server instance '{}': {}", connection.server, e),
+                ))?
+        } else {
+            TcpStream::connect(&addrs[..])
+                .await
+                .map_err(|e| {
+                    let kind = match e.kind() {
+                        std::io::ErrorKind::ConnectionRefused => ConnectErrorKind::TcpRefused,
+                        _ => ConnectErrorKind::Network,
+                    };
+                    ConnectError::new(kind, format!("Failed to connect to SQL Server: {}", e))
+                })?
+        }
+    };
     
     tcp.set_nodelay(true).ok();
     
     let client = Client::connect(config, tcp.compat_write())
         .await
-        .map_err(|e| format!("Failed to authenticate with SQL Server: {}", e))?;
+        .map_err(|e| ConnectError::new(classify_tiberius_error(&e), format!("Failed to authenticate with SQL Server: {}", e)))?;
     
     println!("Connected successfully!");
     Ok(client)
 }
 
-/// Extract all tables from the database
-pub async fn get_tables(client: &mut Client<tokio_util::compat::Compat<TcpStream>>) -> Result<Vec<Table>, String> {
+/// Run a trivial query that reports the server version, current database and login
+pub async fn probe_server(client: &mut Client<tokio_util::compat::Compat<TcpStream>>) -> Result<ServerProbe, String> {
     let query = r#"
-        SELECT TABLE_SCHEMA, TABLE_NAME 
-        FROM INFORMATION_SCHEMA.TABLES 
-        WHERE TABLE_TYPE = 'BASE TABLE'
-        ORDER BY TABLE_SCHEMA, TABLE_NAME
+        SELECT 
+            CAST(@@VERSION AS NVARCHAR(4000)) AS server_version,
+            DB_NAME() AS database_name,
+            SUSER_SNAME() AS login_name
     "#;
     
+    let row = client.query(query, &[])
+        .await
+        .map_err(|e| format!("Failed to query server info: {}", e))?
+        .into_row()
+        .await
+        .map_err(|e| format!("Failed to fetch server info: {}", e))?
+        .ok_or_else(|| "Server info query returned no rows".to_string())?;
+    
+    let server_version: &str = row.get(0).unwrap_or("");
+    let database_name: &str = row.get(1).unwrap_or("");
+    let login_name: &str = row.get(2).unwrap_or("");
+    
+    Ok(ServerProbe {
+        // @@VERSION spans several lines; the first one names the product and build
+        server_version: server_version.lines().next().unwrap_or("").trim().to_string(),
+        database_name: database_name.to_string(),
+        login_name: login_name.to_string(),
+    })
+}
+
+/// Session temp table holding the object ids an incremental refresh re-reads
+const SCOPE_TABLE: &str = "#glance_scope";
+
+/// Join that limits a catalog query to the objects in scope, or nothing when reading everything
+fn scope_join(scoped: bool, object_id: &str) -> String {
+    if scoped {
+        format!("INNER JOIN {} scope ON scope.object_id = {}", SCOPE_TABLE, object_id)
+    } else {
+        String::new()
+    }
+}
+
+/// Fill the scope table with the objects the following catalog queries are limited to
+pub async fn set_scope(client: &mut Client<tokio_util::compat::Compat<TcpStream>>, object_ids: &[i64]) -> Result<(), String> {
+    // A plain batch rather than sp_executesql, so the temp table outlives the statement
+    let create = format!(
+        "IF OBJECT_ID('tempdb..{table}') IS NOT NULL DROP TABLE {table}; CREATE TABLE {table} (object_id INT PRIMARY KEY)",
+        table = SCOPE_TABLE,
+    );
+    client.simple_query(create)
+        .await
+        .map_err(|e| format!("Failed to create scope table: {}", e))?
+        .into_results()
+        .await
+        .map_err(|e| format!("Failed to create scope table: {}", e))?;
+    
+    // A VALUES list takes at most 1000 rows
+    for chunk in object_ids.chunks(1000) {
+        let values: Vec<String> = chunk.iter().map(|id| format!("({})", id)).collect();
+        client.simple_query(format!("INSERT INTO {} (object_id) VALUES {}", SCOPE_TABLE, values.join(", ")))
+            .await
+            .map_err(|e| format!("Failed to fill scope table: {}", e))?
+            .into_results()
+            .await
+            .map_err(|e| format!("Failed to fill scope table: {}", e))?;
+    }
+    
+    Ok(())
+}
+
+/// Creation and modification stamps of every table-like object and routine. A table's stamp
+/// also covers its keys, constraints and triggers, which are objects of their own.
+pub async fn get_object_versions(client: &mut Client<tokio_util::compat::Compat<TcpStream>>) -> Result<Vec<ObjectVersion>, String> {
+    let query = r#"
+        SELECT 
+            o.object_id,
+            SCHEMA_NAME(COALESCE(tt.schema_id, o.schema_id)) AS object_schema,
+            COALESCE(tt.name, o.name) AS object_name,
+            CONVERT(VARCHAR(23), o.create_date, 126) + '/' + CONVERT(VARCHAR(23),
+                CASE WHEN child.modify_date > o.modify_date THEN child.modify_date ELSE o.modify_date END, 126) AS stamp
+        FROM sys.objects o
+        -- User-defined table types are hidden TT objects named after an internal id
+        LEFT JOIN sys.table_types tt ON tt.type_table_object_id = o.object_id
+        OUTER APPLY (
+            SELECT MAX(c.modify_date) AS modify_date
+            FROM sys.objects c
+            WHERE c.parent_object_id = o.object_id
+        ) child
+        WHERE o.type IN ('U', 'V', 'SN', 'ET', 'TT', 'P', 'PC', 'FN', 'FS', 'IF', 'TF', 'FT', 'TR', 'TA')
+            AND (o.is_ms_shipped = 0 OR tt.is_user_defined = 1)
+    "#;
+    
+    let stream = client.query(query, &[])
+        .await
+        .map_err(|e| format!("Failed to query object versions: {}", e))?;
+    
+    let rows = stream.into_first_result()
+        .await
+        .map_err(|e| format!("Failed to fetch object versions: {}", e))?;
+    
+    Ok(rows.iter()
+        .map(|row| ObjectVersion {
+            object_id: row.get::<i32, _>(0).unwrap_or(0) as i64,
+            schema: row.get::<&str, _>(1).unwrap_or("dbo").to_string(),
+            name: row.get::<&str, _>(2).unwrap_or("").to_string(),
+            stamp: row.get::<&str, _>(3).unwrap_or("").to_string(),
+        })
+        .collect())
+}
+
+/// Extract all tables, views, synonyms and user-defined table types from the database
+pub async fn get_tables(client: &mut Client<tokio_util::compat::Compat<TcpStream>>, scoped: bool) -> Result<Vec<Table>, String> {
+    let query = format!(r#"
+        SELECT 
+            SCHEMA_NAME(o.schema_id) AS table_schema,
+            o.name AS table_name,
+            o.type AS object_type,
+            m.definition,
+            syn.base_object_name
+        FROM sys.objects o
+        {}
+        LEFT JOIN sys.sql_modules m ON o.object_id = m.object_id
+        LEFT JOIN sys.synonyms syn ON o.object_id = syn.object_id
+        WHERE o.type IN ('U', 'V', 'SN', 'ET') AND o.is_ms_shipped = 0
+        UNION ALL
+        SELECT 
+            SCHEMA_NAME(tt.schema_id),
+            tt.name,
+            'TT',
+            NULL,
+            NULL
+        FROM sys.table_types tt
+        {}
+        WHERE tt.is_user_defined = 1
+        ORDER BY table_schema, table_name
+    "#, scope_join(scoped, "o.object_id"), scope_join(scoped, "tt.type_table_object_id"));
+    
     let stream = client.query(query, &[])
         .await
         .map_err(|e| format!("Failed to query tables: {}", e))?;
@@ -76,11 +439,26 @@ pub async fn get_tables(client: &mut Client<tokio_util::compat::Compat<TcpStream
     for row in rows {
         let schema: &str = row.get(0).unwrap_or("dbo");
         let name: &str = row.get(1).unwrap_or("");
+        let object_type: &str = row.get(2).unwrap_or("U");
+        let definition: Option<&str> = row.get(3);
+        let synonym_target: Option<&str> = row.get(4);
+        
+        let object_kind = match object_type.trim() {
+            "V" => ObjectKind::View,
+            "SN" => ObjectKind::Synonym,
+            "TT" => ObjectKind::TableType,
+            "ET" => ObjectKind::ExternalTable,
+            _ => ObjectKind::BaseTable,
+        };
         
         tables.push(Table {
             schema: schema.to_string(),
             name: name.to_string(),
             description: None,
+            object_kind,
+            definition: definition.map(|d| d.to_string()),
+            synonym_target: synonym_target.map(|t| t.to_string()),
+            extended_properties: BTreeMap::new(),
         });
     }
     
@@ -88,18 +466,48 @@ pub async fn get_tables(client: &mut Client<tokio_util::compat::Compat<TcpStream
 }
 
 /// Extract all columns from the database
-pub async fn get_columns(client: &mut Client<tokio_util::compat::Compat<TcpStream>>) -> Result<Vec<Column>, String> {
-    let query = r#"
+pub async fn get_columns(client: &mut Client<tokio_util::compat::Compat<TcpStream>>, scoped: bool) -> Result<Vec<Column>, String> {
+    let query = format!(r#"
+        WITH owners AS (
+            SELECT so.object_id, so.schema_id, so.name
+            FROM sys.objects so
+            {}
+            WHERE so.type IN ('U', 'V', 'ET') AND so.is_ms_shipped = 0
+            UNION ALL
+            -- Columns of user-defined table types hang off a hidden TT object
+            SELECT tt.type_table_object_id, tt.schema_id, tt.name
+            FROM sys.table_types tt
+            {}
+            WHERE tt.is_user_defined = 1
+        )
         SELECT 
-            c.TABLE_SCHEMA,
-            c.TABLE_NAME,
-            c.COLUMN_NAME,
-            c.DATA_TYPE,
-            c.IS_NULLABLE,
-            c.ORDINAL_POSITION
-        FROM INFORMATION_SCHEMA.COLUMNS c
-        ORDER BY c.TABLE_SCHEMA, c.TABLE_NAME, c.ORDINAL_POSITION
-    "#;
+            SCHEMA_NAME(o.schema_id) AS table_schema,
+            o.name AS table_name,
+            c.name AS column_name,
+            -- Alias types report their base type, like INFORMATION_SCHEMA does
+            CASE WHEN t.is_user_defined = 1 AND t.is_assembly_type = 0 AND t.is_table_type = 0
+                THEN TYPE_NAME(t.system_type_id) ELSE t.name END AS data_type,
+            c.is_nullable,
+            c.column_id,
+            c.max_length,
+            c.precision,
+            c.scale,
+            c.collation_name,
+            dc.definition AS default_value,
+            TRY_CAST(ic.seed_value AS BIGINT) AS identity_seed,
+            TRY_CAST(ic.increment_value AS BIGINT) AS identity_increment,
+            cc.definition AS computed_definition,
+            c.is_sparse,
+            c.is_rowguidcol,
+            c.is_filestream
+        FROM owners o
+        INNER JOIN sys.columns c ON o.object_id = c.object_id
+        INNER JOIN sys.types t ON c.user_type_id = t.user_type_id
+        LEFT JOIN sys.default_constraints dc ON c.default_object_id = dc.object_id
+        LEFT JOIN sys.identity_columns ic ON c.object_id = ic.object_id AND c.column_id = ic.column_id
+        LEFT JOIN sys.computed_columns cc ON c.object_id = cc.object_id AND c.column_id = cc.column_id
+        ORDER BY 1, 2, 6
+    "#, scope_join(scoped, "so.object_id"), scope_join(scoped, "tt.type_table_object_id"));
     
     let stream = client.query(query, &[])
         .await
@@ -115,19 +523,42 @@ pub async fn get_columns(client: &mut Client<tokio_util::compat::Compat<TcpStrea
         let table_name: &str = row.get(1).unwrap_or("");
         let name: &str = row.get(2).unwrap_or("");
         let data_type: &str = row.get(3).unwrap_or("");
-        let is_nullable: &str = row.get(4).unwrap_or("YES");
+        let is_nullable: bool = row.get(4).unwrap_or(true);
         let ordinal: i32 = row.get(5).unwrap_or(0);
+        let max_length: i16 = row.get(6).unwrap_or(0);
+        let precision: u8 = row.get(7).unwrap_or(0);
+        let scale: u8 = row.get(8).unwrap_or(0);
+        let collation: Option<&str> = row.get(9);
+        let default_value: Option<&str> = row.get(10);
+        let identity_seed: Option<i64> = row.get(11);
+        let identity_increment: Option<i64> = row.get(12);
+        let computed_definition: Option<&str> = row.get(13);
+        
+        let (length, precision, scale) = type_facets(data_type, max_length, precision, scale);
         
         columns.push(Column {
             table_schema: table_schema.to_string(),
             table_name: table_name.to_string(),
             name: name.to_string(),
             data_type: data_type.to_string(),
-            is_nullable: is_nullable == "YES",
+            full_type: format_type(data_type, length, precision, scale),
+            is_nullable,
             is_primary_key: false, // Will be updated later
             is_foreign_key: false, // Will be updated later
             ordinal_position: ordinal,
             description: None,
+            extended_properties: BTreeMap::new(),
+            max_length: length,
+            precision,
+            scale,
+            collation: collation.map(|c| c.to_string()),
+            default_value: default_value.map(|d| d.to_string()),
+            identity_seed,
+            identity_increment,
+            computed_definition: computed_definition.map(|d| d.to_string()),
+            is_sparse: row.get(14).unwrap_or(false),
+            is_rowguid: row.get(15).unwrap_or(false),
+            is_filestream: row.get(16).unwrap_or(false),
         });
     }
     
@@ -135,17 +566,19 @@ pub async fn get_columns(client: &mut Client<tokio_util::compat::Compat<TcpStrea
 }
 
 /// Extract primary keys
-pub async fn get_primary_keys(client: &mut Client<tokio_util::compat::Compat<TcpStream>>) -> Result<Vec<PrimaryKey>, String> {
-    let query = r#"
+pub async fn get_primary_keys(client: &mut Client<tokio_util::compat::Compat<TcpStream>>, scoped: bool) -> Result<Vec<PrimaryKey>, String> {
+    let query = format!(r#"
         SELECT 
+            SCHEMA_NAME(t.schema_id) AS table_schema,
             t.name AS table_name,
             c.name AS column_name
         FROM sys.indexes i
         INNER JOIN sys.index_columns ic ON i.object_id = ic.object_id AND i.index_id = ic.index_id
         INNER JOIN sys.columns c ON ic.object_id = c.object_id AND ic.column_id = c.column_id
         INNER JOIN sys.tables t ON i.object_id = t.object_id
+        {}
         WHERE i.is_primary_key = 1
-    "#;
+    "#, scope_join(scoped, "i.object_id"));
     
     let stream = client.query(query, &[])
         .await
@@ -157,10 +590,12 @@ pub async fn get_primary_keys(client: &mut Client<tokio_util::compat::Compat<Tcp
     
     let mut pks = Vec::new();
     for row in rows {
-        let table_name: &str = row.get(0).unwrap_or("");
-        let column_name: &str = row.get(1).unwrap_or("");
+        let table_schema: &str = row.get(0).unwrap_or("dbo");
+        let table_name: &str = row.get(1).unwrap_or("");
+        let column_name: &str = row.get(2).unwrap_or("");
         
         pks.push(PrimaryKey {
+            table_schema: table_schema.to_string(),
             table_name: table_name.to_string(),
             column_name: column_name.to_string(),
         });
@@ -169,14 +604,20 @@ pub async fn get_primary_keys(client: &mut Client<tokio_util::compat::Compat<Tcp
     Ok(pks)
 }
 
-/// Extract foreign keys
-pub async fn get_foreign_keys(client: &mut Client<tokio_util::compat::Compat<TcpStream>>) -> Result<Vec<ForeignKey>, String> {
-    let query = r#"
+/// Extract foreign keys, with the column pairs of each constraint in key order
+pub async fn get_foreign_keys(client: &mut Client<tokio_util::compat::Compat<TcpStream>>, scoped: bool) -> Result<Vec<ForeignKey>, String> {
+    let query = format!(r#"
         SELECT 
             fk.name AS constraint_name,
+            SCHEMA_NAME(tp.schema_id) AS parent_schema,
             tp.name AS parent_table,
-            cp.name AS parent_column,
+            SCHEMA_NAME(tr.schema_id) AS referenced_schema,
             tr.name AS referenced_table,
+            fk.delete_referential_action_desc,
+            fk.update_referential_action_desc,
+            fk.is_disabled,
+            fk.is_not_trusted,
+            cp.name AS parent_column,
             cr.name AS referenced_column
         FROM sys.foreign_keys fk
         INNER JOIN sys.foreign_key_columns fkc ON fkc.constraint_object_id = fk.object_id
@@ -184,7 +625,9 @@ pub async fn get_foreign_keys(client: &mut Client<tokio_util::compat::Compat<Tcp
         INNER JOIN sys.columns cp ON fkc.parent_object_id = cp.object_id AND fkc.parent_column_id = cp.column_id
         INNER JOIN sys.tables tr ON fkc.referenced_object_id = tr.object_id
         INNER JOIN sys.columns cr ON fkc.referenced_object_id = cr.object_id AND fkc.referenced_column_id = cr.column_id
-    "#;
+        {}
+        ORDER BY fk.object_id, fkc.constraint_column_id
+    "#, scope_join(scoped, "fk.parent_object_id"));
     
     let stream = client.query(query, &[])
         .await
@@ -194,63 +637,707 @@ pub async fn get_foreign_keys(client: &mut Client<tokio_util::compat::Compat<Tcp
         .await
         .map_err(|e| format!("Failed to fetch foreign keys: {}", e))?;
     
-    let mut fks = Vec::new();
+    let mut fks: Vec<ForeignKey> = Vec::new();
     for row in rows {
         let constraint_name: &str = row.get(0).unwrap_or("");
-        let parent_table: &str = row.get(1).unwrap_or("");
-        let parent_column: &str = row.get(2).unwrap_or("");
-        let referenced_table: &str = row.get(3).unwrap_or("");
-        let referenced_column: &str = row.get(4).unwrap_or("");
+        let parent_schema: &str = row.get(1).unwrap_or("dbo");
+        let parent_column: &str = row.get(9).unwrap_or("");
+        let referenced_column: &str = row.get(10).unwrap_or("");
+        
+        let column = ForeignKeyColumn {
+            parent_column: parent_column.to_string(),
+            referenced_column: referenced_column.to_string(),
+        };
+        
+        // Rows are ordered by constraint, so further columns of a composite key follow directly
+        if let Some(fk) = fks.last_mut() {
+            if fk.constraint_name == constraint_name && fk.parent_schema == parent_schema {
+                fk.columns.push(column);
+                continue;
+            }
+        }
+        
+        let parent_table: &str = row.get(2).unwrap_or("");
+        let referenced_schema: &str = row.get(3).unwrap_or("dbo");
+        let referenced_table: &str = row.get(4).unwrap_or("");
+        let delete_action: &str = row.get(5).unwrap_or("NO_ACTION");
+        let update_action: &str = row.get(6).unwrap_or("NO_ACTION");
+        let is_disabled: bool = row.get(7).unwrap_or(false);
+        let is_not_trusted: bool = row.get(8).unwrap_or(false);
         
         fks.push(ForeignKey {
             constraint_name: constraint_name.to_string(),
+            parent_schema: parent_schema.to_string(),
             parent_table: parent_table.to_string(),
-            parent_column: parent_column.to_string(),
+            referenced_schema: referenced_schema.to_string(),
             referenced_table: referenced_table.to_string(),
-            referenced_column: referenced_column.to_string(),
+            columns: vec![column],
+            delete_referential_action: delete_action.to_string(),
+            update_referential_action: update_action.to_string(),
+            is_disabled,
+            is_not_trusted,
         });
     }
     
     Ok(fks)
 }
 
-/// Extract complete schema from database
-pub async fn extract_schema(connection: &Connection, password: Option<&str>) -> Result<SchemaData, String> {
-    let mut client = connect(connection, password).await?;
+/// Extract indexes with their key and included columns
+pub async fn get_indexes(client: &mut Client<tokio_util::compat::Compat<TcpStream>>, scoped: bool) -> Result<Vec<Index>, String> {
+    let query = format!(r#"
+        SELECT 
+            o.object_id,
+            i.index_id,
+            SCHEMA_NAME(o.schema_id) AS table_schema,
+            o.name AS table_name,
+            i.name AS index_name,
+            i.type_desc,
+            i.is_unique,
+            i.is_primary_key,
+            i.is_unique_constraint,
+            i.filter_definition,
+            c.name AS column_name,
+            ic.is_descending_key,
+            ic.is_included_column
+        FROM sys.indexes i
+        INNER JOIN sys.objects o ON i.object_id = o.object_id
+        INNER JOIN sys.index_columns ic ON i.object_id = ic.object_id AND i.index_id = ic.index_id
+        INNER JOIN sys.columns c ON ic.object_id = c.object_id AND ic.column_id = c.column_id
+        {}
+        WHERE i.type > 0 AND o.type IN ('U', 'V') AND o.is_ms_shipped = 0
+        ORDER BY o.object_id, i.index_id, ic.is_included_column, ic.key_ordinal, ic.index_column_id
+    "#, scope_join(scoped, "o.object_id"));
+    
+    let stream = client.query(query, &[])
+        .await
+        .map_err(|e| format!("Failed to query indexes: {}", e))?;
+    
+    let rows = stream.into_first_result()
+        .await
+        .map_err(|e| format!("Failed to fetch indexes: {}", e))?;
+    
+    let mut indexes: Vec<Index> = Vec::new();
+    let mut current: Option<(i32, i32)> = None;
+    for row in rows {
+        let object_id: i32 = row.get(0).unwrap_or(0);
+        let index_id: i32 = row.get(1).unwrap_or(0);
+        let column_name: &str = row.get(10).unwrap_or("");
+        let is_descending: bool = row.get(11).unwrap_or(false);
+        let is_included: bool = row.get(12).unwrap_or(false);
+        
+        // Rows are ordered by index, so a new (object, index) pair starts a new index
+        if current != Some((object_id, index_id)) {
+            current = Some((object_id, index_id));
+            
+            let table_schema: &str = row.get(2).unwrap_or("dbo");
+            let table_name: &str = row.get(3).unwrap_or("");
+            let index_name: &str = row.get(4).unwrap_or("");
+            let index_type: &str = row.get(5).unwrap_or("");
+            let filter_definition: Option<&str> = row.get(9);
+            
+            indexes.push(Index {
+                table_schema: table_schema.to_string(),
+                table_name: table_name.to_string(),
+                name: index_name.to_string(),
+                index_type: index_type.to_string(),
+                is_unique: row.get(6).unwrap_or(false),
+                is_primary_key: row.get(7).unwrap_or(false),
+                is_unique_constraint: row.get(8).unwrap_or(false),
+                key_columns: Vec::new(),
+                included_columns: Vec::new(),
+                filter_definition: filter_definition.map(|f| f.to_string()),
+            });
+        }
+        
+        if let Some(index) = indexes.last_mut() {
+            if is_included {
+                index.included_columns.push(column_name.to_string());
+            } else {
+                index.key_columns.push(IndexColumn {
+                    name: column_name.to_string(),
+                    is_descending,
+                });
+            }
+        }
+    }
+    
+    Ok(indexes)
+}
+
+/// Extract check constraints
+pub async fn get_check_constraints(client: &mut Client<tokio_util::compat::Compat<TcpStream>>, scoped: bool) -> Result<Vec<CheckConstraint>, String> {
+    let query = format!(r#"
+        SELECT 
+            SCHEMA_NAME(t.schema_id) AS table_schema,
+            t.name AS table_name,
+            cc.name AS constraint_name,
+            c.name AS column_name,
+            cc.definition,
+            cc.is_disabled,
+            cc.is_not_trusted
+        FROM sys.check_constraints cc
+        INNER JOIN sys.tables t ON cc.parent_object_id = t.object_id
+        LEFT JOIN sys.columns c ON cc.parent_object_id = c.object_id AND cc.parent_column_id = c.column_id
+        {}
+        ORDER BY table_schema, table_name, constraint_name
+    "#, scope_join(scoped, "cc.parent_object_id"));
+    
+    let stream = client.query(query, &[])
+        .await
+        .map_err(|e| format!("Failed to query check constraints: {}", e))?;
+    
+    let rows = stream.into_first_result()
+        .await
+        .map_err(|e| format!("Failed to fetch check constraints: {}", e))?;
+    
+    let mut constraints = Vec::new();
+    for row in rows {
+        let table_schema: &str = row.get(0).unwrap_or("dbo");
+        let table_name: &str = row.get(1).unwrap_or("");
+        let constraint_name: &str = row.get(2).unwrap_or("");
+        let column_name: Option<&str> = row.get(3);
+        let definition: &str = row.get(4).unwrap_or("");
+        
+        constraints.push(CheckConstraint {
+            table_schema: table_schema.to_string(),
+            table_name: table_name.to_string(),
+            constraint_name: constraint_name.to_string(),
+            column_name: column_name.map(|c| c.to_string()),
+            definition: definition.to_string(),
+            is_disabled: row.get(5).unwrap_or(false),
+            is_not_trusted: row.get(6).unwrap_or(false),
+        });
+    }
+    
+    Ok(constraints)
+}
+
+/// Extract default constraints
+pub async fn get_default_constraints(client: &mut Client<tokio_util::compat::Compat<TcpStream>>, scoped: bool) -> Result<Vec<DefaultConstraint>, String> {
+    let query = format!(r#"
+        SELECT 
+            SCHEMA_NAME(t.schema_id) AS table_schema,
+            t.name AS table_name,
+            c.name AS column_name,
+            dc.name AS constraint_name,
+            dc.definition
+        FROM sys.default_constraints dc
+        INNER JOIN sys.tables t ON dc.parent_object_id = t.object_id
+        INNER JOIN sys.columns c ON dc.parent_object_id = c.object_id AND dc.parent_column_id = c.column_id
+        {}
+        ORDER BY table_schema, table_name, column_name
+    "#, scope_join(scoped, "dc.parent_object_id"));
+    
+    let stream = client.query(query, &[])
+        .await
+        .map_err(|e| format!("Failed to query default constraints: {}", e))?;
+    
+    let rows = stream.into_first_result()
+        .await
+        .map_err(|e| format!("Failed to fetch default constraints: {}", e))?;
+    
+    let mut constraints = Vec::new();
+    for row in rows {
+        let table_schema: &str = row.get(0).unwrap_or("dbo");
+        let table_name: &str = row.get(1).unwrap_or("");
+        let column_name: &str = row.get(2).unwrap_or("");
+        let constraint_name: &str = row.get(3).unwrap_or("");
+        let definition: &str = row.get(4).unwrap_or("");
+        
+        constraints.push(DefaultConstraint {
+            table_schema: table_schema.to_string(),
+            table_name: table_name.to_string(),
+            column_name: column_name.to_string(),
+            constraint_name: constraint_name.to_string(),
+            definition: definition.to_string(),
+        });
+    }
+    
+    Ok(constraints)
+}
+
+/// Name of the extended property SSMS and most tools use for descriptions
+const DESCRIPTION_PROPERTY: &str = "MS_Description";
+
+/// Extract extended properties of tables, views, routines and their columns
+pub async fn get_extended_properties(client: &mut Client<tokio_util::compat::Compat<TcpStream>>) -> Result<HashMap<PropertyOwner, BTreeMap<String, String>>, String> {
+    let query = r#"
+        SELECT 
+            SCHEMA_NAME(o.schema_id) AS object_schema,
+            o.name AS object_name,
+            c.name AS column_name,
+            ep.name AS property_name,
+            CAST(ep.value AS NVARCHAR(4000)) AS property_value
+        FROM sys.extended_properties ep
+        INNER JOIN sys.objects o ON ep.major_id = o.object_id
+        LEFT JOIN sys.columns c ON ep.major_id = c.object_id AND ep.minor_id = c.column_id
+        WHERE ep.class = 1 AND (ep.minor_id = 0 OR c.column_id IS NOT NULL)
+    "#;
+    
+    let stream = client.query(query, &[])
+        .await
+        .map_err(|e| format!("Failed to query extended properties: {}", e))?;
+    
+    let rows = stream.into_first_result()
+        .await
+        .map_err(|e| format!("Failed to fetch extended properties: {}", e))?;
     
-    println!("Extracting tables...");
-    let tables = get_tables(&mut client).await?;
-    println!("Found {} tables", tables.len());
+    let mut properties: HashMap<PropertyOwner, BTreeMap<String, String>> = HashMap::new();
+    for row in rows {
+        let schema: &str = row.get(0).unwrap_or("dbo");
+        let object: &str = row.get(1).unwrap_or("");
+        let column: Option<&str> = row.get(2);
+        let name: &str = row.get(3).unwrap_or("");
+        let value: &str = row.get(4).unwrap_or("");
+        
+        properties
+            .entry((schema.to_string(), object.to_string(), column.map(|c| c.to_string())))
+            .or_default()
+            .insert(name.to_string(), value.to_string());
+    }
     
-    println!("Extracting columns...");
-    let mut columns = get_columns(&mut client).await?;
-    println!("Found {} columns", columns.len());
+    Ok(properties)
+}
+
+/// Split `sys.columns` size metadata into the declared length, precision and scale.
+/// Length is in characters for Unicode types and -1 means `max`; facets a type doesn't take are `None`.
+pub fn type_facets(type_name: &str, max_length: i16, precision: u8, scale: u8) -> (Option<i32>, Option<u8>, Option<u8>) {
+    match type_name.to_ascii_lowercase().as_str() {
+        "varchar" | "char" | "varbinary" | "binary" => (Some(max_length as i32), None, None),
+        // Unicode types store two bytes per character
+        "nvarchar" | "nchar" => match max_length {
+            -1 => (Some(-1), None, None),
+            n => (Some(n as i32 / 2), None, None),
+        },
+        "decimal" | "numeric" => (None, Some(precision), Some(scale)),
+        "datetime2" | "datetimeoffset" | "time" => (None, None, Some(scale)),
+        _ => (None, None, None),
+    }
+}
+
+/// Render a type with its declared facets, e.g. `varchar(max)` or `decimal(18,4)`
+pub fn format_type(type_name: &str, length: Option<i32>, precision: Option<u8>, scale: Option<u8>) -> String {
+    match (length, precision, scale) {
+        (Some(-1), _, _) => format!("{}(max)", type_name),
+        (Some(n), _, _) => format!("{}({})", type_name, n),
+        (None, Some(p), Some(s)) => format!("{}({},{})", type_name, p, s),
+        (None, None, Some(s)) => format!("{}({})", type_name, s),
+        _ => type_name.to_string(),
+    }
+}
+
+/// Render a SQL Server type with its length, precision or scale, e.g. `nvarchar(50)` or `decimal(18,4)`.
+/// `max_length` is in bytes as reported by `sys.columns`/`sys.parameters`, with -1 meaning `max`.
+pub fn render_type_name(type_name: &str, max_length: i16, precision: u8, scale: u8) -> String {
+    let (length, precision, scale) = type_facets(type_name, max_length, precision, scale);
+    format_type(type_name, length, precision, scale)
+}
+
+/// Extract stored procedures, functions and DML triggers with their definitions
+pub async fn get_routines(client: &mut Client<tokio_util::compat::Compat<TcpStream>>, scoped: bool) -> Result<Vec<Routine>, String> {
+    let query = format!(r#"
+        SELECT 
+            o.object_id,
+            SCHEMA_NAME(o.schema_id) AS routine_schema,
+            o.name AS routine_name,
+            o.type AS object_type,
+            m.definition,
+            OBJECT_SCHEMA_NAME(o.parent_object_id) AS parent_schema,
+            OBJECT_NAME(o.parent_object_id) AS parent_table
+        FROM sys.objects o
+        LEFT JOIN sys.sql_modules m ON o.object_id = m.object_id
+        {}
+        WHERE o.type IN ('P', 'PC', 'FN', 'FS', 'IF', 'TF', 'FT', 'TR', 'TA') AND o.is_ms_shipped = 0
+        ORDER BY routine_schema, routine_name
+    "#, scope_join(scoped, "o.object_id"));
     
-    println!("Extracting primary keys...");
-    let primary_keys = get_primary_keys(&mut client).await?;
+    let stream = client.query(query, &[])
+        .await
+        .map_err(|e| format!("Failed to query routines: {}", e))?;
     
-    println!("Extracting foreign keys...");
-    let foreign_keys = get_foreign_keys(&mut client).await?;
+    let rows = stream.into_first_result()
+        .await
+        .map_err(|e| format!("Failed to fetch routines: {}", e))?;
     
-    // Mark primary key and foreign key columns
-    for col in &mut columns {
-        // Check if this column is a primary key
-        col.is_primary_key = primary_keys.iter().any(|pk| {
-            pk.table_name.eq_ignore_ascii_case(&col.table_name) && 
-            pk.column_name.eq_ignore_ascii_case(&col.name)
+    let mut routines = Vec::new();
+    let mut positions: HashMap<i32, usize> = HashMap::new();
+    for row in rows {
+        let object_id: i32 = row.get(0).unwrap_or(0);
+        let schema: &str = row.get(1).unwrap_or("dbo");
+        let name: &str = row.get(2).unwrap_or("");
+        let object_type: &str = row.get(3).unwrap_or("P");
+        let definition: Option<&str> = row.get(4);
+        let parent_schema: Option<&str> = row.get(5);
+        let parent_table: Option<&str> = row.get(6);
+        
+        let routine_kind = match object_type.trim() {
+            "FN" | "FS" => RoutineKind::ScalarFunction,
+            "IF" | "TF" | "FT" => RoutineKind::TableValuedFunction,
+            "TR" | "TA" => RoutineKind::Trigger,
+            _ => RoutineKind::StoredProcedure,
+        };
+        
+        positions.insert(object_id, routines.len());
+        routines.push(Routine {
+            schema: schema.to_string(),
+            name: name.to_string(),
+            routine_kind,
+            description: None,
+            extended_properties: BTreeMap::new(),
+            parent_schema: parent_schema.map(|s| s.to_string()),
+            parent_table: parent_table.map(|t| t.to_string()),
+            definition: definition.map(|d| d.to_string()),
+            parameters: Vec::new(),
         });
+    }
+    
+    let query = format!(r#"
+        SELECT 
+            p.object_id,
+            p.name,
+            TYPE_NAME(p.user_type_id) AS type_name,
+            p.max_length,
+            p.precision,
+            p.scale,
+            p.is_output,
+            p.parameter_id
+        FROM sys.parameters p
+        INNER JOIN sys.objects o ON p.object_id = o.object_id
+        {}
+        WHERE o.type IN ('P', 'PC', 'FN', 'FS', 'IF', 'TF', 'FT') AND o.is_ms_shipped = 0
+        ORDER BY p.object_id, p.parameter_id
+    "#, scope_join(scoped, "o.object_id"));
+    
+    let stream = client.query(query, &[])
+        .await
+        .map_err(|e| format!("Failed to query routine parameters: {}", e))?;
+    
+    let rows = stream.into_first_result()
+        .await
+        .map_err(|e| format!("Failed to fetch routine parameters: {}", e))?;
+    
+    for row in rows {
+        let object_id: i32 = row.get(0).unwrap_or(0);
+        let Some(&i) = positions.get(&object_id) else {
+            continue;
+        };
+        
+        let name: &str = row.get(1).unwrap_or("");
+        let type_name: &str = row.get(2).unwrap_or("");
+        let max_length: i16 = row.get(3).unwrap_or(0);
+        let precision: u8 = row.get(4).unwrap_or(0);
+        let scale: u8 = row.get(5).unwrap_or(0);
+        let is_output: bool = row.get(6).unwrap_or(false);
+        let parameter_id: i32 = row.get(7).unwrap_or(0);
         
-        // Check if this column is a foreign key
-        col.is_foreign_key = foreign_keys.iter().any(|fk| {
-            fk.parent_table.eq_ignore_ascii_case(&col.table_name) && 
-            fk.parent_column.eq_ignore_ascii_case(&col.name)
+        // parameter_id 0 is a scalar function's return value; OUTPUT parameters are also read on input
+        let direction = match (parameter_id, is_output) {
+            (0, _) => ParameterDirection::Return,
+            (_, true) => ParameterDirection::InOut,
+            (_, false) => ParameterDirection::In,
+        };
+        
+        routines[i].parameters.push(RoutineParameter {
+            name: name.to_string(),
+            data_type: render_type_name(type_name, max_length, precision, scale),
+            direction,
+            default_value: None,
+            ordinal_position: parameter_id,
         });
     }
     
-    Ok(SchemaData {
-        tables,
-        columns,
-        primary_keys,
-        foreign_keys,
-    })
+    // SQL Server only records defaults of CLR parameters, so read T-SQL defaults from the source
+    for routine in &mut routines {
+        let Some(definition) = &routine.definition else {
+            continue;
+        };
+        let defaults = parse_parameter_defaults(definition);
+        for param in &mut routine.parameters {
+            param.default_value = defaults.get(&param.name.to_lowercase()).cloned();
+        }
+    }
+    
+    Ok(routines)
+}
+
+/// A token of T-SQL source, with comments and whitespace dropped
+#[derive(Debug, Clone, PartialEq)]
+enum SqlToken {
+    Word(String),
+    Literal(String),
+    Symbol(char),
+}
+
+/// Split T-SQL into words, string literals and symbols
+fn tokenize_sql(sql: &str) -> Vec<SqlToken> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        
+        if c.is_whitespace() {
+            i += 1;
+        } else if c == '-' && next == Some('-') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && next == Some('*') {
+            // Block comments nest in T-SQL
+            let mut depth = 0;
+            while i < chars.len() {
+                if chars[i] == '/' && chars.get(i + 1) == Some(&'*') {
+                    depth += 1;
+                    i += 2;
+                } else if chars[i] == '*' && chars.get(i + 1) == Some(&'/') {
+                    depth -= 1;
+                    i += 2;
+                    if depth == 0 {
+                        break;
+                    }
+                } else {
+                    i += 1;
+                }
+            }
+        } else if c == '\'' || ((c == 'N' || c == 'n') && next == Some('\'')) {
+            let start = i;
+            i += if c == '\'' { 1 } else { 2 };
+            while i < chars.len() {
+                if chars[i] == '\'' && chars.get(i + 1) == Some(&'\'') {
+                    i += 2;
+                } else if chars[i] == '\'' {
+                    i += 1;
+                    break;
+                } else {
+                    i += 1;
+                }
+            }
+            tokens.push(SqlToken::Literal(chars[start..i].iter().collect()));
+        } else if c == '[' {
+            let start = i;
+            while i < chars.len() && chars[i] != ']' {
+                i += 1;
+            }
+            i += 1;
+            tokens.push(SqlToken::Word(chars[start..i.min(chars.len())].iter().collect()));
+        } else if c.is_alphanumeric() || matches!(c, '_' | '@' | '#' | '$') {
+            let start = i;
+            while i < chars.len() && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '@' | '#' | '$' | '.')) {
+                i += 1;
+            }
+            tokens.push(SqlToken::Word(chars[start..i].iter().collect()));
+        } else {
+            tokens.push(SqlToken::Symbol(c));
+            i += 1;
+        }
+    }
+    
+    tokens
+}
+
+/// Read `@param type = default` declarations from the header of a procedure or function.
+/// Returns lowercase parameter name -> default value text.
+fn parse_parameter_defaults(definition: &str) -> HashMap<String, String> {
+    let tokens = tokenize_sql(definition);
+    let mut defaults = HashMap::new();
+    
+    let is_word = |t: &SqlToken, w: &str| matches!(t, SqlToken::Word(x) if x.eq_ignore_ascii_case(w));
+    
+    // Parameters start after CREATE/ALTER PROCEDURE|FUNCTION <name>
+    let Some(start) = tokens.iter().position(|t| {
+        is_word(t, "PROC") || is_word(t, "PROCEDURE") || is_word(t, "FUNCTION")
+    }) else {
+        return defaults;
+    };
+    
+    let mut depth = 0i32;
+    let mut current: Option<(String, i32)> = None;
+    let mut i = start + 1;
+    
+    while i < tokens.len() {
+        let token = &tokens[i];
+        match token {
+            SqlToken::Symbol('(') => depth += 1,
+            SqlToken::Symbol(')') => depth -= 1,
+            SqlToken::Word(w) if depth == 0 && (w.eq_ignore_ascii_case("AS") || w.eq_ignore_ascii_case("RETURNS")) => {
+                // The parameter list ends at AS (procedures) or RETURNS (functions), except for
+                // the optional AS between a parameter name and its type
+                let after_param = matches!(tokens.get(i - 1), Some(SqlToken::Word(p)) if p.starts_with('@'));
+                if !after_param {
+                    break;
+                }
+            }
+            SqlToken::Word(w) if w.starts_with('@') => current = Some((w.to_lowercase(), depth)),
+            SqlToken::Symbol('=') => {
+                if let Some((name, param_depth)) = current.take() {
+                    if param_depth == depth {
+                        let mut value = String::new();
+                        let mut value_depth = 0;
+                        i += 1;
+                        while i < tokens.len() {
+                            match &tokens[i] {
+                                SqlToken::Symbol(',') if value_depth == 0 => break,
+                                SqlToken::Symbol(')') if value_depth == 0 => break,
+                                SqlToken::Word(w) if value_depth == 0 && ["OUT", "OUTPUT", "READONLY", "AS", "WITH", "RETURNS"]
+                                    .iter().any(|k| w.eq_ignore_ascii_case(k)) => break,
+                                t => {
+                                    match t {
+                                        SqlToken::Symbol('(') => value_depth += 1,
+                                        SqlToken::Symbol(')') => value_depth -= 1,
+                                        _ => {}
+                                    }
+                                    let text = match t {
+                                        SqlToken::Word(w) | SqlToken::Literal(w) => w.clone(),
+                                        SqlToken::Symbol(c) => c.to_string(),
+                                    };
+                                    if value.ends_with(|c: char| c.is_alphanumeric()) && text.starts_with(|c: char| c.is_alphanumeric()) {
+                                        value.push(' ');
+                                    }
+                                    value.push_str(&text);
+                                }
+                            }
+                            i += 1;
+                        }
+                        defaults.insert(name, value);
+                        continue;
+                    }
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    
+    defaults
+}
+
+/// A SQL Server session the schema is read from
+pub struct SqlServerSource {
+    client: Client<tokio_util::compat::Compat<TcpStream>>,
+    /// Catalog queries are limited to the objects in `SCOPE_TABLE`
+    scoped: bool,
+}
+
+impl SchemaSource for SqlServerSource {
+    async fn connect(connection: &Connection, password: Option<&str>) -> Result<Self, ConnectError> {
+        let client = connect(connection, password).await?;
+        Ok(Self { client, scoped: false })
+    }
+    
+    async fn probe(&mut self) -> Result<ServerProbe, String> {
+        probe_server(&mut self.client).await
+    }
+    
+    async fn get_tables(&mut self) -> Result<Vec<Table>, String> {
+        get_tables(&mut self.client, self.scoped).await
+    }
+    
+    async fn get_columns(&mut self) -> Result<Vec<Column>, String> {
+        get_columns(&mut self.client, self.scoped).await
+    }
+    
+    async fn get_primary_keys(&mut self) -> Result<Vec<PrimaryKey>, String> {
+        get_primary_keys(&mut self.client, self.scoped).await
+    }
+    
+    async fn get_foreign_keys(&mut self) -> Result<Vec<ForeignKey>, String> {
+        get_foreign_keys(&mut self.client, self.scoped).await
+    }
+    
+    async fn get_indexes(&mut self) -> Result<Vec<Index>, String> {
+        get_indexes(&mut self.client, self.scoped).await
+    }
+    
+    async fn get_check_constraints(&mut self) -> Result<Vec<CheckConstraint>, String> {
+        get_check_constraints(&mut self.client, self.scoped).await
+    }
+    
+    async fn get_default_constraints(&mut self) -> Result<Vec<DefaultConstraint>, String> {
+        get_default_constraints(&mut self.client, self.scoped).await
+    }
+    
+    async fn get_routines(&mut self) -> Result<Vec<Routine>, String> {
+        get_routines(&mut self.client, self.scoped).await
+    }
+    
+    async fn get_object_versions(&mut self) -> Result<Option<Vec<ObjectVersion>>, String> {
+        get_object_versions(&mut self.client).await.map(Some)
+    }
+    
+    async fn set_scope(&mut self, object_ids: &[i64]) -> Result<(), String> {
+        set_scope(&mut self.client, object_ids).await?;
+        self.scoped = true;
+        Ok(())
+    }
+    
+    async fn get_properties(&mut self) -> Result<HashMap<PropertyOwner, ObjectProperties>, String> {
+        let properties = get_extended_properties(&mut self.client).await?;
+        
+        Ok(properties.into_iter()
+            .map(|(owner, mut extended)| {
+                let description = extended.remove(DESCRIPTION_PROPERTY);
+                (owner, ObjectProperties { description, extended })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn word(w: &str) -> SqlToken {
+        SqlToken::Word(w.to_string())
+    }
+    
+    #[test]
+    fn tokenizer_skips_comments_and_keeps_literals_whole() {
+        let tokens = tokenize_sql("SELECT /* outer /* inner */ 'still' a comment */ [my col], N'it''s', -- to the end\n @x+1 /* unclosed");
+        assert_eq!(tokens, [
+            word("SELECT"),
+            word("[my col]"),
+            SqlToken::Symbol(','),
+            SqlToken::Literal("N'it''s'".to_string()),
+            SqlToken::Symbol(','),
+            word("@x"),
+            SqlToken::Symbol('+'),
+            word("1"),
+        ]);
+        
+        assert_eq!(tokenize_sql("n'x'"), [SqlToken::Literal("n'x'".to_string())]);
+        assert_eq!(tokenize_sql("dbo.Orders"), [word("dbo.Orders")]);
+    }
+    
+    #[test]
+    fn parameter_defaults() {
+        let cases: &[(&str, &[(&str, &str)])] = &[
+            ("CREATE PROCEDURE dbo.p @p AS int = 1, @q AS varchar(10) AS SELECT 1", &[("@p", "1")]),
+            (
+                "CREATE PROC dbo.p @total decimal(18, 2) = 0 OUTPUT, @count int OUT, @rows dbo.IdList READONLY, @n int = -1 AS RETURN",
+                &[("@total", "0"), ("@n", "-1")],
+            ),
+            (
+                "CREATE PROCEDURE dbo.p @name nvarchar(20) = N'O''Brien, Jr. (ret)', @ratio float = (1.5), @at datetime = GETDATE() AS SELECT 1",
+                &[("@name", "N'O''Brien, Jr. (ret)'"), ("@ratio", "(1.5)"), ("@at", "GETDATE()")],
+            ),
+            (
+                "CREATE FUNCTION dbo.f(@a int = 1, @b varchar(3) = 'a,b') RETURNS TABLE AS RETURN SELECT @a AS a",
+                &[("@a", "1"), ("@b", "'a,b'")],
+            ),
+            (
+                "/* CREATE PROCEDURE dbo.old @gone int = 9 /* nested */ AS */\nALTER PROCEDURE dbo.p\n    @keep int = 2 -- , @also int = 3\nWITH RECOMPILE\nAS\nDECLARE @local int = 4",
+                &[("@keep", "2")],
+            ),
+            ("CREATE PROCEDURE dbo.p AS DECLARE @local int = 4", &[]),
+            ("SELECT @x = 1", &[]),
+        ];
+        
+        for (definition, expected) in cases {
+            let defaults = parse_parameter_defaults(definition);
+            let expected: HashMap<String, String> = expected.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+            assert_eq!(defaults, expected, "{}", definition);
+        }
+    }
 }
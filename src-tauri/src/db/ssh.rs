@@ -0,0 +1,407 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use ssh2::{Channel, CheckResult, HostKeyType, KnownHostFileKind, Session};
+use crate::config::{KnownHostsPolicy, SshTunnel};
+use super::{ConnectError, ConnectErrorKind};
+
+/// Default SSH port
+const DEFAULT_PORT: u16 = 22;
+
+/// How long reaching the bastion and the SSH handshake may take
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Pause between polls when no data moved in either direction; doubled on each idle poll up to
+/// `MAX_IDLE_POLL`, so a forward waiting between queries does not keep a core busy
+const IDLE_POLL: Duration = Duration::from_millis(2);
+const MAX_IDLE_POLL: Duration = Duration::from_millis(100);
+
+fn ssh_error(message: String) -> ConnectError {
+    ConnectError::new(ConnectErrorKind::Ssh, message)
+}
+
+/// Check the bastion's host key against `~/.ssh/known_hosts` as the policy asks
+fn verify_host_key(session: &Session, tunnel: &SshTunnel, port: u16) -> Result<(), ConnectError> {
+    if tunnel.known_hosts == KnownHostsPolicy::AcceptAny {
+        return Ok(());
+    }
+    
+    let (key, key_type) = session.host_key()
+        .ok_or_else(|| ssh_error(format!("SSH server {} sent no host key", tunnel.host)))?;
+    let path = dirs::home_dir()
+        .map(|home| home.join(".ssh").join("known_hosts"))
+        .ok_or_else(|| ssh_error("Could not determine the home directory for known_hosts".to_string()))?;
+    
+    check_host_key(session, &path, &tunnel.host, port, key, key_type, tunnel.known_hosts)
+}
+
+/// Look the host key up in a known_hosts file, adding it if the policy accepts new hosts
+fn check_host_key(
+    session: &Session,
+    path: &Path,
+    host: &str,
+    port: u16,
+    key: &[u8],
+    key_type: HostKeyType,
+    policy: KnownHostsPolicy,
+) -> Result<(), ConnectError> {
+    if policy == KnownHostsPolicy::AcceptAny {
+        return Ok(());
+    }
+    
+    let mut known_hosts = session.known_hosts()
+        .map_err(|e| ssh_error(format!("Failed to read known hosts: {}", e)))?;
+    if path.exists() {
+        known_hosts.read_file(path, KnownHostFileKind::OpenSSH)
+            .map_err(|e| ssh_error(format!("Failed to read {}: {}", path.display(), e)))?;
+    }
+    
+    match known_hosts.check_port(host, port, key) {
+        CheckResult::Match => Ok(()),
+        CheckResult::NotFound if policy == KnownHostsPolicy::AcceptNew => {
+            let entry = if port == DEFAULT_PORT {
+                host.to_string()
+            } else {
+                format!("[{}]:{}", host, port)
+            };
+            println!("Adding SSH host key for {} to {}", entry, path.display());
+            known_hosts.add(&entry, key, "", key_type.into())
+                .and_then(|_| {
+                    if let Some(dir) = path.parent() {
+                        std::fs::create_dir_all(dir).ok();
+                    }
+                    known_hosts.write_file(path, KnownHostFileKind::OpenSSH)
+                })
+                .map_err(|e| ssh_error(format!("Failed to save host key to {}: {}", path.display(), e)))
+        }
+        CheckResult::NotFound => Err(ssh_error(format!(
+            "Host key for {} is not in {}; connect once with ssh or allow new hosts",
+            host,
+            path.display(),
+        ))),
+        CheckResult::Mismatch => Err(ssh_error(format!(
+            "Host key for {} does not match {}; the server may have been replaced or intercepted",
+            host,
+            path.display(),
+        ))),
+        CheckResult::Failure => Err(ssh_error(format!("Failed to check the host key for {}", host))),
+    }
+}
+
+/// Connect and log in to the bastion
+fn open_session(tunnel: &SshTunnel) -> Result<Session, ConnectError> {
+    let port = tunnel.port.unwrap_or(DEFAULT_PORT);
+    let addrs: Vec<SocketAddr> = (tunnel.host.as_str(), port)
+        .to_socket_addrs()
+        .map_err(|e| ConnectError::new(ConnectErrorKind::Dns, format!("Failed to resolve SSH host '{}': {}", tunnel.host, e)))?
+        .collect();
+    let addr = addrs.first()
+        .ok_or_else(|| ConnectError::new(ConnectErrorKind::Dns, format!("SSH host '{}' has no addresses", tunnel.host)))?;
+    
+    let tcp = TcpStream::connect_timeout(addr, CONNECT_TIMEOUT)
+        .map_err(|e| {
+            let kind = match e.kind() {
+                ErrorKind::ConnectionRefused => ConnectErrorKind::TcpRefused,
+                _ => ConnectErrorKind::Network,
+            };
+            ConnectError::new(kind, format!("Failed to connect to SSH host {}: {}", addr, e))
+        })?;
+    
+    let mut session = Session::new()
+        .map_err(|e| ssh_error(format!("Failed to start SSH session: {}", e)))?;
+    session.set_tcp_stream(tcp);
+    session.set_timeout(CONNECT_TIMEOUT.as_millis() as u32);
+    session.handshake()
+        .map_err(|e| ssh_error(format!("SSH handshake with {} failed: {}", tunnel.host, e)))?;
+    
+    verify_host_key(&session, tunnel, port)?;
+    
+    let auth = match tunnel.key_path.as_deref().map(str::trim).filter(|p| !p.is_empty()) {
+        Some(key_path) => {
+            let key_path = match (key_path.strip_prefix("~/"), dirs::home_dir()) {
+                (Some(rest), Some(home)) => home.join(rest),
+                _ => PathBuf::from(key_path),
+            };
+            session.userauth_pubkey_file(&tunnel.user, None, &key_path, None)
+        }
+        None => session.userauth_agent(&tunnel.user),
+    };
+    auth.map_err(|e| ConnectError::new(
+        ConnectErrorKind::LoginFailed,
+        format!("SSH login as {} on {} failed: {}", tunnel.user, tunnel.host, e),
+    ))?;
+    
+    // Forwarded traffic may idle for long stretches between queries
+    session.set_timeout(0);
+    Ok(session)
+}
+
+/// The far end of a forward
+trait Remote: Read + Write {
+    /// Whether the far end has closed; reading nothing does not tell on its own
+    fn is_closed(&self) -> bool;
+}
+
+impl Remote for Channel {
+    fn is_closed(&self) -> bool {
+        self.eof()
+    }
+}
+
+/// Copy bytes between the local socket and the far end until either side closes. The far end
+/// must already be non-blocking.
+fn pump(mut remote: impl Remote, mut local: TcpStream) -> std::io::Result<()> {
+    local.set_nonblocking(true)?;
+    
+    let mut buf = [0u8; 16 * 1024];
+    // Data read from one side that the other has not accepted yet
+    let mut upstream: Vec<u8> = Vec::new();
+    let mut downstream: Vec<u8> = Vec::new();
+    let mut idle_poll = IDLE_POLL;
+    
+    loop {
+        let mut moved = false;
+        
+        if upstream.is_empty() {
+            match local.read(&mut buf) {
+                // The database client hung up
+                Ok(0) => return Ok(()),
+                Ok(n) => {
+                    upstream.extend_from_slice(&buf[..n]);
+                    moved = true;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if !upstream.is_empty() {
+            match remote.write(&upstream) {
+                Ok(n) => {
+                    upstream.drain(..n);
+                    moved = moved || n > 0;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+        }
+        
+        if downstream.is_empty() {
+            match remote.read(&mut buf) {
+                Ok(0) if remote.is_closed() => return Ok(()),
+                Ok(n) => {
+                    downstream.extend_from_slice(&buf[..n]);
+                    moved = moved || n > 0;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if !downstream.is_empty() {
+            match local.write(&downstream) {
+                Ok(n) => {
+                    downstream.drain(..n);
+                    moved = moved || n > 0;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+        }
+        
+        if moved {
+            idle_poll = IDLE_POLL;
+        } else {
+            std::thread::sleep(idle_poll);
+            idle_poll = (idle_poll * 2).min(MAX_IDLE_POLL);
+        }
+    }
+}
+
+/// Connect a socket to a fresh loopback port and return both ends. Only the returned client
+/// is accepted, so no other local process can slip in on the port meanwhile.
+fn loopback_pair() -> std::io::Result<(TcpStream, TcpStream)> {
+    let listener = TcpListener::bind(("127.0.0.1", 0))?;
+    let client = TcpStream::connect(listener.local_addr()?)?;
+    let client_addr = client.local_addr()?;
+    
+    loop {
+        let (accepted, peer) = listener.accept()?;
+        if peer == client_addr {
+            return Ok((client, accepted));
+        }
+        eprintln!("Refused a stray connection from {} to the SSH tunnel", peer);
+    }
+}
+
+/// Open an SSH session to the bastion and forward a connection to `target_host:target_port`.
+/// The forward carries a single connection, returned already connected: it is meant for the
+/// one database session opened on it, and closes together with it.
+pub async fn open_forward(tunnel: &SshTunnel, target_host: &str, target_port: u16) -> Result<tokio::net::TcpStream, ConnectError> {
+    if tunnel.host.trim().is_empty() || tunnel.user.trim().is_empty() {
+        return Err(ConnectError::new(ConnectErrorKind::Configuration, "SSH tunnel needs a host and a user"));
+    }
+    
+    let tunnel = tunnel.clone();
+    let target_host = target_host.to_string();
+    
+    let client = tokio::task::spawn_blocking(move || {
+        println!("Opening SSH tunnel via {}@{} to {}:{}", tunnel.user, tunnel.host, target_host, target_port);
+        let session = open_session(&tunnel)?;
+        
+        // Open the channel up front so a refused forward is reported as a connection error
+        let channel = session.channel_direct_tcpip(&target_host, target_port, None)
+            .map_err(|e| ssh_error(format!("SSH host {} could not reach {}:{}: {}", tunnel.host, target_host, target_port, e)))?;
+        
+        let (client, local) = loopback_pair()
+            .and_then(|(client, local)| client.set_nonblocking(true).map(|_| (client, local)))
+            .map_err(|e| ssh_error(format!("Failed to open local port for SSH tunnel: {}", e)))?;
+        
+        std::thread::spawn(move || {
+            session.set_blocking(false);
+            if let Err(e) = pump(channel, local) {
+                eprintln!("SSH tunnel to {}:{} failed: {}", target_host, target_port, e);
+            }
+            // The session lives as long as its channel
+            drop(session);
+        });
+        
+        Ok(client)
+    })
+    .await
+    .map_err(|e| ssh_error(format!("SSH tunnel task failed: {}", e)))??;
+    
+    tokio::net::TcpStream::from_std(client)
+        .map_err(|e| ssh_error(format!("Failed to connect to SSH tunnel: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    /// Stands in for the SSH server's end of a channel: whatever it is sent comes back
+    fn echo_remote() -> TcpStream {
+        let (near, mut far) = loopback_pair().unwrap();
+        std::thread::spawn(move || {
+            let mut buf = [0u8; 1024];
+            while let Ok(n) = far.read(&mut buf) {
+                if n == 0 || far.write_all(&buf[..n]).is_err() {
+                    break;
+                }
+            }
+        });
+        near.set_nonblocking(true).unwrap();
+        near
+    }
+    
+    impl Remote for TcpStream {
+        fn is_closed(&self) -> bool {
+            false
+        }
+    }
+    
+    #[test]
+    fn forward_carries_the_connected_client() {
+        let (mut client, local) = loopback_pair().unwrap();
+        let forward = std::thread::spawn(move || pump(echo_remote(), local));
+        
+        for message in [&b"SELECT 1"[..], &[0u8; 40_000][..]] {
+            // Long enough for the forward to back off to its slowest poll
+            std::thread::sleep(MAX_IDLE_POLL * 3);
+            client.write_all(message).unwrap();
+            let mut echoed = vec![0u8; message.len()];
+            client.read_exact(&mut echoed).unwrap();
+            assert_eq!(echoed, message);
+        }
+        
+        // Hanging up ends the forward
+        drop(client);
+        forward.join().unwrap().unwrap();
+    }
+    
+    /// An ed25519 public key blob as a server would present it
+    fn host_key(fill: u8) -> Vec<u8> {
+        let mut blob = Vec::new();
+        for part in [&b"ssh-ed25519"[..], &[fill; 32][..]] {
+            blob.extend_from_slice(&(part.len() as u32).to_be_bytes());
+            blob.extend_from_slice(part);
+        }
+        blob
+    }
+    
+    #[test]
+    fn known_hosts_policies() {
+        let dir = std::env::temp_dir().join(format!("glance-ssh-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        let path = dir.join("known_hosts");
+        let session = Session::new().unwrap();
+        let (known, other) = (host_key(1), host_key(2));
+        let check = |key: &[u8], policy| {
+            check_host_key(&session, &path, "bastion.example", 2222, key, HostKeyType::Ed25519, policy)
+        };
+        
+        // An unknown host is refused unless new hosts are allowed
+        let refused = check(&known, KnownHostsPolicy::Strict).unwrap_err();
+        assert!(matches!(refused.kind, ConnectErrorKind::Ssh));
+        assert!(refused.message.contains("is not in"), "{}", refused.message);
+        assert!(check(&known, KnownHostsPolicy::AcceptAny).is_ok());
+        assert!(!path.exists());
+        
+        // Accepting a new host records it for later checks
+        check(&known, KnownHostsPolicy::AcceptNew).unwrap();
+        let saved = std::fs::read_to_string(&path).unwrap();
+        assert!(saved.starts_with("[bastion.example]:2222 ssh-ed25519 "), "{}", saved);
+        check(&known, KnownHostsPolicy::Strict).unwrap();
+        
+        // A changed key is refused even when new hosts are allowed
+        for policy in [KnownHostsPolicy::Strict, KnownHostsPolicy::AcceptNew] {
+            let refused = check(&other, policy).unwrap_err();
+            assert!(refused.message.contains("does not match"), "{}", refused.message);
+        }
+        assert!(check(&other, KnownHostsPolicy::AcceptAny).is_ok());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), saved);
+        
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    
+    /// A real SSH server to test against, from `GLANCE_TEST_SSH_HOST`, `GLANCE_TEST_SSH_PORT`,
+    /// `GLANCE_TEST_SSH_USER` and `GLANCE_TEST_SSH_KEY` (the agent is used without a key)
+    fn test_tunnel() -> SshTunnel {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        SshTunnel {
+            host: var("GLANCE_TEST_SSH_HOST").expect("GLANCE_TEST_SSH_HOST names the SSH server to test against"),
+            port: var("GLANCE_TEST_SSH_PORT").map(|p| p.parse().expect("GLANCE_TEST_SSH_PORT is a port number")),
+            user: var("GLANCE_TEST_SSH_USER").expect("GLANCE_TEST_SSH_USER names the user to log in as"),
+            key_path: var("GLANCE_TEST_SSH_KEY"),
+            known_hosts: KnownHostsPolicy::AcceptAny,
+        }
+    }
+    
+    #[test]
+    #[ignore = "needs an SSH server, see test_tunnel"]
+    fn logs_in_to_a_real_server() {
+        let session = open_session(&test_tunnel()).unwrap();
+        assert!(session.authenticated());
+        
+        let mut refused = test_tunnel();
+        refused.user = "glance-no-such-user".to_string();
+        let Err(error) = open_session(&refused) else { panic!("logged in as an unknown user") };
+        assert!(matches!(error.kind, ConnectErrorKind::LoginFailed), "{}", error.message);
+    }
+    
+    #[tokio::test]
+    #[ignore = "needs an SSH server, see test_tunnel"]
+    async fn forwards_through_a_real_server() {
+        use tokio::io::AsyncReadExt;
+        
+        // The server forwards to its own SSH port, which greets every connection with its banner
+        let tunnel = test_tunnel();
+        let mut stream = open_forward(&tunnel, "127.0.0.1", DEFAULT_PORT).await.unwrap();
+        let mut banner = [0u8; 8];
+        stream.read_exact(&mut banner).await.unwrap();
+        assert_eq!(&banner, b"SSH-2.0-");
+        
+        // Nothing listens on port 1, so the server refuses the channel
+        let error = open_forward(&tunnel, "127.0.0.1", 1).await.unwrap_err();
+        assert!(matches!(error.kind, ConnectErrorKind::Ssh), "{}", error.message);
+    }
+}
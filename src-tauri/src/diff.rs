@@ -0,0 +1,959 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use crate::commands::schema::{
+    CheckConstraint, Column, ForeignKey, Index, ObjectKind, Routine, RoutineKind, SchemaData, Table,
+};
+use crate::db::cache::routine_signature;
+
+/// How an object differs between the two schemas
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    /// Same contents under another name (tables only)
+    Renamed,
+    Modified,
+}
+
+/// Column attributes compared between the two schemas
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ColumnAspect {
+    Type,
+    Nullability,
+    Default,
+    Identity,
+    Computed,
+    Collation,
+}
+
+/// Differences between two schemas, going from the first to the second
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SchemaDiff {
+    pub tables: Vec<TableDiff>,
+    pub routines: Vec<RoutineDiff>,
+    /// The same differences as plain text, one line per change
+    pub report: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableDiff {
+    pub schema: String,
+    pub name: String,
+    pub object_kind: ObjectKind,
+    pub change: ChangeKind,
+    /// Qualified name in the first schema of a renamed table
+    pub renamed_from: Option<String>,
+    /// Column, key and index changes of a modified or renamed table
+    pub columns: Vec<ColumnChange>,
+    pub primary_key: Option<ObjectChange>,
+    pub foreign_keys: Vec<ObjectChange>,
+    pub indexes: Vec<ObjectChange>,
+    pub check_constraints: Vec<ObjectChange>,
+    /// The SQL text of a view or the target of a synonym differs
+    pub definition_changed: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnChange {
+    pub name: String,
+    pub change: ChangeKind,
+    /// Declaration on each side, e.g. `nvarchar(50) NOT NULL DEFAULT ('')`
+    pub before: Option<String>,
+    pub after: Option<String>,
+    /// What differs for a modified column
+    pub aspects: Vec<ColumnAspect>,
+}
+
+/// A key, index or constraint that differs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObjectChange {
+    pub name: String,
+    pub change: ChangeKind,
+    /// Definition on each side, e.g. `UNIQUE NONCLUSTERED (Email) INCLUDE (Name)`
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutineDiff {
+    pub schema: String,
+    pub name: String,
+    pub routine_kind: RoutineKind,
+    pub change: ChangeKind,
+    /// Source text on each side
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// A table together with everything attached to it in the flat `SchemaData` lists
+struct TableParts<'a> {
+    table: &'a Table,
+    /// In ordinal order
+    columns: Vec<&'a Column>,
+    primary_key: Option<String>,
+    foreign_keys: Vec<(String, String)>,
+    indexes: Vec<(String, String)>,
+    check_constraints: Vec<(String, String)>,
+}
+
+/// Case-insensitive lookup key for an object, since both sides may differ only in casing
+fn key(schema: &str, name: &str) -> String {
+    format!("{}.{}", schema.to_lowercase(), name.to_lowercase())
+}
+
+fn qualified(schema: &str, name: &str) -> String {
+    if schema.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", schema, name)
+    }
+}
+
+/// Compare SQL text ignoring layout
+fn same_sql(a: &str, b: &str) -> bool {
+    a.split_whitespace().eq(b.split_whitespace())
+}
+
+fn column_type(column: &Column) -> &str {
+    if column.full_type.is_empty() {
+        &column.data_type
+    } else {
+        &column.full_type
+    }
+}
+
+fn describe_column(column: &Column) -> String {
+    let mut text = format!("{} {}", column_type(column), if column.is_nullable { "NULL" } else { "NOT NULL" });
+    if let (Some(seed), Some(increment)) = (column.identity_seed, column.identity_increment) {
+        text.push_str(&format!(" IDENTITY({}, {})", seed, increment));
+    }
+    if let Some(expression) = &column.computed_definition {
+        text.push_str(&format!(" AS {}", expression));
+    }
+    if let Some(default) = &column.default_value {
+        text.push_str(&format!(" DEFAULT {}", default));
+    }
+    if let Some(collation) = &column.collation {
+        text.push_str(&format!(" COLLATE {}", collation));
+    }
+    text
+}
+
+fn column_aspects(before: &Column, after: &Column) -> Vec<ColumnAspect> {
+    let same_expression = |a: &Option<String>, b: &Option<String>| match (a, b) {
+        (Some(a), Some(b)) => same_sql(a, b),
+        (a, b) => a == b,
+    };
+    
+    let mut aspects = Vec::new();
+    if !column_type(before).eq_ignore_ascii_case(column_type(after)) {
+        aspects.push(ColumnAspect::Type);
+    }
+    if before.is_nullable != after.is_nullable {
+        aspects.push(ColumnAspect::Nullability);
+    }
+    if !same_expression(&before.default_value, &after.default_value) {
+        aspects.push(ColumnAspect::Default);
+    }
+    if (before.identity_seed, before.identity_increment) != (after.identity_seed, after.identity_increment) {
+        aspects.push(ColumnAspect::Identity);
+    }
+    if !same_expression(&before.computed_definition, &after.computed_definition) {
+        aspects.push(ColumnAspect::Computed);
+    }
+    if before.collation != after.collation {
+        aspects.push(ColumnAspect::Collation);
+    }
+    aspects
+}
+
+fn describe_foreign_key(fk: &ForeignKey) -> String {
+    let parent: Vec<&str> = fk.columns.iter().map(|c| c.parent_column.as_str()).collect();
+    let referenced: Vec<&str> = fk.columns.iter().map(|c| c.referenced_column.as_str()).collect();
+    let mut text = format!(
+        "({}) REFERENCES {} ({})",
+        parent.join(", "),
+        qualified(&fk.referenced_schema, &fk.referenced_table),
+        referenced.join(", "),
+    );
+    for (event, action) in [("DELETE", &fk.delete_referential_action), ("UPDATE", &fk.update_referential_action)] {
+        if !action.is_empty() && action != "NO_ACTION" {
+            text.push_str(&format!(" ON {} {}", event, action.replace('_', " ")));
+        }
+    }
+    if fk.is_disabled {
+        text.push_str(" DISABLED");
+    } else if fk.is_not_trusted {
+        text.push_str(" NOT TRUSTED");
+    }
+    text
+}
+
+fn index_columns(index: &Index) -> String {
+    index.key_columns.iter()
+        .map(|c| if c.is_descending { format!("{} DESC", c.name) } else { c.name.clone() })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn describe_index(index: &Index) -> String {
+    let mut text = String::new();
+    if index.is_unique_constraint {
+        text.push_str("UNIQUE CONSTRAINT ");
+    } else if index.is_unique {
+        text.push_str("UNIQUE ");
+    }
+    text.push_str(&format!("{} ({})", index.index_type, index_columns(index)));
+    if !index.included_columns.is_empty() {
+        text.push_str(&format!(" INCLUDE ({})", index.included_columns.join(", ")));
+    }
+    if let Some(filter) = &index.filter_definition {
+        text.push_str(&format!(" WHERE {}", filter));
+    }
+    text
+}
+
+fn describe_check(check: &CheckConstraint) -> String {
+    let mut text = check.definition.split_whitespace().collect::<Vec<_>>().join(" ");
+    if check.is_disabled {
+        text.push_str(" DISABLED");
+    } else if check.is_not_trusted {
+        text.push_str(" NOT TRUSTED");
+    }
+    text
+}
+
+/// Group the flat lists of a schema by table
+fn gather(schema: &SchemaData) -> BTreeMap<String, TableParts<'_>> {
+    let mut tables: BTreeMap<String, TableParts> = schema.tables.iter()
+        .map(|table| (key(&table.schema, &table.name), TableParts {
+            table,
+            columns: Vec::new(),
+            primary_key: None,
+            foreign_keys: Vec::new(),
+            indexes: Vec::new(),
+            check_constraints: Vec::new(),
+        }))
+        .collect();
+    
+    for column in &schema.columns {
+        if let Some(parts) = tables.get_mut(&key(&column.table_schema, &column.table_name)) {
+            parts.columns.push(column);
+        }
+    }
+    for parts in tables.values_mut() {
+        parts.columns.sort_by_key(|c| c.ordinal_position);
+    }
+    
+    let mut primary_keys: HashMap<String, Vec<&str>> = HashMap::new();
+    for pk in &schema.primary_keys {
+        primary_keys.entry(key(&pk.table_schema, &pk.table_name)).or_default().push(&pk.column_name);
+    }
+    for (table_key, mut columns) in primary_keys {
+        if let Some(parts) = tables.get_mut(&table_key) {
+            // Without an index the key order is unknown; column order at least does not depend
+            // on how the provider happened to list them
+            columns.sort_by_key(|name| find_column(&parts.columns, name).map(|c| c.ordinal_position));
+            parts.primary_key = Some(format!("PRIMARY KEY ({})", columns.join(", ")));
+        }
+    }
+    
+    for index in &schema.indexes {
+        let Some(parts) = tables.get_mut(&key(&index.table_schema, &index.table_name)) else { continue };
+        if index.is_primary_key {
+            // The index gives the key's column order and clustering
+            parts.primary_key = Some(format!("PRIMARY KEY {} ({})", index.index_type, index_columns(index)));
+        } else {
+            parts.indexes.push((index.name.clone(), describe_index(index)));
+        }
+    }
+    for fk in &schema.foreign_keys {
+        if let Some(parts) = tables.get_mut(&key(&fk.parent_schema, &fk.parent_table)) {
+            parts.foreign_keys.push((fk.constraint_name.clone(), describe_foreign_key(fk)));
+        }
+    }
+    for check in &schema.check_constraints {
+        if let Some(parts) = tables.get_mut(&key(&check.table_schema, &check.table_name)) {
+            parts.check_constraints.push((check.constraint_name.clone(), describe_check(check)));
+        }
+    }
+    
+    tables
+}
+
+/// Compare named objects by name. Objects whose name differs but whose definition is
+/// identical on both sides are taken to be the same, since generated constraint names
+/// rarely match between environments.
+fn diff_named(before: &[(String, String)], after: &[(String, String)]) -> Vec<ObjectChange> {
+    let find = |list: &[(String, String)], name: &str| list.iter().position(|(n, _)| n.eq_ignore_ascii_case(name));
+    let mut changes = Vec::new();
+    
+    for (name, definition) in before {
+        if let Some(i) = find(after, name) {
+            if after[i].1 != *definition {
+                changes.push(ObjectChange {
+                    name: after[i].0.clone(),
+                    change: ChangeKind::Modified,
+                    before: Some(definition.clone()),
+                    after: Some(after[i].1.clone()),
+                });
+            }
+        }
+    }
+    
+    let mut added: Vec<&(String, String)> = after.iter().filter(|(n, _)| find(before, n).is_none()).collect();
+    let removed: Vec<&(String, String)> = before.iter()
+        .filter(|(n, _)| find(after, n).is_none())
+        .filter(|(_, definition)| match added.iter().position(|(_, d)| d == definition) {
+            Some(i) => {
+                added.remove(i);
+                false
+            }
+            None => true,
+        })
+        .collect();
+    
+    for (name, definition) in removed {
+        changes.push(ObjectChange { name: name.clone(), change: ChangeKind::Removed, before: Some(definition.clone()), after: None });
+    }
+    for (name, definition) in added {
+        changes.push(ObjectChange { name: name.clone(), change: ChangeKind::Added, before: None, after: Some(definition.clone()) });
+    }
+    changes.sort_by_key(|c| c.name.to_lowercase());
+    changes
+}
+
+fn find_column<'a>(columns: &[&'a Column], name: &str) -> Option<&'a Column> {
+    columns.iter().find(|c| c.name.eq_ignore_ascii_case(name)).copied()
+}
+
+fn diff_columns(before: &[&Column], after: &[&Column]) -> Vec<ColumnChange> {
+    let mut changes = Vec::new();
+    
+    for column in after {
+        match find_column(before, &column.name) {
+            None => changes.push(ColumnChange {
+                name: column.name.clone(),
+                change: ChangeKind::Added,
+                before: None,
+                after: Some(describe_column(column)),
+                aspects: Vec::new(),
+            }),
+            Some(old) => {
+                let aspects = column_aspects(old, column);
+                if !aspects.is_empty() {
+                    changes.push(ColumnChange {
+                        name: column.name.clone(),
+                        change: ChangeKind::Modified,
+                        before: Some(describe_column(old)),
+                        after: Some(describe_column(column)),
+                        aspects,
+                    });
+                }
+            }
+        }
+    }
+    for column in before {
+        if find_column(after, &column.name).is_none() {
+            changes.push(ColumnChange {
+                name: column.name.clone(),
+                change: ChangeKind::Removed,
+                before: Some(describe_column(column)),
+                after: None,
+                aspects: Vec::new(),
+            });
+        }
+    }
+    changes
+}
+
+/// Text a view or synonym is defined by
+fn table_definition(table: &Table) -> Option<&str> {
+    table.definition.as_deref().or(table.synonym_target.as_deref())
+}
+
+/// Compare the contents of a table present on both sides; `None` if nothing differs
+fn diff_table(before: &TableParts, after: &TableParts) -> Option<TableDiff> {
+    let renamed = !key(&before.table.schema, &before.table.name).eq(&key(&after.table.schema, &after.table.name));
+    let primary_key = match (&before.primary_key, &after.primary_key) {
+        (a, b) if a == b => None,
+        (a, b) => Some(ObjectChange {
+            name: "PRIMARY KEY".to_string(),
+            change: match (a, b) {
+                (None, _) => ChangeKind::Added,
+                (_, None) => ChangeKind::Removed,
+                _ => ChangeKind::Modified,
+            },
+            before: a.clone(),
+            after: b.clone(),
+        }),
+    };
+    let definition_changed = match (table_definition(before.table), table_definition(after.table)) {
+        (Some(a), Some(b)) => !same_sql(a, b),
+        (a, b) => a != b,
+    };
+    
+    let diff = TableDiff {
+        schema: after.table.schema.clone(),
+        name: after.table.name.clone(),
+        object_kind: after.table.object_kind,
+        change: if renamed { ChangeKind::Renamed } else { ChangeKind::Modified },
+        renamed_from: renamed.then(|| qualified(&before.table.schema, &before.table.name)),
+        columns: diff_columns(&before.columns, &after.columns),
+        primary_key,
+        foreign_keys: diff_named(&before.foreign_keys, &after.foreign_keys),
+        indexes: diff_named(&before.indexes, &after.indexes),
+        check_constraints: diff_named(&before.check_constraints, &after.check_constraints),
+        definition_changed,
+    };
+    
+    let unchanged = !renamed
+        && diff.columns.is_empty()
+        && diff.primary_key.is_none()
+        && diff.foreign_keys.is_empty()
+        && diff.indexes.is_empty()
+        && diff.check_constraints.is_empty()
+        && !diff.definition_changed;
+    (!unchanged).then_some(diff)
+}
+
+/// A table only present on one side
+fn added_or_removed(parts: &TableParts, change: ChangeKind) -> TableDiff {
+    TableDiff {
+        schema: parts.table.schema.clone(),
+        name: parts.table.name.clone(),
+        object_kind: parts.table.object_kind,
+        change,
+        renamed_from: None,
+        columns: Vec::new(),
+        primary_key: None,
+        foreign_keys: Vec::new(),
+        indexes: Vec::new(),
+        check_constraints: Vec::new(),
+        definition_changed: false,
+    }
+}
+
+/// Column names and types in order; tables with the same signature hold the same data
+fn signature(parts: &TableParts) -> String {
+    parts.columns.iter()
+        .map(|c| format!("{} {}", c.name.to_lowercase(), column_type(c).to_lowercase()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn diff_tables(before: &SchemaData, after: &SchemaData) -> Vec<TableDiff> {
+    let before = gather(before);
+    let after = gather(after);
+    let mut diffs = Vec::new();
+    
+    // Present on both sides; a table that became a view counts as removed and added
+    let same = |a: &TableParts, b: &TableParts| a.table.object_kind == b.table.object_kind;
+    let mut removed: Vec<&TableParts> = Vec::new();
+    for (table_key, old) in &before {
+        match after.get(table_key) {
+            Some(new) if same(old, new) => diffs.extend(diff_table(old, new)),
+            _ => removed.push(old),
+        }
+    }
+    let mut added: Vec<&TableParts> = after.iter()
+        .filter(|(table_key, new)| !before.get(*table_key).is_some_and(|old| same(old, new)))
+        .map(|(_, new)| new)
+        .collect();
+    
+    // A removed and an added table with identical columns, each the only one of its shape
+    // on its side, are a rename
+    let shape = |parts: &TableParts| format!("{} {}", parts.table.object_kind.as_str(), signature(parts));
+    let mut shapes: HashMap<String, (usize, usize)> = HashMap::new();
+    for parts in removed.iter().filter(|t| !t.columns.is_empty()) {
+        shapes.entry(shape(parts)).or_default().0 += 1;
+    }
+    for parts in added.iter().filter(|t| !t.columns.is_empty()) {
+        shapes.entry(shape(parts)).or_default().1 += 1;
+    }
+    removed.retain(|old| {
+        let old_shape = shape(old);
+        if old.columns.is_empty() || shapes.get(&old_shape) != Some(&(1, 1)) {
+            return true;
+        }
+        match added.iter().position(|new| shape(new) == old_shape) {
+            Some(i) => {
+                let new = added.remove(i);
+                diffs.extend(diff_table(old, new));
+                false
+            }
+            None => true,
+        }
+    });
+    
+    diffs.extend(removed.into_iter().map(|parts| added_or_removed(parts, ChangeKind::Removed)));
+    diffs.extend(added.into_iter().map(|parts| added_or_removed(parts, ChangeKind::Added)));
+    diffs.sort_by_key(|d| key(&d.schema, &d.name));
+    diffs
+}
+
+fn routine_kind_label(kind: RoutineKind) -> &'static str {
+    match kind {
+        RoutineKind::StoredProcedure => "procedure",
+        RoutineKind::ScalarFunction => "function",
+        RoutineKind::TableValuedFunction => "table-valued function",
+        RoutineKind::Trigger => "trigger",
+    }
+}
+
+fn object_kind_label(kind: ObjectKind) -> &'static str {
+    match kind {
+        ObjectKind::BaseTable => "table",
+        ObjectKind::View => "view",
+        ObjectKind::Synonym => "synonym",
+        ObjectKind::TableType => "table type",
+        ObjectKind::ExternalTable => "external table",
+    }
+}
+
+/// Parameter list, compared for routines whose source text is not available
+fn parameter_list(routine: &Routine) -> String {
+    routine.parameters.iter()
+        .map(|p| format!("{} {} {}", p.name.to_lowercase(), p.data_type.to_lowercase(), p.direction.as_str()))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Routines by name and signature, so overloads and same-named triggers on different tables
+/// are compared one by one
+fn routines_by_key(schema: &SchemaData) -> BTreeMap<String, &Routine> {
+    schema.routines.iter()
+        .map(|r| (format!("{} {}", key(&r.schema, &r.name), routine_signature(r).to_lowercase()), r))
+        .collect()
+}
+
+fn diff_routines(before: &SchemaData, after: &SchemaData) -> Vec<RoutineDiff> {
+    let before = routines_by_key(before);
+    let after = routines_by_key(after);
+    let diff = |routine: &Routine, change, before: Option<&Routine>, after: Option<&Routine>| RoutineDiff {
+        schema: routine.schema.clone(),
+        name: routine.name.clone(),
+        routine_kind: routine.routine_kind,
+        change,
+        before: before.and_then(|r| r.definition.clone()),
+        after: after.and_then(|r| r.definition.clone()),
+    };
+    
+    let mut diffs = Vec::new();
+    for (routine_key, old) in &before {
+        match after.get(routine_key) {
+            None => diffs.push(diff(old, ChangeKind::Removed, Some(old), None)),
+            Some(new) => {
+                let changed = old.routine_kind != new.routine_kind
+                    || parameter_list(old) != parameter_list(new)
+                    || match (&old.definition, &new.definition) {
+                        (Some(a), Some(b)) => !same_sql(a, b),
+                        (a, b) => a != b,
+                    };
+                if changed {
+                    diffs.push(diff(new, ChangeKind::Modified, Some(old), Some(new)));
+                }
+            }
+        }
+    }
+    for (routine_key, new) in &after {
+        if !before.contains_key(routine_key) {
+            diffs.push(diff(new, ChangeKind::Added, None, Some(new)));
+        }
+    }
+    diffs.sort_by_key(|d| key(&d.schema, &d.name));
+    diffs
+}
+
+fn marker(change: ChangeKind) -> char {
+    match change {
+        ChangeKind::Added => '+',
+        ChangeKind::Removed => '-',
+        ChangeKind::Renamed | ChangeKind::Modified => '~',
+    }
+}
+
+/// One line for a key, index or constraint change
+fn object_line(label: &str, change: &ObjectChange) -> String {
+    match (change.change, &change.before, &change.after) {
+        (ChangeKind::Modified, Some(before), Some(after)) => {
+            format!("    ~ {} {}: {} -> {}", label, change.name, before, after)
+        }
+        (kind, before, after) => format!(
+            "    {} {} {} {}",
+            marker(kind),
+            label,
+            change.name,
+            after.as_ref().or(before.as_ref()).map(String::as_str).unwrap_or(""),
+        ),
+    }
+}
+
+fn count(n: usize, what: &str) -> String {
+    format!("{} {}{}", n, what, if n == 1 { "" } else { "s" })
+}
+
+fn render_report(tables: &[TableDiff], routines: &[RoutineDiff]) -> String {
+    if tables.is_empty() && routines.is_empty() {
+        return "No differences".to_string();
+    }
+    
+    let tally = |kind: ChangeKind| tables.iter().filter(|t| t.change == kind).count();
+    let mut summary = vec![
+        format!("{} added", count(tally(ChangeKind::Added), "table")),
+        format!("{} removed", tally(ChangeKind::Removed)),
+        format!("{} renamed", tally(ChangeKind::Renamed)),
+        format!("{} modified", tally(ChangeKind::Modified)),
+    ];
+    if !routines.is_empty() {
+        summary.push(format!("{} changed", count(routines.len(), "routine")));
+    }
+    let mut lines = vec![format!("Schema differences: {}", summary.join(", ")), String::new()];
+    
+    for table in tables {
+        let name = qualified(&table.schema, &table.name);
+        let kind = object_kind_label(table.object_kind);
+        lines.push(match &table.renamed_from {
+            Some(from) => format!("~ {} {} (renamed from {})", kind, name, from),
+            None => format!("{} {} {}", marker(table.change), kind, name),
+        });
+        
+        for column in &table.columns {
+            lines.push(match (column.change, &column.before, &column.after) {
+                (ChangeKind::Modified, Some(before), Some(after)) => {
+                    format!("    ~ column {}: {} -> {}", column.name, before, after)
+                }
+                (kind, before, after) => format!(
+                    "    {} column {} {}",
+                    marker(kind),
+                    column.name,
+                    after.as_ref().or(before.as_ref()).map(String::as_str).unwrap_or(""),
+                ),
+            });
+        }
+        if let Some(pk) = &table.primary_key {
+            lines.push(match (&pk.before, &pk.after) {
+                (Some(before), Some(after)) => format!("    ~ {} -> {}", before, after),
+                (before, after) => format!(
+                    "    {} {}",
+                    marker(pk.change),
+                    after.as_ref().or(before.as_ref()).map(String::as_str).unwrap_or(""),
+                ),
+            });
+        }
+        lines.extend(table.foreign_keys.iter().map(|c| object_line("foreign key", c)));
+        lines.extend(table.indexes.iter().map(|c| object_line("index", c)));
+        lines.extend(table.check_constraints.iter().map(|c| object_line("check", c)));
+        if table.definition_changed {
+            lines.push("    ~ definition changed".to_string());
+        }
+    }
+    
+    for routine in routines {
+        lines.push(format!(
+            "{} {} {}",
+            marker(routine.change),
+            routine_kind_label(routine.routine_kind),
+            qualified(&routine.schema, &routine.name),
+        ));
+    }
+    
+    lines.join("\n")
+}
+
+/// Compare two schemas, reporting what changed going from `before` to `after`.
+/// Names are matched case-insensitively.
+pub fn diff_schemas(before: &SchemaData, after: &SchemaData) -> SchemaDiff {
+    let tables = diff_tables(before, after);
+    let routines = diff_routines(before, after);
+    let report = render_report(&tables, &routines);
+    SchemaDiff { tables, routines, report }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::schema::{ForeignKeyColumn, IndexColumn, ParameterDirection, PrimaryKey, RoutineParameter};
+    
+    fn table(name: &str) -> Table {
+        Table {
+            schema: "dbo".to_string(),
+            name: name.to_string(),
+            description: None,
+            object_kind: ObjectKind::BaseTable,
+            definition: None,
+            synonym_target: None,
+            extended_properties: Default::default(),
+        }
+    }
+    
+    fn column(table: &str, name: &str, full_type: &str, ordinal: i32) -> Column {
+        Column {
+            table_schema: "dbo".to_string(),
+            table_name: table.to_string(),
+            name: name.to_string(),
+            data_type: full_type.split('(').next().unwrap().to_string(),
+            full_type: full_type.to_string(),
+            is_nullable: false,
+            is_primary_key: false,
+            is_foreign_key: false,
+            ordinal_position: ordinal,
+            description: None,
+            extended_properties: Default::default(),
+            max_length: None,
+            precision: None,
+            scale: None,
+            collation: None,
+            default_value: None,
+            identity_seed: None,
+            identity_increment: None,
+            computed_definition: None,
+            is_sparse: false,
+            is_rowguid: false,
+            is_filestream: false,
+        }
+    }
+    
+    fn primary_key(table: &str, column: &str) -> PrimaryKey {
+        PrimaryKey { table_schema: "dbo".to_string(), table_name: table.to_string(), column_name: column.to_string() }
+    }
+    
+    fn index(table: &str, name: &str, columns: &[&str]) -> Index {
+        Index {
+            table_schema: "dbo".to_string(),
+            table_name: table.to_string(),
+            name: name.to_string(),
+            index_type: "NONCLUSTERED".to_string(),
+            is_unique: false,
+            is_primary_key: false,
+            is_unique_constraint: false,
+            key_columns: columns.iter().map(|c| IndexColumn { name: c.to_string(), is_descending: false }).collect(),
+            included_columns: Vec::new(),
+            filter_definition: None,
+        }
+    }
+    
+    /// `dbo.Total` overloaded on its input types
+    fn total(inputs: &[&str], definition: &str) -> Routine {
+        Routine {
+            schema: "dbo".to_string(),
+            name: "Total".to_string(),
+            routine_kind: RoutineKind::ScalarFunction,
+            description: None,
+            extended_properties: Default::default(),
+            parent_schema: None,
+            parent_table: None,
+            definition: Some(definition.to_string()),
+            parameters: inputs.iter().enumerate()
+                .map(|(i, data_type)| RoutineParameter {
+                    name: format!("@p{}", i + 1),
+                    data_type: data_type.to_string(),
+                    direction: ParameterDirection::In,
+                    default_value: None,
+                    ordinal_position: i as i32 + 1,
+                })
+                .collect(),
+        }
+    }
+    
+    /// Customers, their orders and order lines; the lines have no primary key index
+    fn base() -> SchemaData {
+        let mut name = column("Customer", "Name", "nvarchar(50)", 2);
+        name.is_nullable = true;
+        SchemaData {
+            tables: vec![table("Customer"), table("Orders"), table("OrderLine")],
+            columns: vec![
+                column("Customer", "Id", "int", 1),
+                name,
+                column("Customer", "Email", "varchar(200)", 3),
+                column("Orders", "Id", "int", 1),
+                column("Orders", "CustomerId", "int", 2),
+                column("OrderLine", "OrderId", "int", 1),
+                column("OrderLine", "Line", "int", 2),
+            ],
+            primary_keys: vec![
+                primary_key("Customer", "Id"),
+                primary_key("Orders", "Id"),
+                primary_key("OrderLine", "OrderId"),
+                primary_key("OrderLine", "Line"),
+            ],
+            foreign_keys: vec![ForeignKey {
+                constraint_name: "FK_Orders_Customer".to_string(),
+                parent_schema: "dbo".to_string(),
+                parent_table: "Orders".to_string(),
+                referenced_schema: "dbo".to_string(),
+                referenced_table: "Customer".to_string(),
+                columns: vec![ForeignKeyColumn { parent_column: "CustomerId".to_string(), referenced_column: "Id".to_string() }],
+                delete_referential_action: "NO_ACTION".to_string(),
+                update_referential_action: "NO_ACTION".to_string(),
+                is_disabled: false,
+                is_not_trusted: false,
+            }],
+            indexes: vec![index("Customer", "IX_Customer_Email", &["Email"])],
+            routines: vec![
+                total(&["int"], "CREATE FUNCTION dbo.Total(@p1 int) RETURNS money AS BEGIN RETURN 0 END"),
+                total(&["int", "money"], "CREATE FUNCTION dbo.Total(@p1 int, @p2 money) RETURNS money AS BEGIN RETURN @p2 END"),
+                Routine {
+                    schema: "dbo".to_string(),
+                    name: "GetCustomer".to_string(),
+                    routine_kind: RoutineKind::StoredProcedure,
+                    description: None,
+                    extended_properties: Default::default(),
+                    parent_schema: None,
+                    parent_table: None,
+                    definition: Some("CREATE PROCEDURE dbo.GetCustomer AS SELECT * FROM dbo.Customer".to_string()),
+                    parameters: Vec::new(),
+                },
+            ],
+            ..Default::default()
+        }
+    }
+    
+    fn column_mut<'a>(schema: &'a mut SchemaData, table: &str, name: &str) -> &'a mut Column {
+        schema.columns.iter_mut().find(|c| c.table_name == table && c.name == name).unwrap()
+    }
+    
+    fn rename_orders(schema: &mut SchemaData) {
+        schema.tables.iter_mut().filter(|t| t.name == "Orders").for_each(|t| t.name = "Purchases".to_string());
+        schema.columns.iter_mut().filter(|c| c.table_name == "Orders").for_each(|c| c.table_name = "Purchases".to_string());
+        schema.primary_keys.iter_mut().filter(|k| k.table_name == "Orders").for_each(|k| k.table_name = "Purchases".to_string());
+        schema.foreign_keys[0].parent_table = "Purchases".to_string();
+    }
+    
+    #[test]
+    fn changes_are_reported() {
+        type Change = fn(&mut SchemaData);
+        let cases: &[(&str, Change, &[&str])] = &[
+            ("nothing", |_| {}, &[]),
+            ("table added", |s| {
+                s.tables.push(table("Audit"));
+                s.columns.push(column("Audit", "At", "datetime2", 1));
+            }, &["+ table dbo.Audit"]),
+            ("table removed", |s| {
+                s.tables.retain(|t| t.name != "OrderLine");
+                s.columns.retain(|c| c.table_name != "OrderLine");
+                s.primary_keys.retain(|k| k.table_name != "OrderLine");
+            }, &["- table dbo.OrderLine"]),
+            ("table renamed", rename_orders, &["~ table dbo.Purchases (renamed from dbo.Orders)"]),
+            ("renamed table also changed", |s| {
+                rename_orders(s);
+                s.foreign_keys[0].delete_referential_action = "CASCADE".to_string();
+            }, &[
+                "~ table dbo.Purchases (renamed from dbo.Orders)",
+                "    ~ foreign key FK_Orders_Customer: (CustomerId) REFERENCES dbo.Customer (Id) -> (CustomerId) REFERENCES dbo.Customer (Id) ON DELETE CASCADE",
+            ]),
+            ("name casing only", |s| s.tables[0].name = "CUSTOMER".to_string(), &[]),
+            ("column added", |s| s.columns.push(column("Customer", "Phone", "varchar(20)", 4)), &[
+                "~ table dbo.Customer",
+                "    + column Phone varchar(20) NOT NULL",
+            ]),
+            ("column removed", |s| s.columns.retain(|c| c.name != "Email"), &[
+                "~ table dbo.Customer",
+                "    - column Email varchar(200) NOT NULL",
+            ]),
+            ("column type", |s| column_mut(s, "Customer", "Name").full_type = "nvarchar(100)".to_string(), &[
+                "~ table dbo.Customer",
+                "    ~ column Name: nvarchar(50) NULL -> nvarchar(100) NULL",
+            ]),
+            ("type casing only", |s| column_mut(s, "Customer", "Name").full_type = "NVARCHAR(50)".to_string(), &[]),
+            ("nullability", |s| column_mut(s, "Customer", "Name").is_nullable = false, &[
+                "~ table dbo.Customer",
+                "    ~ column Name: nvarchar(50) NULL -> nvarchar(50) NOT NULL",
+            ]),
+            ("default", |s| column_mut(s, "Customer", "Name").default_value = Some("(N'')".to_string()), &[
+                "~ table dbo.Customer",
+                "    ~ column Name: nvarchar(50) NULL -> nvarchar(50) NULL DEFAULT (N'')",
+            ]),
+            ("foreign key removed", |s| s.foreign_keys.clear(), &[
+                "~ table dbo.Orders",
+                "    - foreign key FK_Orders_Customer (CustomerId) REFERENCES dbo.Customer (Id)",
+            ]),
+            ("foreign key under a generated name", |s| s.foreign_keys[0].constraint_name = "FK__Orders__1A2B".to_string(), &[]),
+            ("foreign key disabled", |s| s.foreign_keys[0].is_disabled = true, &[
+                "~ table dbo.Orders",
+                "    ~ foreign key FK_Orders_Customer: (CustomerId) REFERENCES dbo.Customer (Id) -> (CustomerId) REFERENCES dbo.Customer (Id) DISABLED",
+            ]),
+            ("index added", |s| s.indexes.push(index("Orders", "IX_Orders_Customer", &["CustomerId"])), &[
+                "~ table dbo.Orders",
+                "    + index IX_Orders_Customer NONCLUSTERED (CustomerId)",
+            ]),
+            ("index changed", |s| s.indexes[0].included_columns.push("Name".to_string()), &[
+                "~ table dbo.Customer",
+                "    ~ index IX_Customer_Email: NONCLUSTERED (Email) -> NONCLUSTERED (Email) INCLUDE (Name)",
+            ]),
+            ("index filtered and unique", |s| {
+                s.indexes[0].is_unique = true;
+                s.indexes[0].filter_definition = Some("Email IS NOT NULL".to_string());
+            }, &[
+                "~ table dbo.Customer",
+                "    ~ index IX_Customer_Email: NONCLUSTERED (Email) -> UNIQUE NONCLUSTERED (Email) WHERE Email IS NOT NULL",
+            ]),
+            ("primary key listed in another order", |s| s.primary_keys.reverse(), &[]),
+            ("primary key widened", |s| s.primary_keys.push(primary_key("Orders", "CustomerId")), &[
+                "~ table dbo.Orders",
+                "    ~ PRIMARY KEY (Id) -> PRIMARY KEY (Id, CustomerId)",
+            ]),
+            ("routine changed", |s| {
+                s.routines[2].definition = Some("CREATE PROCEDURE dbo.GetCustomer AS SELECT Id FROM dbo.Customer".to_string());
+            }, &["~ procedure dbo.GetCustomer"]),
+            ("one of two overloads changed", |s| {
+                s.routines[1].definition = Some("CREATE FUNCTION dbo.Total(@p1 int, @p2 money) RETURNS money AS BEGIN RETURN @p2 * 2 END".to_string());
+            }, &["~ function dbo.Total"]),
+            ("one of two overloads dropped", |s| {
+                s.routines.remove(0);
+            }, &["- function dbo.Total"]),
+            ("routine layout only", |s| {
+                s.routines[2].definition = Some("CREATE PROCEDURE dbo.GetCustomer\nAS\n    SELECT * FROM dbo.Customer".to_string());
+            }, &[]),
+        ];
+        
+        for (name, change, expected) in cases {
+            let before = base();
+            let mut after = base();
+            change(&mut after);
+            
+            let diff = diff_schemas(&before, &after);
+            if expected.is_empty() {
+                assert_eq!(diff.report, "No differences", "{}", name);
+            } else {
+                let lines: Vec<&str> = diff.report.lines().skip(2).collect();
+                assert_eq!(lines, *expected, "{}", name);
+            }
+        }
+    }
+    
+    #[test]
+    fn going_back_reverses_the_changes() {
+        let before = base();
+        let mut after = base();
+        rename_orders(&mut after);
+        column_mut(&mut after, "Customer", "Name").is_nullable = false;
+        after.tables.push(table("Audit"));
+        
+        let forward = diff_schemas(&before, &after);
+        let changes: Vec<(&str, ChangeKind)> = forward.tables.iter().map(|t| (t.name.as_str(), t.change)).collect();
+        assert_eq!(changes, [
+            ("Audit", ChangeKind::Added),
+            ("Customer", ChangeKind::Modified),
+            ("Purchases", ChangeKind::Renamed),
+        ]);
+        assert_eq!(forward.tables[1].columns[0].aspects, [ColumnAspect::Nullability]);
+        assert!(forward.report.starts_with("Schema differences: 1 table added, 0 removed, 1 renamed, 1 modified"));
+        
+        let backward = diff_schemas(&after, &before);
+        let changes: Vec<(&str, ChangeKind)> = backward.tables.iter().map(|t| (t.name.as_str(), t.change)).collect();
+        assert_eq!(changes, [
+            ("Audit", ChangeKind::Removed),
+            ("Customer", ChangeKind::Modified),
+            ("Orders", ChangeKind::Renamed),
+        ]);
+        assert_eq!(backward.tables[2].renamed_from.as_deref(), Some("dbo.Purchases"));
+    }
+}
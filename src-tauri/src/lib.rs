@@ -1,8 +1,10 @@
 mod commands;
 mod config;
 mod db;
+mod diff;
+mod scheduler;
 
-use commands::{connection, schema, clipboard};
+use commands::{connection, schema, clipboard, settings};
 use tauri::Manager;
 use tauri::menu::{Menu, MenuItem};
 use tauri::tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent};
@@ -103,14 +105,22 @@ pub fn run() {
             if let Some(window) = app.get_webview_window("main") {
                 let window_clone = window.clone();
                 window.on_window_event(move |event| {
-                    if let tauri::WindowEvent::CloseRequested { api, .. } = event {
-                        // Prevent the window from closing, just hide it
-                        api.prevent_close();
-                        let _ = window_clone.hide();
+                    match event {
+                        tauri::WindowEvent::CloseRequested { api, .. } => {
+                            // Prevent the window from closing, just hide it
+                            api.prevent_close();
+                            let _ = window_clone.hide();
+                        }
+                        // Shown windows are focused; a stale schema is refreshed on the way in
+                        tauri::WindowEvent::Focused(true) => scheduler::wake(true),
+                        _ => {}
                     }
                 });
             }
             
+            // Refresh schemas in the background when auto-refresh is on
+            scheduler::start(app.handle().clone());
+            
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -122,10 +132,25 @@ pub fn run() {
             connection::delete_connection,
             connection::set_default_connection,
             connection::generate_connection_id,
+            connection::parse_connection_string,
+            connection::export_connection_string,
+            connection::save_password,
+            connection::delete_saved_password,
+            connection::has_saved_password,
             // Schema commands
             schema::index_schema,
             schema::get_schema,
             schema::refresh_schema,
+            schema::cancel_indexing,
+            schema::list_schema_snapshots,
+            schema::get_schema_snapshot,
+            schema::prune_schema_snapshots,
+            schema::diff_schemas,
+            schema::get_routines,
+            schema::get_refresh_status,
+            // Settings commands
+            settings::get_settings,
+            settings::save_settings,
             // Clipboard commands
             clipboard::copy_to_clipboard,
             // Window commands
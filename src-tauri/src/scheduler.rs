@@ -0,0 +1,194 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::Notify;
+use tokio::time::Instant;
+use crate::commands::{connection, schema};
+use crate::config::{self, Connection, Settings};
+use crate::db::cache;
+
+/// Event carrying a `RefreshStatus` whenever a background refresh starts or ends
+pub const REFRESH_EVENT: &str = "schema-refresh";
+
+/// How often the schedule is checked when nothing wakes it earlier
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Wait before retrying a failed refresh; doubles with each further failure, up to the
+/// refresh interval
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RefreshState {
+    /// No background refresh has run since the app started
+    Idle,
+    Refreshing,
+    Updated,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshStatus {
+    pub connection_id: String,
+    pub state: RefreshState,
+    /// When the cached schema was last refreshed, by any means (ISO 8601, UTC)
+    pub refreshed_at: Option<String>,
+    pub error: Option<String>,
+    /// Seconds until a failed refresh is tried again
+    pub retry_in_secs: Option<u64>,
+}
+
+/// Latest status of each connection refreshed in the background
+static STATUS: LazyLock<Mutex<HashMap<String, RefreshStatus>>> = LazyLock::new(Default::default);
+
+static WAKE: LazyLock<Notify> = LazyLock::new(Notify::new);
+
+/// Set when the window was shown since the last check
+static SHOWN: AtomicBool = AtomicBool::new(false);
+
+/// Consecutive failures of a connection and when it may be tried again
+struct Backoff {
+    failures: u32,
+    retry_at: Instant,
+}
+
+/// Start refreshing schemas in the background, as the settings ask for
+pub fn start(app: AppHandle) {
+    tauri::async_runtime::spawn(run(app));
+}
+
+/// Check the schedule now rather than at the next tick, e.g. because the settings changed.
+/// `shown` tells that the window was just shown.
+pub fn wake(shown: bool) {
+    if shown {
+        SHOWN.store(true, Ordering::Relaxed);
+    }
+    WAKE.notify_one();
+}
+
+/// The connection's refresh status, with the cache's current refresh time
+pub fn status(connection_id: &str) -> RefreshStatus {
+    let known = STATUS.lock().unwrap().get(connection_id).cloned();
+    let mut status = known.unwrap_or_else(|| RefreshStatus {
+        connection_id: connection_id.to_string(),
+        state: RefreshState::Idle,
+        refreshed_at: None,
+        error: None,
+        retry_in_secs: None,
+    });
+    status.refreshed_at = cache::last_refresh(connection_id).ok().flatten().map(|(at, _)| at);
+    status
+}
+
+async fn run(app: AppHandle) {
+    let mut backoff: HashMap<String, Backoff> = HashMap::new();
+    let mut starting = true;
+    
+    loop {
+        let shown = SHOWN.swap(false, Ordering::Relaxed);
+        match config::load_config() {
+            Ok(app_config) if app_config.settings.auto_refresh => {
+                for connection in &app_config.connections {
+                    if is_due(connection, &app_config.settings, starting, shown, &backoff) {
+                        refresh(&app, connection, &app_config.settings, &mut backoff).await;
+                    }
+                }
+            }
+            Ok(_) => backoff.clear(),
+            Err(e) => eprintln!("Warning: Auto-refresh could not read the settings: {}", e),
+        }
+        starting = false;
+        
+        tokio::select! {
+            _ = tokio::time::sleep(CHECK_INTERVAL) => {}
+            _ = WAKE.notified() => {}
+        }
+    }
+}
+
+fn is_due(connection: &Connection, settings: &Settings, starting: bool, shown: bool, backoff: &HashMap<String, Backoff>) -> bool {
+    if backoff.get(&connection.id).is_some_and(|b| Instant::now() < b.retry_at) {
+        return false;
+    }
+    // Leave connections being indexed from the UI alone
+    if schema::is_indexing(&connection.id) {
+        return false;
+    }
+    
+    let age = match cache::last_refresh(&connection.id) {
+        Ok(Some((_, age))) => age,
+        // Never indexed; the first refresh builds the cache
+        Ok(None) => return true,
+        Err(e) => {
+            eprintln!("Warning: Auto-refresh could not read the cache: {}", e);
+            return false;
+        }
+    };
+    
+    (starting && settings.refresh_on_start())
+        || (shown && settings.refresh_when_shown_after().is_some_and(|after| age >= after))
+        || age >= settings.refresh_interval()
+}
+
+async fn refresh(app: &AppHandle, connection: &Connection, settings: &Settings, backoff: &mut HashMap<String, Backoff>) {
+    // Only stored credentials are used; nobody is there to be asked for a password
+    let password = if connection.auth_type == "sql" {
+        match connection::load_saved_password(&connection.id).await {
+            Ok(Some(p)) => Some(p),
+            Ok(None) => {
+                // Back off like any failure rather than trying again every check
+                fail(app, connection, settings, backoff, "No saved password; connect once from the app to save it".to_string());
+                return;
+            }
+            Err(e) => {
+                fail(app, connection, settings, backoff, format!("Failed to read saved password: {}", e));
+                return;
+            }
+        }
+    } else {
+        None
+    };
+    
+    println!("Auto-refresh: {}", connection.name);
+    publish(app, &connection.id, RefreshState::Refreshing, None, None);
+    match schema::refresh_cache(connection, password.as_deref(), false).await {
+        Ok(_) => {
+            backoff.remove(&connection.id);
+            publish(app, &connection.id, RefreshState::Updated, None, None);
+        }
+        Err(e) => fail(app, connection, settings, backoff, e),
+    }
+}
+
+fn fail(app: &AppHandle, connection: &Connection, settings: &Settings, backoff: &mut HashMap<String, Backoff>, error: String) {
+    eprintln!("Auto-refresh of {} failed: {}", connection.name, error);
+    
+    let entry = backoff.entry(connection.id.clone()).or_insert(Backoff { failures: 0, retry_at: Instant::now() });
+    entry.failures += 1;
+    let delay = FIRST_RETRY_DELAY
+        .saturating_mul(2u32.saturating_pow(entry.failures - 1))
+        .min(settings.refresh_interval());
+    entry.retry_at = Instant::now() + delay;
+    
+    publish(app, &connection.id, RefreshState::Failed, Some(error), Some(delay.as_secs()));
+}
+
+/// Record the connection's new status and tell the UI
+fn publish(app: &AppHandle, connection_id: &str, state: RefreshState, error: Option<String>, retry_in_secs: Option<u64>) {
+    let status = RefreshStatus {
+        connection_id: connection_id.to_string(),
+        state,
+        refreshed_at: cache::last_refresh(connection_id).ok().flatten().map(|(at, _)| at),
+        error,
+        retry_in_secs,
+    };
+    STATUS.lock().unwrap().insert(connection_id.to_string(), status.clone());
+    
+    if let Err(e) = app.emit(REFRESH_EVENT, status) {
+        eprintln!("Warning: Failed to emit refresh status: {}", e);
+    }
+}
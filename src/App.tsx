@@ -6,6 +6,7 @@ import { Sidebar } from './components/Sidebar';
 import { ConnectionForm } from './components/ConnectionForm';
 import { Settings } from './components/Settings';
 import { KeyboardShortcutsHelp } from './components/KeyboardShortcutsHelp';
+import { RefreshIndicator } from './components/RefreshIndicator';
 import { ToastManager, useToast } from './components/Toast';
 import { useSearch } from './hooks/useSearch';
 import { useSearchStore } from './stores/searchStore';
@@ -14,7 +15,7 @@ import { useHistoryStore } from './stores/historyStore';
 import { useJoinQueryStore } from './stores/joinQueryStore';
 import { useSettingsStore } from './stores/settingsStore';
 import { generateSqlFromResult } from './lib/sqlGenerator';
-import { copyToClipboard, hideWindow } from './lib/tauri';
+import { copyToClipboard, hideWindow, onSchemaRefresh } from './lib/tauri';
 import type { SearchResult, Connection } from './lib/types';
 import './App.css';
 
@@ -37,7 +38,10 @@ function App() {
     isConnected,
     currentConnection,
     schema,
+    refreshStatus,
     loadConnections,
+    loadRefreshStatus,
+    applyRefreshStatus,
     connect
   } = useConnectionStore();
 
@@ -72,6 +76,18 @@ function App() {
     initApp();
   }, [loadConnections]);
 
+  // Follow background schema refreshes
+  useEffect(() => {
+    const unlisten = onSchemaRefresh(applyRefreshStatus);
+    return () => {
+      unlisten.then((stop) => stop());
+    };
+  }, [applyRefreshStatus]);
+
+  useEffect(() => {
+    loadRefreshStatus();
+  }, [currentConnection, loadRefreshStatus]);
+
   // Update search store when schema changes
   useEffect(() => {
     if (schema) {
@@ -267,9 +283,12 @@ function App() {
           </div>
           <div className="flex items-center gap-2">
             {currentConnection && (
-              <span className="text-sm text-gray-500 dark:text-gray-400">
-                {currentConnection.name}
-              </span>
+              <>
+                <RefreshIndicator status={refreshStatus} />
+                <span className="text-sm text-gray-500 dark:text-gray-400">
+                  {currentConnection.name}
+                </span>
+              </>
             )}
             {view === 'connection' ? (
               <button
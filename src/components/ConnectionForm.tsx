@@ -1,6 +1,6 @@
 import { useState } from 'react';
-import type { Connection } from '../lib/types';
-import { testConnection, saveConnection } from '../lib/tauri';
+import { isLocalProvider, type AuthType, type Connection, type KnownHostsPolicy, type Provider, type SshTunnel } from '../lib/types';
+import { testConnection, saveConnection, parseConnectionString } from '../lib/tauri';
 
 interface ConnectionFormProps {
     onConnectionSaved?: (connection: Connection, password?: string) => void;
@@ -9,19 +9,49 @@ interface ConnectionFormProps {
 }
 
 export function ConnectionForm({ onConnectionSaved, existingConnection, onBack }: ConnectionFormProps) {
+    const [provider, setProvider] = useState<Provider>(existingConnection?.provider || 'sqlServer');
     const [server, setServer] = useState(existingConnection?.server || '');
     const [database, setDatabase] = useState(existingConnection?.database || '');
-    const [authType, setAuthType] = useState<'windows' | 'sql'>(
-        (existingConnection?.authType as 'windows' | 'sql') || 'sql'  // Default to SQL auth
+    const [filePath, setFilePath] = useState(existingConnection?.filePath || '');
+    const [authType, setAuthType] = useState<AuthType>(
+        existingConnection?.authType || 'sql'  // Default to SQL auth
+    );
+    const [tokenSource, setTokenSource] = useState<'file' | 'command'>(
+        existingConnection?.accessTokenFile ? 'file' : 'command'
+    );
+    const [tokenValue, setTokenValue] = useState(
+        existingConnection?.accessTokenFile || existingConnection?.accessTokenCommand || ''
     );
     const [username, setUsername] = useState(existingConnection?.username || '');
     const [password, setPassword] = useState('');
     const [connectionName, setConnectionName] = useState(existingConnection?.name || '');
+    // Settings without a form field (port, encryption, ...) taken from a pasted connection string
+    const [imported, setImported] = useState<Connection | undefined>();
+    const [showImport, setShowImport] = useState(false);
+    const [useTunnel, setUseTunnel] = useState(!!existingConnection?.sshTunnel);
+    const [tunnel, setTunnel] = useState<SshTunnel>(
+        existingConnection?.sshTunnel || { host: '', user: '', knownHosts: 'strict' }
+    );
+    const [importText, setImportText] = useState('');
+    const [connectTimeoutSecs, setConnectTimeoutSecs] = useState(existingConnection?.connectTimeoutSecs);
+    const [queryTimeoutSecs, setQueryTimeoutSecs] = useState(existingConnection?.queryTimeoutSecs);
 
     const [isLoading, setIsLoading] = useState(false);
     const [error, setError] = useState<string | null>(null);
     const [success, setSuccess] = useState<string | null>(null);
 
+    const isFile = isLocalProvider(provider);
+    const hasTunnel = provider === 'sqlServer' && useTunnel;
+    const isComplete = (isFile ? !!filePath.trim() : !!server && !!database)
+        && (authType !== 'azureToken' || !!tokenValue.trim())
+        && (!hasTunnel || (!!tunnel.host.trim() && !!tunnel.user.trim()));
+
+    // Only the selected token source is kept
+    const tokenFields = {
+        accessTokenFile: authType === 'azureToken' && tokenSource === 'file' ? tokenValue.trim() : undefined,
+        accessTokenCommand: authType === 'azureToken' && tokenSource === 'command' ? tokenValue.trim() : undefined,
+    };
+
     const handleTestConnection = async () => {
         setIsLoading(true);
         setError(null);
@@ -29,13 +59,30 @@ export function ConnectionForm({ onConnectionSaved, existingConnection, onBack }
 
         try {
             const result = await testConnection(
-                server,
-                database,
-                authType,
-                authType === 'sql' ? username : undefined,
+                {
+                    ...existingConnection,
+                    ...imported,
+                    id: existingConnection?.id || '',
+                    name: connectionName,
+                    provider,
+                    server,
+                    database,
+                    filePath: isFile ? filePath.trim() : undefined,
+                    authType,
+                    username: authType === 'sql' ? username : undefined,
+                    ...tokenFields,
+                    sshTunnel: hasTunnel ? tunnel : undefined,
+                    connectTimeoutSecs,
+                    queryTimeoutSecs,
+                    isDefault: false,
+                },
                 authType === 'sql' ? password : undefined
             );
-            setSuccess(result);
+            if (result.success) {
+                setSuccess(`${result.message} (${result.latencyMs} ms)`);
+            } else {
+                setError(result.message);
+            }
         } catch (err) {
             setError(err as string);
         } finally {
@@ -49,12 +96,20 @@ export function ConnectionForm({ onConnectionSaved, existingConnection, onBack }
 
         try {
             const connection: Connection = {
+                ...existingConnection,
+                ...imported,
                 id: existingConnection?.id || crypto.randomUUID(),
-                name: connectionName || `${server}/${database}`,
+                name: connectionName || (isFile ? filePath.trim().split(/[\\/]/).pop() || filePath : `${server}/${database}`),
+                provider,
                 server,
                 database,
+                filePath: isFile ? filePath.trim() : undefined,
                 authType,
                 username: authType === 'sql' ? username : undefined,
+                ...tokenFields,
+                sshTunnel: hasTunnel ? tunnel : undefined,
+                connectTimeoutSecs,
+                queryTimeoutSecs,
                 isDefault: existingConnection?.isDefault ?? true,
             };
 
@@ -68,6 +123,37 @@ export function ConnectionForm({ onConnectionSaved, existingConnection, onBack }
         }
     };
 
+    const handleImport = async () => {
+        setError(null);
+        setSuccess(null);
+
+        try {
+            const result = await parseConnectionString(importText);
+            const parsed = result.connection;
+            setImported(parsed);
+            setProvider(parsed.provider || 'sqlServer');
+            setServer(parsed.server);
+            setDatabase(parsed.database);
+            setAuthType(parsed.authType);
+            setUsername(parsed.username || '');
+            setPassword(result.password || '');
+            if (parsed.accessTokenCommand) {
+                setTokenSource('command');
+                setTokenValue(parsed.accessTokenCommand);
+            }
+            if (!connectionName) setConnectionName(parsed.name);
+
+            const ignored = Object.keys(result.options);
+            setSuccess(ignored.length > 0
+                ? `Connection string imported; ignored ${ignored.join(', ')}`
+                : 'Connection string imported');
+            setShowImport(false);
+            setImportText('');
+        } catch (err) {
+            setError(err as string);
+        }
+    };
+
     // Common input props to disable autocorrect/autocomplete
     const inputProps = {
         autoComplete: 'off',
@@ -101,6 +187,42 @@ export function ConnectionForm({ onConnectionSaved, existingConnection, onBack }
             </div>
 
             <div className="space-y-4">
+                {showImport ? (
+                    <div>
+                        <label className="block text-sm font-medium mb-1">Connection String</label>
+                        <textarea
+                            value={importText}
+                            onChange={(e) => setImportText(e.target.value)}
+                            placeholder="Server=tcp:host,1433;Database=...;User Id=...  (ADO.NET, JDBC or ODBC)"
+                            rows={3}
+                            className="w-full px-3 py-2 border rounded-lg font-mono text-sm focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                            {...inputProps}
+                        />
+                        <div className="flex gap-2 mt-2">
+                            <button
+                                onClick={handleImport}
+                                disabled={!importText.trim()}
+                                className="px-3 py-1 text-sm bg-blue-600 text-white rounded-lg hover:bg-blue-700 disabled:opacity-50 disabled:cursor-not-allowed"
+                            >
+                                Import
+                            </button>
+                            <button
+                                onClick={() => setShowImport(false)}
+                                className="px-3 py-1 text-sm text-gray-600 dark:text-gray-400 hover:text-gray-900 dark:hover:text-white"
+                            >
+                                Cancel
+                            </button>
+                        </div>
+                    </div>
+                ) : (
+                    <button
+                        onClick={() => setShowImport(true)}
+                        className="text-sm text-blue-600 dark:text-blue-400 hover:underline"
+                    >
+                        Import from connection string…
+                    </button>
+                )}
+
                 <div>
                     <label className="block text-sm font-medium mb-1">Connection Name</label>
                     <input
@@ -114,41 +236,113 @@ export function ConnectionForm({ onConnectionSaved, existingConnection, onBack }
                 </div>
 
                 <div>
-                    <label className="block text-sm font-medium mb-1">Server</label>
-                    <input
-                        type="text"
-                        value={server}
-                        onChange={(e) => setServer(e.target.value)}
-                        placeholder="localhost\SQLEXPRESS"
-                        className="w-full px-3 py-2 border rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
-                        {...inputProps}
-                    />
-                </div>
-
-                <div>
-                    <label className="block text-sm font-medium mb-1">Database</label>
-                    <input
-                        type="text"
-                        value={database}
-                        onChange={(e) => setDatabase(e.target.value)}
-                        placeholder="DatabaseName"
-                        className="w-full px-3 py-2 border rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
-                        {...inputProps}
-                    />
-                </div>
-
-                <div>
-                    <label className="block text-sm font-medium mb-1">Authentication</label>
+                    <label className="block text-sm font-medium mb-1">Database Type</label>
                     <select
-                        value={authType}
-                        onChange={(e) => setAuthType(e.target.value as 'windows' | 'sql')}
+                        value={provider}
+                        onChange={(e) => {
+                            const next = e.target.value as Provider;
+                            setProvider(next);
+                            // Integrated auth is SQL Server only; local files need none
+                            if (isLocalProvider(next)) setAuthType('none');
+                            else if (next !== 'sqlServer' || authType === 'none') setAuthType('sql');
+                        }}
                         className="w-full px-3 py-2 border rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
                     >
-                        <option value="sql">SQL Server Authentication</option>
-                        <option value="windows">Windows Authentication</option>
+                        <option value="sqlServer">SQL Server</option>
+                        <option value="postgres">PostgreSQL</option>
+                        <option value="mysql">MySQL / MariaDB</option>
+                        <option value="sqlite">SQLite file</option>
+                        <option value="ddlScripts">SQL scripts folder</option>
+                        <option value="dacpac">Dacpac / bacpac file</option>
                     </select>
                 </div>
 
+                {isFile ? (
+                    <div>
+                        <label className="block text-sm font-medium mb-1">
+                            {provider === 'sqlite' ? 'Database File' : provider === 'dacpac' ? 'Package File' : 'Scripts Folder'}
+                        </label>
+                        <input
+                            type="text"
+                            value={filePath}
+                            onChange={(e) => setFilePath(e.target.value)}
+                            placeholder={provider === 'sqlite' ? '/path/to/database.sqlite' : provider === 'dacpac' ? '/path/to/bin/Release/Database.dacpac' : '/path/to/repo/schema'}
+                            className="w-full px-3 py-2 border rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                            {...inputProps}
+                        />
+                    </div>
+                ) : (
+                    <>
+                        <div>
+                            <label className="block text-sm font-medium mb-1">Server</label>
+                            <input
+                                type="text"
+                                value={server}
+                                onChange={(e) => setServer(e.target.value)}
+                                placeholder={provider === 'postgres' ? 'localhost:5432' : provider === 'mysql' ? 'localhost:3306' : 'localhost\\SQLEXPRESS'}
+                                className="w-full px-3 py-2 border rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                                {...inputProps}
+                            />
+                        </div>
+
+                        <div>
+                            <label className="block text-sm font-medium mb-1">Database</label>
+                            <input
+                                type="text"
+                                value={database}
+                                onChange={(e) => setDatabase(e.target.value)}
+                                placeholder="DatabaseName"
+                                className="w-full px-3 py-2 border rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                                {...inputProps}
+                            />
+                        </div>
+                    </>
+                )}
+
+                {provider === 'sqlServer' && (
+                    <div>
+                        <label className="block text-sm font-medium mb-1">Authentication</label>
+                        <select
+                            value={authType}
+                            onChange={(e) => setAuthType(e.target.value as AuthType)}
+                            className="w-full px-3 py-2 border rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                        >
+                            <option value="sql">SQL Server Authentication</option>
+                            <option value="windows">Windows Authentication</option>
+                            <option value="kerberos">Kerberos (kinit ticket)</option>
+                            <option value="azureToken">Azure AD Access Token</option>
+                        </select>
+                    </div>
+                )}
+
+                {authType === 'azureToken' && (
+                    <div>
+                        <div className="flex items-center justify-between mb-1">
+                            <label className="block text-sm font-medium">
+                                {tokenSource === 'file' ? 'Token File' : 'Token Command'}
+                            </label>
+                            <select
+                                value={tokenSource}
+                                onChange={(e) => setTokenSource(e.target.value as 'file' | 'command')}
+                                className="text-sm px-2 py-1 border rounded-lg"
+                            >
+                                <option value="command">From command</option>
+                                <option value="file">From file</option>
+                            </select>
+                        </div>
+                        <input
+                            type="text"
+                            value={tokenValue}
+                            onChange={(e) => setTokenValue(e.target.value)}
+                            placeholder={tokenSource === 'file'
+                                ? '/path/to/access-token.txt'
+                                : 'az account get-access-token --resource https://database.windows.net/'}
+                            className="w-full px-3 py-2 border rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                            {...inputProps}
+                        />
+                    </div>
+                )}
+
                 {authType === 'sql' && (
                     <>
                         <div>
@@ -180,6 +374,92 @@ export function ConnectionForm({ onConnectionSaved, existingConnection, onBack }
                     </>
                 )}
 
+                {provider === 'sqlServer' && (
+                    <div>
+                        <label className="flex items-center gap-2 text-sm font-medium">
+                            <input
+                                type="checkbox"
+                                checked={useTunnel}
+                                onChange={(e) => setUseTunnel(e.target.checked)}
+                            />
+                            Connect through SSH tunnel
+                        </label>
+                        {useTunnel && (
+                            <div className="mt-2 pl-6 space-y-2">
+                                <div className="flex gap-2">
+                                    <input
+                                        type="text"
+                                        value={tunnel.host}
+                                        onChange={(e) => setTunnel({ ...tunnel, host: e.target.value })}
+                                        placeholder="bastion.example.com"
+                                        className="flex-1 px-3 py-2 border rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                                        {...inputProps}
+                                    />
+                                    <input
+                                        type="number"
+                                        value={tunnel.port ?? ''}
+                                        onChange={(e) => setTunnel({ ...tunnel, port: e.target.value ? Number(e.target.value) : undefined })}
+                                        placeholder="22"
+                                        className="w-20 px-3 py-2 border rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                                    />
+                                </div>
+                                <input
+                                    type="text"
+                                    value={tunnel.user}
+                                    onChange={(e) => setTunnel({ ...tunnel, user: e.target.value })}
+                                    placeholder="SSH user"
+                                    className="w-full px-3 py-2 border rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                                    {...inputProps}
+                                />
+                                <input
+                                    type="text"
+                                    value={tunnel.keyPath || ''}
+                                    onChange={(e) => setTunnel({ ...tunnel, keyPath: e.target.value || undefined })}
+                                    placeholder="~/.ssh/id_ed25519 (empty to use the SSH agent)"
+                                    className="w-full px-3 py-2 border rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                                    {...inputProps}
+                                />
+                                <select
+                                    value={tunnel.knownHosts || 'strict'}
+                                    onChange={(e) => setTunnel({ ...tunnel, knownHosts: e.target.value as KnownHostsPolicy })}
+                                    className="w-full px-3 py-2 border rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                                >
+                                    <option value="strict">Host must be in known_hosts</option>
+                                    <option value="acceptNew">Add new hosts to known_hosts</option>
+                                    <option value="acceptAny">Don't check the host key</option>
+                                </select>
+                            </div>
+                        )}
+                    </div>
+                )}
+
+                {!isFile && (
+                    <div className="flex gap-2">
+                        <div className="flex-1">
+                            <label className="block text-sm font-medium mb-1">Connect Timeout (s)</label>
+                            <input
+                                type="number"
+                                min={1}
+                                value={connectTimeoutSecs ?? ''}
+                                onChange={(e) => setConnectTimeoutSecs(e.target.value ? Number(e.target.value) : undefined)}
+                                placeholder="15"
+                                className="w-full px-3 py-2 border rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                            />
+                        </div>
+                        <div className="flex-1">
+                            <label className="block text-sm font-medium mb-1">Query Timeout (s)</label>
+                            <input
+                                type="number"
+                                min={1}
+                                value={queryTimeoutSecs ?? ''}
+                                onChange={(e) => setQueryTimeoutSecs(e.target.value ? Number(e.target.value) : undefined)}
+                                placeholder="120"
+                                className="w-full px-3 py-2 border rounded-lg focus:ring-2 focus:ring-blue-500 focus:border-transparent"
+                            />
+                        </div>
+                    </div>
+                )}
+
                 {error && (
                     <div className="p-3 bg-red-100 text-red-700 rounded-lg text-sm">
                         {error}
@@ -195,7 +475,7 @@ export function ConnectionForm({ onConnectionSaved, existingConnection, onBack }
                 <div className="flex gap-3">
                     <button
                         onClick={handleTestConnection}
-                        disabled={isLoading || !server || !database}
+                        disabled={isLoading || !isComplete}
                         className="px-4 py-2 bg-gray-600 text-white rounded-lg hover:bg-gray-700 disabled:opacity-50 disabled:cursor-not-allowed"
                     >
                         {isLoading ? 'Testing...' : 'Test Connection'}
@@ -203,7 +483,7 @@ export function ConnectionForm({ onConnectionSaved, existingConnection, onBack }
 
                     <button
                         onClick={handleSaveConnection}
-                        disabled={isLoading || !server || !database}
+                        disabled={isLoading || !isComplete}
                         className="px-4 py-2 bg-blue-600 text-white rounded-lg hover:bg-blue-700 disabled:opacity-50 disabled:cursor-not-allowed"
                     >
                         {isLoading ? 'Saving...' : 'Save & Connect'}
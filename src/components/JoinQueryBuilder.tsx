@@ -152,7 +152,9 @@ function JoinTableItem({
             {/* Join Condition */}
             {table.joinCondition && (
                 <div className="text-xs text-gray-500 dark:text-gray-400 mb-2 pl-2">
-                    ON {table.alias}.{table.joinCondition.toColumn} = {table.joinCondition.fromColumn}
+                    ON {table.joinCondition.columns
+                        .map(c => `${table.alias}.${c.toColumn} = ${c.fromColumn}`)
+                        .join(' AND ')}
                 </div>
             )}
 
@@ -196,7 +198,7 @@ function JoinTableItem({
                                     ? 'bg-blue-500 text-white'
                                     : 'bg-gray-200 dark:bg-gray-700 text-gray-600 dark:text-gray-300 hover:bg-gray-300 dark:hover:bg-gray-600'
                                 }`}
-                            title={`${col.name} (${col.dataType})`}
+                            title={`${col.name} (${col.fullType || col.dataType})`}
                         >
                             {col.isPrimaryKey && '🔑'}
                             {col.isForeignKey && '🔗'}
@@ -14,6 +14,18 @@ interface PreviewPanelProps {
     onCopySQL?: () => void;
 }
 
+interface TableRef {
+    schema?: string;
+    name: string;
+}
+
+// Whether (schema, name) is the given table; a table without a schema matches by name alone
+function isTable(table: TableRef | null, schema: string, name: string): boolean {
+    if (!table) return false;
+    return name.toLowerCase() === table.name.toLowerCase() &&
+        (!table.schema || schema.toLowerCase() === table.schema.toLowerCase());
+}
+
 export function PreviewPanel({
     result,
     columns,
@@ -25,41 +37,35 @@ export function PreviewPanel({
     const { tables: joinTables, addTable, isBuilding, toggleColumn, toggleStar } = useJoinQueryStore();
     const [columnsExpanded, setColumnsExpanded] = useState(false);
 
-    // Get the current table name
-    const currentTableName = useMemo(() => {
+    // Get the current table; the same name may exist in several schemas
+    const currentTable = useMemo((): TableRef | null => {
         if (!result) return null;
-        return result.type === 'table' ? result.name : result.table;
+        const name = result.type === 'table' ? result.name : result.table;
+        return name ? { schema: result.schema, name } : null;
     }, [result]);
+    // The JOIN builder keys tables by name; its default columns come from `tableColumns`
+    const currentTableName = currentTable?.name ?? null;
 
     // Get columns for the selected table
     const tableColumns = useMemo(() => {
-        if (!result) return [];
-        const tableName = result.type === 'table' ? result.name : result.table;
-        if (!tableName) return [];
         return columns.filter(col =>
-            col.tableName.toLowerCase() === tableName.toLowerCase()
+            isTable(currentTable, col.tableSchema, col.tableName)
         ).sort((a, b) => a.ordinalPosition - b.ordinalPosition);
-    }, [result, columns]);
+    }, [currentTable, columns]);
 
     // Get outgoing foreign keys (this table references other tables)
     const outgoingFKs = useMemo(() => {
-        if (!result) return [];
-        const tableName = result.type === 'table' ? result.name : result.table;
-        if (!tableName) return [];
         return foreignKeys.filter(fk =>
-            fk.parentTable.toLowerCase() === tableName.toLowerCase()
+            isTable(currentTable, fk.parentSchema, fk.parentTable)
         );
-    }, [result, foreignKeys]);
+    }, [currentTable, foreignKeys]);
 
     // Get incoming foreign keys (other tables reference this table)
     const incomingFKs = useMemo(() => {
-        if (!result) return [];
-        const tableName = result.type === 'table' ? result.name : result.table;
-        if (!tableName) return [];
         return foreignKeys.filter(fk =>
-            fk.referencedTable.toLowerCase() === tableName.toLowerCase()
+            isTable(currentTable, fk.referencedSchema, fk.referencedTable)
         );
-    }, [result, foreignKeys]);
+    }, [currentTable, foreignKeys]);
 
     // Check if current table is in JOIN builder
     const currentTableInBuilder = useMemo(() => {
@@ -70,7 +76,7 @@ export function PreviewPanel({
     // Start column selection for single table
     const handleStartColumnSelection = () => {
         if (currentTableName && !currentTableInBuilder) {
-            addTable(currentTableName, undefined, columns);
+            addTable(currentTableName, undefined, tableColumns);
         }
     };
 
@@ -79,7 +85,7 @@ export function PreviewPanel({
         if (currentTableName) {
             // If not in builder yet, add it first
             if (!currentTableInBuilder) {
-                addTable(currentTableName, undefined, columns);
+                addTable(currentTableName, undefined, tableColumns);
             }
             toggleColumn(currentTableName, columnName);
         }
@@ -89,7 +95,7 @@ export function PreviewPanel({
     const handleToggleStar = () => {
         if (currentTableName) {
             if (!currentTableInBuilder) {
-                addTable(currentTableName, undefined, columns);
+                addTable(currentTableName, undefined, tableColumns);
             }
             toggleStar(currentTableName);
         }
@@ -102,18 +108,18 @@ export function PreviewPanel({
     }, [result, primaryKeys]);
 
     // Check if a column is a primary key
-    const isPrimaryKey = (columnName: string, tableName: string): boolean => {
+    const isPrimaryKey = (col: Column): boolean => {
         return primaryKeys.some(pk =>
-            pk.columnName.toLowerCase() === columnName.toLowerCase() &&
-            pk.tableName.toLowerCase() === tableName.toLowerCase()
+            pk.columnName.toLowerCase() === col.name.toLowerCase() &&
+            pk.tableName.toLowerCase() === col.tableName.toLowerCase() &&
+            pk.tableSchema.toLowerCase() === col.tableSchema.toLowerCase()
         );
     };
 
     // Check if a column is a foreign key
-    const isForeignKey = (columnName: string, tableName: string): ForeignKey | undefined => {
-        return foreignKeys.find(fk =>
-            fk.parentColumn.toLowerCase() === columnName.toLowerCase() &&
-            fk.parentTable.toLowerCase() === tableName.toLowerCase()
+    const isForeignKey = (col: Column): ForeignKey | undefined => {
+        return outgoingFKs.find(fk =>
+            fk.columns.some(c => c.parentColumn.toLowerCase() === col.name.toLowerCase())
         );
     };
 
@@ -134,7 +140,7 @@ export function PreviewPanel({
 
             // If this is the first table, add current table first
             if (joinTables.length === 0 && currentTableName) {
-                addTable(currentTableName, undefined, columns);
+                addTable(currentTableName, undefined, tableColumns);
             }
 
             // Add the clicked table
@@ -227,12 +233,12 @@ export function PreviewPanel({
                 </div>
                 <div className="space-y-1">
                     {(columnsExpanded ? tableColumns : tableColumns.slice(0, 15)).map((col) => {
-                        const fk = isForeignKey(col.name, col.tableName);
-                        const pk = isPrimaryKey(col.name, col.tableName);
+                        const fk = isForeignKey(col);
+                        const pk = isPrimaryKey(col);
                         const isColSelected = currentTableInBuilder?.selectedColumns.includes(col.name) || currentTableInBuilder?.useStar;
                         return (
                             <div
-                                key={`${col.tableName}.${col.name}`}
+                                key={`${col.tableSchema}.${col.tableName}.${col.name}`}
                                 onClick={() => isBuilding && handleToggleColumn(col.name)}
                                 className={`flex items-center gap-2 text-sm py-1 px-2 rounded transition-colors ${isBuilding ? 'cursor-pointer' : ''
                                     } ${isColSelected && isBuilding
@@ -250,7 +256,7 @@ export function PreviewPanel({
                                     {col.name}
                                 </span>
                                 <span className="text-xs text-gray-400 dark:text-gray-500 truncate">
-                                    {col.dataType}
+                                    {col.fullType || col.dataType}
                                     {!col.isNullable && (
                                         <span className="text-amber-500 ml-1">*</span>
                                     )}
@@ -262,7 +268,7 @@ export function PreviewPanel({
                                             ? 'text-green-500 hover:text-green-600'
                                             : 'text-blue-500 hover:text-blue-600'
                                             }`}
-                                        title={`Click to navigate, Shift+Click to add to JOIN\n${fk.referencedTable}.${fk.referencedColumn}`}
+                                        title={`Click to navigate, Shift+Click to add to JOIN\n${fk.referencedTable}(${fk.columns.map(c => c.referencedColumn).join(', ')})`}
                                     >
                                         → {fk.referencedTable}
                                         {isInJoinChain(fk.referencedTable) && ' ✓'}
@@ -323,7 +329,7 @@ export function PreviewPanel({
                                     )}
                                 </span>
                                 <span className="text-xs text-gray-400 dark:text-gray-500">
-                                    ({fk.parentColumn} → {fk.referencedColumn})
+                                    ({fk.columns.map(c => c.parentColumn).join(', ')} → {fk.columns.map(c => c.referencedColumn).join(', ')})
                                 </span>
                             </button>
                         ))}
@@ -348,7 +354,7 @@ export function PreviewPanel({
                                     )}
                                 </span>
                                 <span className="text-xs text-gray-400 dark:text-gray-500">
-                                    ({fk.parentColumn} → {fk.referencedColumn})
+                                    ({fk.columns.map(c => c.parentColumn).join(', ')} → {fk.columns.map(c => c.referencedColumn).join(', ')})
                                 </span>
                             </button>
                         ))}
@@ -0,0 +1,50 @@
+import { useEffect, useState } from 'react';
+import type { RefreshStatus } from '../lib/types';
+
+interface RefreshIndicatorProps {
+    status: RefreshStatus | null;
+}
+
+export function RefreshIndicator({ status }: RefreshIndicatorProps) {
+    // Re-render every minute so the age stays current
+    const [now, setNow] = useState(() => Date.now());
+    useEffect(() => {
+        const timer = setInterval(() => setNow(Date.now()), 60_000);
+        return () => clearInterval(timer);
+    }, []);
+
+    if (!status) return null;
+
+    if (status.state === 'refreshing') {
+        return (
+            <span className="text-xs text-gray-400 dark:text-gray-500">Refreshing schema…</span>
+        );
+    }
+
+    const age = status.refreshedAt ? `Schema updated ${formatAge(now - Date.parse(status.refreshedAt))}` : null;
+    if (status.state === 'failed') {
+        const retry = status.retryInSecs ? `; retrying in ${formatDuration(status.retryInSecs * 1000)}` : '';
+        return (
+            <span className="text-xs text-amber-600 dark:text-amber-400" title={status.error}>
+                {age ? `${age} · refresh failed${retry}` : `Refresh failed${retry}`}
+            </span>
+        );
+    }
+
+    return age ? (
+        <span className="text-xs text-gray-400 dark:text-gray-500">{age}</span>
+    ) : null;
+}
+
+function formatAge(ms: number): string {
+    return ms < 60_000 ? 'just now' : `${formatDuration(ms)} ago`;
+}
+
+function formatDuration(ms: number): string {
+    const minutes = Math.max(1, Math.round(ms / 60_000));
+    if (minutes < 60) return `${minutes} min`;
+    const hours = Math.round(minutes / 60);
+    if (hours < 24) return `${hours} h`;
+    const days = Math.round(hours / 24);
+    return days === 1 ? '1 day' : `${days} days`;
+}
@@ -1,4 +1,12 @@
-import type { SearchResult } from '../lib/types';
+import type { ObjectKind, SearchResult } from '../lib/types';
+
+const objectKindIcons: Record<ObjectKind, string> = {
+    baseTable: '📋',
+    view: '👁️',
+    synonym: '🔀',
+    tableType: '🧩',
+    externalTable: '🌐',
+};
 
 interface ResultItemProps {
     result: SearchResult;
@@ -8,7 +16,7 @@ interface ResultItemProps {
 }
 
 export function ResultItem({ result, isSelected, onClick, query }: ResultItemProps) {
-    const icon = result.type === 'table' ? '📋' : '📄';
+    const icon = result.type === 'column' ? '📄' : objectKindIcons[result.objectKind ?? 'baseTable'];
 
     // Highlight matching text
     const highlightedName = highlightMatch(result.displayName, query);
@@ -51,6 +59,7 @@ function highlightMatch(text: string, query: string): string {
     let searchTerm = query;
     if (query.startsWith(':col ')) searchTerm = query.slice(5);
     else if (query.startsWith(':table ')) searchTerm = query.slice(7);
+    else if (query.startsWith(':view ')) searchTerm = query.slice(6);
 
     if (!searchTerm) return escapeHtml(text);
 
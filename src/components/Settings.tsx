@@ -1,7 +1,8 @@
 import { useState } from 'react';
 import { useSettingsStore, Theme, CopyBehavior } from '../stores/settingsStore';
 import { useConnectionStore } from '../stores/connectionStore';
-import type { Connection } from '../lib/types';
+import { isLocalProvider, type Connection, type ConnectionStringDialect } from '../lib/types';
+import { copyToClipboard, exportConnectionString } from '../lib/tauri';
 
 type SettingsTab = 'general' | 'connections' | 'appearance' | 'shortcuts';
 
@@ -17,12 +18,18 @@ export function Settings({ onClose, onEditConnection, initialTab = 'general' }:
         rowLimit,
         copyBehavior,
         autoRefreshSchema,
+        refreshIntervalMinutes,
+        refreshOnStart,
+        refreshWhenShownAfterHours,
         hotkey,
         groupsFilePath,
         setTheme,
         setRowLimit,
         setCopyBehavior,
         setAutoRefreshSchema,
+        setRefreshIntervalMinutes,
+        setRefreshOnStart,
+        setRefreshWhenShownAfterHours,
         setGroupsFilePath,
         resetToDefaults,
     } = useSettingsStore();
@@ -83,10 +90,16 @@ export function Settings({ onClose, onEditConnection, initialTab = 'general' }:
                                 rowLimit={rowLimit}
                                 copyBehavior={copyBehavior}
                                 autoRefreshSchema={autoRefreshSchema}
+                                refreshIntervalMinutes={refreshIntervalMinutes}
+                                refreshOnStart={refreshOnStart}
+                                refreshWhenShownAfterHours={refreshWhenShownAfterHours}
                                 groupsFilePath={groupsFilePath}
                                 onRowLimitChange={setRowLimit}
                                 onCopyBehaviorChange={setCopyBehavior}
                                 onAutoRefreshChange={setAutoRefreshSchema}
+                                onRefreshIntervalChange={setRefreshIntervalMinutes}
+                                onRefreshOnStartChange={setRefreshOnStart}
+                                onRefreshWhenShownChange={setRefreshWhenShownAfterHours}
                                 onGroupsFilePathChange={setGroupsFilePath}
                             />
                         )}
@@ -141,10 +154,16 @@ interface GeneralSettingsProps {
     rowLimit: number;
     copyBehavior: CopyBehavior;
     autoRefreshSchema: boolean;
+    refreshIntervalMinutes: number;
+    refreshOnStart: boolean;
+    refreshWhenShownAfterHours: number;
     groupsFilePath: string;
     onRowLimitChange: (limit: number) => void;
     onCopyBehaviorChange: (behavior: CopyBehavior) => void;
     onAutoRefreshChange: (enabled: boolean) => void;
+    onRefreshIntervalChange: (minutes: number) => void;
+    onRefreshOnStartChange: (enabled: boolean) => void;
+    onRefreshWhenShownChange: (hours: number) => void;
     onGroupsFilePathChange: (path: string) => void;
 }
 
@@ -152,10 +171,16 @@ function GeneralSettings({
     rowLimit,
     copyBehavior,
     autoRefreshSchema,
+    refreshIntervalMinutes,
+    refreshOnStart,
+    refreshWhenShownAfterHours,
     groupsFilePath,
     onRowLimitChange,
     onCopyBehaviorChange,
     onAutoRefreshChange,
+    onRefreshIntervalChange,
+    onRefreshOnStartChange,
+    onRefreshWhenShownChange,
     onGroupsFilePathChange,
 }: GeneralSettingsProps) {
     return (
@@ -225,13 +250,54 @@ function GeneralSettings({
                     />
                     <div>
                         <span className="text-sm font-medium text-gray-700 dark:text-gray-300">
-                            Auto-refresh schema on startup
+                            Auto-refresh schema
                         </span>
                         <p className="text-xs text-gray-500 dark:text-gray-400">
-                            Automatically fetch latest schema from database when app starts
+                            Keep every connection's schema up to date in the background, using saved passwords
                         </p>
                     </div>
                 </label>
+                {autoRefreshSchema && (
+                    <div className="ml-7 space-y-2">
+                        <div className="flex items-center gap-2">
+                            <span className="text-sm text-gray-700 dark:text-gray-300">Every</span>
+                            <select
+                                value={refreshIntervalMinutes}
+                                onChange={(e) => onRefreshIntervalChange(Number(e.target.value))}
+                                className="px-2 py-1 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-lg text-sm"
+                            >
+                                <option value={15}>15 minutes</option>
+                                <option value={30}>30 minutes</option>
+                                <option value={60}>hour</option>
+                                <option value={240}>4 hours</option>
+                                <option value={1440}>day</option>
+                            </select>
+                        </div>
+                        <label className="flex items-center gap-2 cursor-pointer">
+                            <input
+                                type="checkbox"
+                                checked={refreshOnStart}
+                                onChange={(e) => onRefreshOnStartChange(e.target.checked)}
+                                className="w-4 h-4 text-blue-600 rounded"
+                            />
+                            <span className="text-sm text-gray-700 dark:text-gray-300">Also when the app starts</span>
+                        </label>
+                        <div className="flex items-center gap-2">
+                            <span className="text-sm text-gray-700 dark:text-gray-300">When the window is shown</span>
+                            <select
+                                value={refreshWhenShownAfterHours}
+                                onChange={(e) => onRefreshWhenShownChange(Number(e.target.value))}
+                                className="px-2 py-1 bg-white dark:bg-gray-700 border border-gray-300 dark:border-gray-600 rounded-lg text-sm"
+                            >
+                                <option value={0}>never</option>
+                                <option value={1}>after 1 hour</option>
+                                <option value={4}>after 4 hours</option>
+                                <option value={12}>after 12 hours</option>
+                                <option value={24}>after a day</option>
+                            </select>
+                        </div>
+                    </div>
+                )}
             </div>
 
             {/* Groups File Path */}
@@ -331,11 +397,31 @@ function ConnectionSettings({
                                             )}
                                         </div>
                                         <p className="text-sm text-gray-500 dark:text-gray-400">
-                                            {conn.server} / {conn.database}
+                                            {isLocalProvider(conn.provider) ? conn.filePath : `${conn.server} / ${conn.database}`}
                                         </p>
                                     </div>
                                 </div>
                                 <div className="flex items-center gap-2">
+                                    {(conn.provider ?? 'sqlServer') === 'sqlServer' && (
+                                        <select
+                                            value=""
+                                            onChange={async (e) => {
+                                                const dialect = e.target.value as ConnectionStringDialect;
+                                                try {
+                                                    await copyToClipboard(await exportConnectionString(conn.id, dialect));
+                                                } catch (err) {
+                                                    console.error('Failed to export connection string:', err);
+                                                }
+                                            }}
+                                            className="px-1 py-1 text-xs bg-transparent text-gray-600 dark:text-gray-400"
+                                            title="Copy a connection string (without password)"
+                                        >
+                                            <option value="" disabled>Copy as…</option>
+                                            <option value="adoNet">ADO.NET</option>
+                                            <option value="jdbc">JDBC</option>
+                                            <option value="odbc">ODBC</option>
+                                        </select>
+                                    )}
                                     {!conn.isDefault && (
                                         <button
                                             onClick={() => onSetDefault(conn.id)}
@@ -1,5 +1,5 @@
 import Fuse from 'fuse.js';
-import type { Table, Column, SearchResult } from './types';
+import type { Table, Column, SearchResult, ObjectKind } from './types';
 
 // Fuse.js configuration for fuzzy search - optimized settings
 const fuseOptions = {
@@ -22,6 +22,8 @@ export interface SearchableItem {
     displayName: string;
     description?: string;
     table?: string;
+    schema?: string;
+    objectKind?: ObjectKind;
 }
 
 // Cache for Fuse instances to avoid re-indexing
@@ -43,6 +45,8 @@ export function createSearchableItems(tables: Table[], columns: Column[]): Searc
             searchName: createSearchableName(table.name),
             displayName: table.schema ? `${table.schema}.${table.name}` : table.name,
             description: table.description,
+            schema: table.schema,
+            objectKind: table.objectKind,
         });
     }
 
@@ -54,6 +58,7 @@ export function createSearchableItems(tables: Table[], columns: Column[]): Searc
             displayName: `${column.tableName}.${column.name}`,
             description: column.description,
             table: column.tableName,
+            schema: column.tableSchema,
         });
     }
 
@@ -84,6 +89,9 @@ export function parseSearchQuery(query: string): { prefix: string | null; term:
     if (trimmed.startsWith(':table ')) {
         return { prefix: 'table', term: trimmed.slice(7) };
     }
+    if (trimmed.startsWith(':view ')) {
+        return { prefix: 'view', term: trimmed.slice(6) };
+    }
 
     return { prefix: null, term: trimmed };
 }
@@ -118,6 +126,8 @@ export function searchSchema(
             filtered = allItems.filter(i => i.type === 'column');
         } else if (prefix === 'table') {
             filtered = allItems.filter(i => i.type === 'table');
+        } else if (prefix === 'view') {
+            filtered = allItems.filter(i => i.objectKind === 'view');
         }
 
         // Fast substring matching
@@ -137,6 +147,8 @@ export function searchSchema(
                     displayName: item.displayName,
                     description: item.description,
                     table: item.table,
+                    schema: item.schema,
+                    objectKind: item.objectKind,
                     score,
                 };
             })
@@ -153,6 +165,8 @@ export function searchSchema(
         results = results.filter(r => r.item.type === 'column');
     } else if (prefix === 'table') {
         results = results.filter(r => r.item.type === 'table');
+    } else if (prefix === 'view') {
+        results = results.filter(r => r.item.objectKind === 'view');
     }
 
     // Convert to SearchResult format
@@ -179,6 +193,8 @@ export function searchSchema(
             displayName: item.displayName,
             description: item.description,
             table: item.table,
+            schema: item.schema,
+            objectKind: item.objectKind,
             score,
         };
     });
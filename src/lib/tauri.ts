@@ -1,15 +1,13 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { Connection, SchemaData, AppConfig, Settings } from './types';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { Connection, ConnectionStringDialect, ConnectionTestResult, ImportedConnection, RefreshStatus, Routine, SchemaData, SchemaDiff, SchemaRef, SchemaSnapshot, AppConfig, Settings } from './types';
 
 // Connection commands
 export async function testConnection(
-    server: string,
-    database: string,
-    authType: 'windows' | 'sql',
-    username?: string,
+    connection: Connection,
     password?: string
-): Promise<string> {
-    return invoke('test_connection', { server, database, authType, username, password });
+): Promise<ConnectionTestResult> {
+    return invoke('test_connection', { connection, password });
 }
 
 export async function saveConnection(connection: Connection): Promise<void> {
@@ -28,17 +26,75 @@ export async function setDefaultConnection(connectionId: string): Promise<void>
     return invoke('set_default_connection', { connectionId });
 }
 
+export async function parseConnectionString(connectionString: string): Promise<ImportedConnection> {
+    return invoke('parse_connection_string', { connectionString });
+}
+
+export async function exportConnectionString(
+    connectionId: string,
+    dialect: ConnectionStringDialect,
+    includePassword = false
+): Promise<string> {
+    return invoke('export_connection_string', { connectionId, dialect, includePassword });
+}
+
+export async function savePassword(connectionId: string, password: string): Promise<void> {
+    return invoke('save_password', { connectionId, password });
+}
+
+export async function deleteSavedPassword(connectionId: string): Promise<void> {
+    return invoke('delete_saved_password', { connectionId });
+}
+
+export async function hasSavedPassword(connectionId: string): Promise<boolean> {
+    return invoke('has_saved_password', { connectionId });
+}
+
 // Schema commands
-export async function indexSchema(connectionId: string, password?: string): Promise<SchemaData> {
-    return invoke('index_schema', { connectionId, password });
+export async function indexSchema(connectionId: string, password?: string, full?: boolean): Promise<SchemaData> {
+    return invoke('index_schema', { connectionId, password, full });
+}
+
+export async function cancelIndexing(connectionId: string): Promise<boolean> {
+    return invoke('cancel_indexing', { connectionId });
+}
+
+export async function getSchema(connectionId?: string): Promise<SchemaData> {
+    return invoke('get_schema', { connectionId });
+}
+
+export async function listSchemaSnapshots(connectionId: string): Promise<SchemaSnapshot[]> {
+    return invoke('list_schema_snapshots', { connectionId });
+}
+
+export async function getSchemaSnapshot(snapshotId: number): Promise<SchemaData> {
+    return invoke('get_schema_snapshot', { snapshotId });
+}
+
+/** Compare two cached schemas, reporting what changed going from before to after */
+export async function diffSchemas(before: SchemaRef, after: SchemaRef): Promise<SchemaDiff> {
+    return invoke('diff_schemas', { before, after });
+}
+
+/** Delete snapshots older than maxAgeDays or beyond the newest maxCount; resolves to how many were deleted */
+export async function pruneSchemaSnapshots(connectionId: string, maxAgeDays?: number, maxCount?: number): Promise<number> {
+    return invoke('prune_schema_snapshots', { connectionId, maxAgeDays, maxCount });
+}
+
+export async function refreshSchema(connectionId: string, full?: boolean): Promise<SchemaData> {
+    return invoke('refresh_schema', { connectionId, full });
+}
+
+export async function getRoutines(connectionId?: string): Promise<Routine[]> {
+    return invoke('get_routines', { connectionId });
 }
 
-export async function getSchema(): Promise<SchemaData> {
-    return invoke('get_schema');
+export async function getRefreshStatus(connectionId: string): Promise<RefreshStatus> {
+    return invoke('get_refresh_status', { connectionId });
 }
 
-export async function refreshSchema(connectionId: string): Promise<SchemaData> {
-    return invoke('refresh_schema', { connectionId });
+export async function onSchemaRefresh(handler: (status: RefreshStatus) => void): Promise<UnlistenFn> {
+    return listen<RefreshStatus>('schema-refresh', (event) => handler(event.payload));
 }
 
 // Clipboard commands
@@ -2,11 +2,77 @@
 export interface Connection {
     id: string;
     name: string;
+    provider?: Provider; // Defaults to 'sqlServer'
     server: string;
     database: string;
-    authType: 'windows' | 'sql';
+    filePath?: string; // SQLite file or DDL script folder, instead of server and database
+    port?: number;
+    authType: AuthType;
     username?: string;
+    accessTokenFile?: string; // Azure AD token auth: file holding the token
+    accessTokenCommand?: string; // ...or a command printing it, e.g. az account get-access-token
     isDefault: boolean;
+    encryption?: EncryptionMode;
+    trustServerCertificate?: boolean;
+    caCertificatePath?: string;
+    pinnedCertificatePath?: string; // PostgreSQL and MySQL only
+    readOnlyIntent?: boolean; // ApplicationIntent=ReadOnly
+    sshTunnel?: SshTunnel;
+    connectTimeoutSecs?: number; // Defaults to 15
+    queryTimeoutSecs?: number; // Per catalog query while indexing; defaults to 120
+    connectRetries?: number; // Retries after network failures; defaults to 2
+}
+
+/** SSH bastion a connection is forwarded through (SQL Server only) */
+export interface SshTunnel {
+    host: string;
+    port?: number; // Defaults to 22
+    user: string;
+    keyPath?: string; // Without one, the SSH agent is used
+    knownHosts?: KnownHostsPolicy;
+}
+
+export type KnownHostsPolicy = 'strict' | 'acceptNew' | 'acceptAny';
+
+export type ConnectionStringDialect = 'adoNet' | 'jdbc' | 'odbc';
+
+/** A connection read from a pasted connection string, not yet saved */
+export interface ImportedConnection {
+    connection: Connection;
+    password?: string;
+    dialect: ConnectionStringDialect;
+    options: Record<string, string>; // Keywords Glance doesn't use
+}
+
+export type AuthType = 'sql' | 'windows' | 'kerberos' | 'azureToken' | 'none';
+
+export type Provider = 'sqlServer' | 'postgres' | 'mysql' | 'sqlite' | 'ddlScripts' | 'dacpac';
+
+/** Providers that read a local file or folder instead of a server */
+export const isLocalProvider = (provider?: Provider) => provider === 'sqlite' || provider === 'ddlScripts' || provider === 'dacpac';
+
+/** 'strict' is not available for SQL Server, where it would need TDS 8.0 */
+export type EncryptionMode = 'off' | 'on' | 'required' | 'strict';
+
+export type ConnectErrorKind =
+    | 'configuration'
+    | 'dns'
+    | 'tcpRefused'
+    | 'network'
+    | 'tls'
+    | 'loginFailed'
+    | 'databaseNotFound'
+    | 'ssh'
+    | 'other';
+
+export interface ConnectionTestResult {
+    success: boolean;
+    message: string;
+    latencyMs: number;
+    serverVersion?: string;
+    databaseName?: string;
+    loginName?: string;
+    errorCategory?: ConnectErrorKind;
 }
 
 // Schema types
@@ -15,31 +81,126 @@ export interface Table {
     name: string;
     description?: string;
     tips?: string;
+    objectKind: ObjectKind;
+    definition?: string; // View SQL
+    synonymTarget?: string;
+    extendedProperties: Record<string, string>; // Besides MS_Description
 }
 
+export type ObjectKind = 'baseTable' | 'view' | 'synonym' | 'tableType' | 'externalTable';
+
 export interface Column {
     tableSchema: string;
     tableName: string;
     name: string;
     dataType: string;
+    fullType: string; // e.g. "nvarchar(50)", "decimal(18,4)"
     isNullable: boolean;
     isPrimaryKey: boolean;
     isForeignKey: boolean;
     ordinalPosition: number;
     description?: string;
+    extendedProperties: Record<string, string>;
+    maxLength?: number; // -1 means max
+    precision?: number;
+    scale?: number;
+    collation?: string;
+    defaultValue?: string;
+    identitySeed?: number;
+    identityIncrement?: number;
+    computedDefinition?: string;
+    isSparse: boolean;
+    isRowguid: boolean;
+    isFilestream: boolean;
 }
 
 export interface ForeignKey {
     constraintName: string;
+    parentSchema: string;
     parentTable: string;
-    parentColumn: string;
+    referencedSchema: string;
     referencedTable: string;
+    columns: ForeignKeyColumn[]; // In key order; several for composite keys
+    deleteReferentialAction: string;
+    updateReferentialAction: string;
+    isDisabled: boolean;
+    isNotTrusted: boolean;
+}
+
+export interface ForeignKeyColumn {
+    parentColumn: string;
     referencedColumn: string;
 }
 
 export interface PrimaryKey {
+    tableSchema: string;
+    tableName: string;
+    columnName: string;
+}
+
+export interface Index {
+    tableSchema: string;
+    tableName: string;
+    name: string;
+    indexType: string;
+    isUnique: boolean;
+    isPrimaryKey: boolean;
+    isUniqueConstraint: boolean;
+    keyColumns: IndexColumn[];
+    includedColumns: string[];
+    filterDefinition?: string;
+}
+
+export interface IndexColumn {
+    name: string;
+    isDescending: boolean;
+}
+
+export interface UniqueConstraint {
+    tableSchema: string;
+    tableName: string;
+    constraintName: string;
+    columns: string[];
+}
+
+export interface CheckConstraint {
+    tableSchema: string;
+    tableName: string;
+    constraintName: string;
+    columnName?: string;
+    definition: string;
+    isDisabled: boolean;
+    isNotTrusted: boolean;
+}
+
+export interface DefaultConstraint {
+    tableSchema: string;
     tableName: string;
     columnName: string;
+    constraintName: string;
+    definition: string;
+}
+
+export type RoutineKind = 'storedProcedure' | 'scalarFunction' | 'tableValuedFunction' | 'trigger';
+
+export interface Routine {
+    schema: string;
+    name: string;
+    routineKind: RoutineKind;
+    description?: string;
+    extendedProperties: Record<string, string>;
+    parentSchema?: string; // For triggers
+    parentTable?: string;
+    definition?: string;
+    parameters: RoutineParameter[];
+}
+
+export interface RoutineParameter {
+    name: string;
+    dataType: string;
+    direction: 'in' | 'out' | 'inOut' | 'return';
+    defaultValue?: string;
+    ordinalPosition: number;
 }
 
 // Search types
@@ -51,6 +212,8 @@ export interface SearchResult {
     displayName: string;
     description?: string;
     table?: string; // For columns, the parent table
+    schema?: string; // Schema of the table, or of a column's table
+    objectKind?: ObjectKind; // For tables
     score: number;
 }
 
@@ -60,6 +223,86 @@ export interface SchemaData {
     columns: Column[];
     foreignKeys: ForeignKey[];
     primaryKeys: PrimaryKey[];
+    indexes: Index[];
+    uniqueConstraints: UniqueConstraint[];
+    checkConstraints: CheckConstraint[];
+    defaultConstraints: DefaultConstraint[];
+    routines: Routine[];
+}
+
+/** A past state of a connection's schema kept in the cache */
+export interface SchemaSnapshot {
+    id: number;
+    connectionId: string;
+    takenAt: string; // When this schema was first read (UTC)
+    lastSeenAt: string; // Latest refresh that found it unchanged
+    serverVersion?: string;
+    contentHash: string;
+    tableCount: number;
+    columnCount: number;
+}
+
+/** Background refresh of a connection's schema, as sent with the `schema-refresh` event */
+export interface RefreshStatus {
+    connectionId: string;
+    state: 'idle' | 'refreshing' | 'updated' | 'failed';
+    refreshedAt?: string; // When the cached schema was last refreshed (UTC)
+    error?: string;
+    retryInSecs?: number; // Until a failed refresh is tried again
+}
+
+/** A cached schema to compare: a connection's latest, or a snapshot */
+export type SchemaRef =
+    | { kind: 'connection'; connectionId: string }
+    | { kind: 'snapshot'; snapshotId: number };
+
+export type ChangeKind = 'added' | 'removed' | 'renamed' | 'modified';
+
+export type ColumnAspect = 'type' | 'nullability' | 'default' | 'identity' | 'computed' | 'collation';
+
+export interface ColumnChange {
+    name: string;
+    change: ChangeKind;
+    before?: string; // Declaration, e.g. "nvarchar(50) NOT NULL"
+    after?: string;
+    aspects: ColumnAspect[];
+}
+
+/** A key, index or constraint that differs */
+export interface ObjectChange {
+    name: string;
+    change: ChangeKind;
+    before?: string;
+    after?: string;
+}
+
+export interface TableDiff {
+    schema: string;
+    name: string;
+    objectKind: ObjectKind;
+    change: ChangeKind;
+    renamedFrom?: string;
+    columns: ColumnChange[];
+    primaryKey?: ObjectChange;
+    foreignKeys: ObjectChange[];
+    indexes: ObjectChange[];
+    checkConstraints: ObjectChange[];
+    definitionChanged: boolean;
+}
+
+export interface RoutineDiff {
+    schema: string;
+    name: string;
+    routineKind: RoutineKind;
+    change: ChangeKind;
+    before?: string;
+    after?: string;
+}
+
+export interface SchemaDiff {
+    tables: TableDiff[];
+    routines: RoutineDiff[];
+    report: string; // Plain text, one line per change
 }
 
 // Settings
@@ -70,6 +313,9 @@ export interface Settings {
     autoRefresh: boolean;
     copyBehavior: 'copyOnly' | 'copyAndClose';
     groupsFilePath?: string;
+    refreshIntervalMinutes?: number; // Default 60
+    refreshOnStart?: boolean; // Default false
+    refreshWhenShownAfterHours?: number; // Unset: never
 }
 
 // Groups configuration
@@ -1,6 +1,6 @@
 import { create } from 'zustand';
-import type { Connection, SchemaData } from '../lib/types';
-import { getConnections, saveConnection as saveConnectionApi, deleteConnection as deleteConnectionApi, setDefaultConnection as setDefaultConnectionApi, indexSchema } from '../lib/tauri';
+import type { Connection, RefreshStatus, SchemaData } from '../lib/types';
+import { getConnections, saveConnection as saveConnectionApi, deleteConnection as deleteConnectionApi, setDefaultConnection as setDefaultConnectionApi, indexSchema, cancelIndexing as cancelIndexingApi, getSchema, getRefreshStatus } from '../lib/tauri';
 
 interface ConnectionState {
     connections: Connection[];
@@ -10,6 +10,7 @@ interface ConnectionState {
     isIndexing: boolean;
     indexingProgress: number;
     schema: SchemaData | null;
+    refreshStatus: RefreshStatus | null; // Of the current connection
     error: string | null;
 
     // Actions
@@ -19,6 +20,9 @@ interface ConnectionState {
     deleteConnection: (connectionId: string) => Promise<void>;
     setDefaultConnection: (connectionId: string) => Promise<void>;
     connect: (connection: Connection, password?: string) => Promise<void>;
+    cancelIndexing: () => Promise<void>;
+    loadRefreshStatus: () => Promise<void>;
+    applyRefreshStatus: (status: RefreshStatus) => Promise<void>;
     disconnect: () => void;
     clearError: () => void;
 }
@@ -31,6 +35,7 @@ export const useConnectionStore = create<ConnectionState>((set, get) => ({
     isIndexing: false,
     indexingProgress: 0,
     schema: null,
+    refreshStatus: null,
     error: null,
 
     loadConnections: async () => {
@@ -121,21 +126,61 @@ export const useConnectionStore = create<ConnectionState>((set, get) => ({
                 isIndexing: false,
                 indexingProgress: 100,
             });
+            get().loadRefreshStatus();
         } catch (err) {
             console.error('Error indexing schema:', err);
             set({
-                error: err as string,
+                // A cancelled run keeps whatever schema was shown before
+                error: err === 'Indexing cancelled' ? null : err as string,
                 isConnecting: false,
                 isIndexing: false
             });
         }
     },
 
+    cancelIndexing: async () => {
+        const connection = get().currentConnection;
+        if (!connection) return;
+
+        try {
+            await cancelIndexingApi(connection.id);
+        } catch (err) {
+            set({ error: err as string });
+        }
+    },
+
+    loadRefreshStatus: async () => {
+        const connection = get().currentConnection;
+        if (!connection) return;
+
+        try {
+            set({ refreshStatus: await getRefreshStatus(connection.id) });
+        } catch (err) {
+            console.error('Failed to load refresh status:', err);
+        }
+    },
+
+    // Called for each `schema-refresh` event from the background refresh
+    applyRefreshStatus: async (status) => {
+        if (status.connectionId !== get().currentConnection?.id) return;
+
+        set({ refreshStatus: status });
+        if (status.state === 'updated' && !get().isIndexing) {
+            try {
+                const schema = await getSchema(status.connectionId);
+                set({ schema });
+            } catch (err) {
+                console.error('Failed to load refreshed schema:', err);
+            }
+        }
+    },
+
     disconnect: () => {
         set({
             isConnected: false,
             currentConnection: null,
             schema: null,
+            refreshStatus: null,
             indexingProgress: 0
         });
     },
@@ -8,9 +8,8 @@ export interface JoinTable {
     useStar: boolean; // Use table.* instead of individual columns
     joinCondition?: {
         fromTable: string;
-        fromColumn: string;
         toTable: string;
-        toColumn: string;
+        columns: { fromColumn: string; toColumn: string }[];
     };
 }
 
@@ -90,9 +89,11 @@ export const useJoinQueryStore = create<JoinQueryState>((set, get) => ({
             useStar: false,
             joinCondition: foreignKey ? {
                 fromTable: foreignKey.parentTable,
-                fromColumn: foreignKey.parentColumn,
                 toTable: foreignKey.referencedTable,
-                toColumn: foreignKey.referencedColumn,
+                columns: foreignKey.columns.map(c => ({
+                    fromColumn: c.parentColumn,
+                    toColumn: c.referencedColumn,
+                })),
             } : undefined,
         };
 
@@ -208,7 +209,7 @@ export const useJoinQueryStore = create<JoinQueryState>((set, get) => ({
         for (let i = 1; i < tables.length; i++) {
             const table = tables[i];
             if (table.joinCondition) {
-                const { fromTable, fromColumn, toTable, toColumn } = table.joinCondition;
+                const { fromTable, toTable, columns } = table.joinCondition;
 
                 // Find aliases
                 const fromAlias = tables.find(t =>
@@ -218,16 +219,13 @@ export const useJoinQueryStore = create<JoinQueryState>((set, get) => ({
                     t.name.toLowerCase() === toTable.toLowerCase()
                 )?.alias || toTable;
 
-                // Determine which side is this table
-                if (table.name.toLowerCase() === toTable.toLowerCase()) {
-                    joinClauses.push(
-                        `JOIN ${table.name} ${table.alias} ON ${table.alias}.${toColumn} = ${fromAlias}.${fromColumn}`
-                    );
-                } else {
-                    joinClauses.push(
-                        `JOIN ${table.name} ${table.alias} ON ${table.alias}.${fromColumn} = ${toAlias}.${toColumn}`
-                    );
-                }
+                // Determine which side is this table; composite keys join on every column pair
+                const conditions = table.name.toLowerCase() === toTable.toLowerCase()
+                    ? columns.map(c => `${table.alias}.${c.toColumn} = ${fromAlias}.${c.fromColumn}`)
+                    : columns.map(c => `${table.alias}.${c.fromColumn} = ${toAlias}.${c.toColumn}`);
+                joinClauses.push(
+                    `JOIN ${table.name} ${table.alias} ON ${conditions.join(' AND ')}`
+                );
             } else {
                 // No join condition, just add the table (cross join)
                 joinClauses.push(`CROSS JOIN ${table.name} ${table.alias}`);
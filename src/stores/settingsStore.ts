@@ -1,5 +1,6 @@
 import { create } from 'zustand';
 import { persist } from 'zustand/middleware';
+import { getSettings, saveSettings } from '../lib/tauri';
 
 export type Theme = 'light' | 'dark' | 'system';
 export type CopyBehavior = 'copyOnly' | 'copyAndClose';
@@ -12,6 +13,9 @@ export interface Settings {
     rowLimit: number;
     copyBehavior: CopyBehavior;
     autoRefreshSchema: boolean;
+    refreshIntervalMinutes: number;
+    refreshOnStart: boolean;
+    refreshWhenShownAfterHours: number; // 0 = never
 
     // Hotkey
     hotkey: string;
@@ -26,6 +30,9 @@ interface SettingsState extends Settings {
     setRowLimit: (limit: number) => void;
     setCopyBehavior: (behavior: CopyBehavior) => void;
     setAutoRefreshSchema: (enabled: boolean) => void;
+    setRefreshIntervalMinutes: (minutes: number) => void;
+    setRefreshOnStart: (enabled: boolean) => void;
+    setRefreshWhenShownAfterHours: (hours: number) => void;
     setHotkey: (hotkey: string) => void;
     setGroupsFilePath: (path: string) => void;
     resetToDefaults: () => void;
@@ -36,13 +43,16 @@ const defaultSettings: Settings = {
     rowLimit: 100,
     copyBehavior: 'copyAndClose',
     autoRefreshSchema: false,
+    refreshIntervalMinutes: 60,
+    refreshOnStart: false,
+    refreshWhenShownAfterHours: 0,
     hotkey: 'CommandOrControl+Shift+Space',
     groupsFilePath: '',
 };
 
 export const useSettingsStore = create<SettingsState>()(
     persist(
-        (set) => ({
+        (set, get) => ({
             ...defaultSettings,
 
             setTheme: (theme) => {
@@ -54,7 +64,25 @@ export const useSettingsStore = create<SettingsState>()(
 
             setCopyBehavior: (copyBehavior) => set({ copyBehavior }),
 
-            setAutoRefreshSchema: (autoRefreshSchema) => set({ autoRefreshSchema }),
+            setAutoRefreshSchema: (autoRefreshSchema) => {
+                set({ autoRefreshSchema });
+                syncRefreshSettings(get());
+            },
+
+            setRefreshIntervalMinutes: (refreshIntervalMinutes) => {
+                set({ refreshIntervalMinutes });
+                syncRefreshSettings(get());
+            },
+
+            setRefreshOnStart: (refreshOnStart) => {
+                set({ refreshOnStart });
+                syncRefreshSettings(get());
+            },
+
+            setRefreshWhenShownAfterHours: (refreshWhenShownAfterHours) => {
+                set({ refreshWhenShownAfterHours });
+                syncRefreshSettings(get());
+            },
 
             setHotkey: (hotkey) => set({ hotkey }),
 
@@ -63,6 +91,7 @@ export const useSettingsStore = create<SettingsState>()(
             resetToDefaults: () => {
                 set(defaultSettings);
                 applyTheme(defaultSettings.theme);
+                syncRefreshSettings(defaultSettings);
             },
         }),
         {
@@ -72,11 +101,30 @@ export const useSettingsStore = create<SettingsState>()(
                 if (state?.theme) {
                     applyTheme(state.theme);
                 }
+                if (state) {
+                    syncRefreshSettings(state);
+                }
             },
         }
     )
 );
 
+// Background refreshes are scheduled by the backend, which keeps its own copy of these
+async function syncRefreshSettings(settings: Settings) {
+    try {
+        const saved = await getSettings();
+        await saveSettings({
+            ...saved,
+            autoRefresh: settings.autoRefreshSchema,
+            refreshIntervalMinutes: settings.refreshIntervalMinutes,
+            refreshOnStart: settings.refreshOnStart,
+            refreshWhenShownAfterHours: settings.refreshWhenShownAfterHours || undefined,
+        });
+    } catch (err) {
+        console.error('Failed to save refresh settings:', err);
+    }
+}
+
 // Apply theme to document
 function applyTheme(theme: Theme) {
     const root = document.documentElement;